@@ -0,0 +1,635 @@
+use anyhow::Result;
+
+fn main() -> Result<()> {
+    println!("cargo:rerun-if-changed=proto");
+    println!("cargo:rerun-if-changed=data");
+    println!("cargo:rerun-if-env-changed=JSON_SCHEMA_OUT_DIR");
+
+    annotations::generate()?;
+
+    #[cfg(feature = "json-schema")]
+    json_schema::generate()?;
+
+    Ok(())
+}
+
+/// Turns each chain's `data/annotations*.json` into sorted static tables in
+/// `$OUT_DIR/annotations.rs`, so enrichment lookups are binary searches inside
+/// the WASM instead of RPC calls.
+mod annotations {
+    use anyhow::{bail, Context, Result};
+    use serde_json::Value;
+    use std::fmt::Write;
+    use std::path::PathBuf;
+
+    /// `(static name, source)` per chain profile; Polygon's list predates the
+    /// profiles and keeps the plain name.
+    const SOURCES: &[(&str, &str)] = &[
+        ("POLYGON", "data/annotations.json"),
+        ("BNB", "data/annotations.bnb.json"),
+        ("ARBITRUM", "data/annotations.arbitrum.json"),
+    ];
+
+    pub fn generate() -> Result<()> {
+        let mut out = String::from("// @generated by build.rs from data/annotations*.json\n");
+        for (name, source) in SOURCES {
+            writeln!(out)?;
+            tables(&mut out, name, source)?;
+        }
+        let path = PathBuf::from(std::env::var("OUT_DIR")?).join("annotations.rs");
+        std::fs::write(path, out)?;
+        Ok(())
+    }
+
+    fn tables(out: &mut String, name: &str, source: &'static str) -> Result<()> {
+        let raw = std::fs::read_to_string(source).with_context(|| format!("reading {}", source))?;
+        let root: Value =
+            serde_json::from_str(&raw).with_context(|| format!("parsing {}", source))?;
+
+        writeln!(out, "/// Tables of `{}`, each sorted by address.", source)?;
+        writeln!(out, "pub static {}: super::Tables = super::Tables {{", name)?;
+
+        // Decimals of every listed token, which equivalent assets must share.
+        let mut listed = std::collections::BTreeMap::new();
+        out.push_str("    tokens: &[\n");
+        for (address, token) in section(&root, "tokens", source)? {
+            let symbol = string(token, "symbol", address, source)?;
+            let Some(decimals) = token
+                .get("decimals")
+                .and_then(Value::as_u64)
+                .filter(|d| *d <= 77)
+            else {
+                bail!("{}: token {} needs integer decimals <= 77", source, address);
+            };
+            listed.insert(address.as_str(), decimals);
+            let peg = match token.get("peg") {
+                None => "",
+                Some(_) => string(token, "peg", address, source)?,
+            };
+            writeln!(
+                out,
+                "        ({:?}, {:?}, {}, {:?}),",
+                address, symbol, decimals, peg
+            )?;
+        }
+        out.push_str("    ],\n");
+
+        out.push_str("    equivalents: &[\n");
+        for (address, asset) in section(&root, "equivalents", source)? {
+            let Some(asset) = asset.as_str() else {
+                bail!("{}: equivalent of {} must be a string", source, address);
+            };
+            // The native asset has 18 decimals on every supported chain.
+            let asset_decimals = match asset {
+                "native" => Some(18),
+                token => listed.get(token).copied(),
+            };
+            if !listed.contains_key(address.as_str()) || asset_decimals.is_none() {
+                bail!(
+                    "{}: equivalent {} -> {} needs both listed as tokens (or \"native\")",
+                    source,
+                    address,
+                    asset
+                );
+            }
+            if listed.get(address.as_str()).copied() != asset_decimals {
+                bail!(
+                    "{}: equivalent {} -> {} must have the same decimals",
+                    source,
+                    address,
+                    asset
+                );
+            }
+            writeln!(out, "        ({:?}, {:?}),", address, asset)?;
+        }
+        out.push_str("    ],\n");
+
+        out.push_str("    protocols: &[\n");
+        for (address, protocol) in section(&root, "protocols", source)? {
+            let protocol_name = string(protocol, "protocol", address, source)?;
+            let version = string(protocol, "version", address, source)?;
+            writeln!(
+                out,
+                "        ({:?}, {:?}, {:?}),",
+                address, protocol_name, version
+            )?;
+        }
+        out.push_str("    ],\n");
+
+        out.push_str("    labels: &[\n");
+        for (address, labels) in section(&root, "labels", source)? {
+            let Some(labels) = labels.as_array() else {
+                bail!("{}: labels for {} must be an array", source, address);
+            };
+            let labels: Vec<&str> = labels
+                .iter()
+                .map(|l| {
+                    l.as_str().with_context(|| {
+                        format!("{}: label for {} must be a string", source, address)
+                    })
+                })
+                .collect::<Result<_>>()?;
+            writeln!(out, "        ({:?}, &{:?}),", address, labels)?;
+        }
+        out.push_str("    ],\n};\n");
+        Ok(())
+    }
+
+    /// Entries of one top-level object sorted by address, which the lookups'
+    /// binary search relies on.
+    fn section<'a>(
+        root: &'a Value,
+        name: &str,
+        source: &str,
+    ) -> Result<Vec<(&'a String, &'a Value)>> {
+        let Some(section) = root.get(name).and_then(Value::as_object) else {
+            bail!("{}: missing object {:?}", source, name);
+        };
+        let mut entries: Vec<(&String, &Value)> = section.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        for (address, _) in entries.iter() {
+            let valid = address.len() == 42
+                && address.starts_with("0x")
+                && address[2..]
+                    .bytes()
+                    .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+            if !valid {
+                bail!(
+                    "{}: {:?} is not a lowercase 0x-prefixed address",
+                    source,
+                    address
+                );
+            }
+        }
+        Ok(entries)
+    }
+
+    fn string<'a>(entry: &'a Value, field: &str, address: &str, source: &str) -> Result<&'a str> {
+        entry
+            .get(field)
+            .and_then(Value::as_str)
+            .with_context(|| format!("{}: {} needs a string {:?}", source, address, field))
+    }
+}
+
+/// Emits one JSON Schema document per proto message, following the canonical
+/// proto3 JSON mapping (lowerCamelCase names, 64-bit integers as strings,
+/// bytes as base64, enums by name) so consumers can generate types from the
+/// same shape the sinks see on the wire.
+#[cfg(feature = "json-schema")]
+mod json_schema {
+    use anyhow::{anyhow, bail, Context, Result};
+    use serde_json::{json, Map, Value};
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+
+    struct Field {
+        name: String,
+        ty: String,
+        repeated: bool,
+        map_types: Option<(String, String)>,
+        scope: String,
+        description: Option<String>,
+    }
+
+    struct Message {
+        fields: Vec<Field>,
+    }
+
+    #[derive(Default)]
+    struct Schema {
+        package: String,
+        messages: BTreeMap<String, Message>,
+        enums: BTreeMap<String, Vec<String>>,
+    }
+
+    pub fn generate() -> Result<()> {
+        let out_dir = match std::env::var_os("JSON_SCHEMA_OUT_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var("OUT_DIR")?).join("json-schema"),
+        };
+        std::fs::create_dir_all(&out_dir)?;
+
+        let mut schema = Schema::default();
+        for entry in std::fs::read_dir("proto")? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("proto") {
+                parse_file(&path, &mut schema)
+                    .with_context(|| format!("parsing {}", path.display()))?;
+            }
+        }
+
+        for name in schema.messages.keys() {
+            let document = document_for(&schema, name)?;
+            let file = out_dir.join(format!("{}.schema.json", name));
+            std::fs::write(&file, serde_json::to_string_pretty(&document)? + "\n")?;
+        }
+
+        println!(
+            "cargo:warning=wrote {} JSON schemas to {}",
+            schema.messages.len(),
+            out_dir.display()
+        );
+        Ok(())
+    }
+
+    fn document_for(schema: &Schema, root: &str) -> Result<Value> {
+        let mut defs = Map::new();
+        let mut pending = vec![root.to_string()];
+        while let Some(name) = pending.pop() {
+            if defs.contains_key(&name) {
+                continue;
+            }
+            let message = &schema.messages[&name];
+            defs.insert(name.clone(), message_schema(schema, message, &mut pending)?);
+        }
+
+        let root_def = defs.remove(root).expect("root message is always defined");
+        let mut document = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": format!("{}.{}", schema.package, root),
+            "title": root,
+        });
+        let object = document.as_object_mut().expect("document is an object");
+        if let Value::Object(fields) = root_def {
+            object.extend(fields);
+        }
+        if !defs.is_empty() {
+            object.insert("$defs".to_string(), Value::Object(defs));
+        }
+        Ok(document)
+    }
+
+    fn message_schema(
+        schema: &Schema,
+        message: &Message,
+        pending: &mut Vec<String>,
+    ) -> Result<Value> {
+        let mut properties = Map::new();
+        for field in &message.fields {
+            let mut value = match &field.map_types {
+                Some((_, value_ty)) => json!({
+                    "type": "object",
+                    "additionalProperties": type_schema(schema, value_ty, &field.scope, pending)?,
+                }),
+                None => type_schema(schema, &field.ty, &field.scope, pending)?,
+            };
+            if field.repeated {
+                value = json!({ "type": "array", "items": value });
+            }
+            if let Some(description) = &field.description {
+                value
+                    .as_object_mut()
+                    .expect("field schemas are objects")
+                    .insert("description".to_string(), json!(description));
+            }
+            properties.insert(lower_camel(&field.name), value);
+        }
+
+        Ok(json!({
+            "type": "object",
+            "properties": properties,
+            "additionalProperties": false,
+        }))
+    }
+
+    fn type_schema(
+        schema: &Schema,
+        ty: &str,
+        scope: &str,
+        pending: &mut Vec<String>,
+    ) -> Result<Value> {
+        let value = match ty {
+            "string" => json!({ "type": "string" }),
+            "bool" => json!({ "type": "boolean" }),
+            "bytes" => json!({ "type": "string", "contentEncoding": "base64" }),
+            "float" | "double" => json!({ "type": "number" }),
+            "int32" | "sint32" | "sfixed32" => json!({ "type": "integer" }),
+            "uint32" | "fixed32" => json!({ "type": "integer", "minimum": 0 }),
+            "int64" | "sint64" | "sfixed64" => json!({ "type": "string", "pattern": "^-?[0-9]+$" }),
+            "uint64" | "fixed64" => json!({ "type": "string", "pattern": "^[0-9]+$" }),
+            _ => {
+                let local = resolve(schema, ty, scope)
+                    .ok_or_else(|| anyhow!("unknown proto type {}", ty))?;
+                if let Some(variants) = schema.enums.get(&local) {
+                    json!({ "type": "string", "enum": variants })
+                } else {
+                    let value = json!({ "$ref": format!("#/$defs/{}", local) });
+                    pending.push(local);
+                    value
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    /// Looks a type name up the way protoc does: innermost enclosing message
+    /// first, then outwards to the package level.
+    fn resolve(schema: &Schema, ty: &str, scope: &str) -> Option<String> {
+        let ty = ty
+            .strip_prefix(&format!("{}.", schema.package))
+            .unwrap_or(ty);
+        let mut scope = scope.trim_end_matches('.');
+        loop {
+            let candidate = if scope.is_empty() {
+                ty.to_string()
+            } else {
+                format!("{}.{}", scope, ty)
+            };
+            if schema.messages.contains_key(&candidate) || schema.enums.contains_key(&candidate) {
+                return Some(candidate);
+            }
+            if scope.is_empty() {
+                return None;
+            }
+            scope = scope.rfind('.').map(|idx| &scope[..idx]).unwrap_or("");
+        }
+    }
+
+    fn lower_camel(name: &str) -> String {
+        let mut out = String::with_capacity(name.len());
+        let mut upper = false;
+        for c in name.chars() {
+            if c == '_' {
+                upper = true;
+            } else if upper {
+                out.extend(c.to_uppercase());
+                upper = false;
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    struct Token {
+        text: String,
+        line: usize,
+    }
+
+    struct Parser {
+        tokens: Vec<Token>,
+        comments: BTreeMap<usize, String>,
+        pos: usize,
+    }
+
+    fn parse_file(path: &Path, schema: &mut Schema) -> Result<()> {
+        let source = std::fs::read_to_string(path)?;
+        let (tokens, comments) = tokenize(&source);
+        let mut parser = Parser {
+            tokens,
+            comments,
+            pos: 0,
+        };
+
+        while let Some(token) = parser.next() {
+            match token.as_str() {
+                "syntax" | "import" | "option" => parser.skip_statement(),
+                "package" => {
+                    schema.package = parser.expect_ident()?;
+                    parser.expect(";")?;
+                }
+                "message" => parser.parse_message("", schema)?,
+                "enum" => parser.parse_enum("", schema)?,
+                other => bail!("unexpected top-level token {}", other),
+            }
+        }
+        Ok(())
+    }
+
+    impl Parser {
+        fn next(&mut self) -> Option<String> {
+            let token = self.tokens.get(self.pos)?.text.clone();
+            self.pos += 1;
+            Some(token)
+        }
+
+        fn peek(&self) -> Option<&str> {
+            self.tokens.get(self.pos).map(|t| t.text.as_str())
+        }
+
+        fn expect(&mut self, want: &str) -> Result<()> {
+            match self.next() {
+                Some(got) if got == want => Ok(()),
+                got => bail!("expected {}, found {:?}", want, got),
+            }
+        }
+
+        fn expect_ident(&mut self) -> Result<String> {
+            self.next().ok_or_else(|| anyhow!("unexpected end of file"))
+        }
+
+        fn skip_statement(&mut self) {
+            while let Some(token) = self.next() {
+                if token == ";" {
+                    break;
+                }
+            }
+        }
+
+        fn skip_block(&mut self) -> Result<()> {
+            self.expect("{")?;
+            let mut depth = 1;
+            while depth > 0 {
+                match self.next().as_deref() {
+                    Some("{") => depth += 1,
+                    Some("}") => depth -= 1,
+                    Some(_) => {}
+                    None => bail!("unterminated block"),
+                }
+            }
+            Ok(())
+        }
+
+        fn parse_enum(&mut self, scope: &str, schema: &mut Schema) -> Result<()> {
+            let name = format!("{}{}", scope, self.expect_ident()?);
+            self.expect("{")?;
+            let mut variants = Vec::new();
+            loop {
+                match self.next().as_deref() {
+                    Some("}") => break,
+                    Some("option") | Some("reserved") => self.skip_statement(),
+                    Some(variant) => {
+                        variants.push(variant.to_string());
+                        self.skip_statement();
+                    }
+                    None => bail!("unterminated enum {}", name),
+                }
+            }
+            schema.enums.insert(name, variants);
+            Ok(())
+        }
+
+        fn parse_message(&mut self, scope: &str, schema: &mut Schema) -> Result<()> {
+            let name = format!("{}{}", scope, self.expect_ident()?);
+            let nested_scope = format!("{}.", name);
+            self.expect("{")?;
+
+            let mut fields = Vec::new();
+            loop {
+                let start_line = self
+                    .tokens
+                    .get(self.pos)
+                    .map(|t| t.line)
+                    .unwrap_or_default();
+                match self.next().as_deref() {
+                    Some("}") => break,
+                    Some("message") => self.parse_message(&nested_scope, schema)?,
+                    Some("enum") => self.parse_enum(&nested_scope, schema)?,
+                    Some("option") | Some("reserved") | Some("extensions") => self.skip_statement(),
+                    Some("oneof") => {
+                        self.expect_ident()?;
+                        self.expect("{")?;
+                        while self.peek() != Some("}") {
+                            let line = self
+                                .tokens
+                                .get(self.pos)
+                                .map(|t| t.line)
+                                .unwrap_or_default();
+                            let ty = self.expect_ident()?;
+                            fields.push(self.parse_field(ty, false, line, &nested_scope)?);
+                        }
+                        self.expect("}")?;
+                    }
+                    Some("extend") => {
+                        self.expect_ident()?;
+                        self.skip_block()?;
+                    }
+                    Some("repeated") => {
+                        let ty = self.expect_ident()?;
+                        fields.push(self.parse_field(ty, true, start_line, &nested_scope)?);
+                    }
+                    Some("optional") => {
+                        let ty = self.expect_ident()?;
+                        fields.push(self.parse_field(ty, false, start_line, &nested_scope)?);
+                    }
+                    Some(ty) => {
+                        let ty = ty.to_string();
+                        fields.push(self.parse_field(ty, false, start_line, &nested_scope)?);
+                    }
+                    None => bail!("unterminated message {}", name),
+                }
+            }
+
+            schema.messages.insert(name, Message { fields });
+            Ok(())
+        }
+
+        fn parse_field(
+            &mut self,
+            ty: String,
+            repeated: bool,
+            start_line: usize,
+            scope: &str,
+        ) -> Result<Field> {
+            let mut map_types = None;
+            if ty == "map" {
+                self.expect("<")?;
+                let key = self.expect_ident()?;
+                self.expect(",")?;
+                let value = self.expect_ident()?;
+                self.expect(">")?;
+                map_types = Some((key, value));
+            }
+
+            let name = self.expect_ident()?;
+            let end_line = loop {
+                let line = self
+                    .tokens
+                    .get(self.pos)
+                    .map(|t| t.line)
+                    .unwrap_or_default();
+                match self.next().as_deref() {
+                    Some(";") => break line,
+                    Some(_) => {}
+                    None => bail!("unterminated field {}", name),
+                }
+            };
+
+            let description = self
+                .comments
+                .get(&end_line)
+                .cloned()
+                .or_else(|| self.leading_comment(start_line));
+
+            Ok(Field {
+                name,
+                ty,
+                repeated,
+                map_types,
+                scope: scope.to_string(),
+                description,
+            })
+        }
+
+        fn leading_comment(&self, line: usize) -> Option<String> {
+            let mut lines = Vec::new();
+            let mut current = line.checked_sub(1)?;
+            while let Some(comment) = self.comments.get(&current) {
+                if self.tokens.iter().any(|t| t.line == current) {
+                    break;
+                }
+                lines.push(comment.clone());
+                current = current.checked_sub(1)?;
+            }
+            if lines.is_empty() {
+                return None;
+            }
+            lines.reverse();
+            Some(lines.join(" "))
+        }
+    }
+
+    fn tokenize(source: &str) -> (Vec<Token>, BTreeMap<usize, String>) {
+        let mut tokens = Vec::new();
+        let mut comments = BTreeMap::new();
+
+        for (number, raw_line) in source.lines().enumerate() {
+            let line = number + 1;
+            let (code, comment) = match raw_line.find("//") {
+                Some(idx) => (&raw_line[..idx], Some(raw_line[idx + 2..].trim())),
+                None => (raw_line, None),
+            };
+            if let Some(comment) = comment.filter(|c| !c.is_empty()) {
+                comments.insert(line, comment.to_string());
+            }
+
+            let mut chars = code.char_indices().peekable();
+            while let Some((start, c)) = chars.next() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                if "{}=;<>,[]()".contains(c) {
+                    tokens.push(Token {
+                        text: c.to_string(),
+                        line,
+                    });
+                    continue;
+                }
+                let mut end = start + c.len_utf8();
+                if c == '"' {
+                    for (idx, next) in chars.by_ref() {
+                        end = idx + next.len_utf8();
+                        if next == '"' {
+                            break;
+                        }
+                    }
+                } else {
+                    while let Some(&(idx, next)) = chars.peek() {
+                        if next.is_whitespace() || "{}=;<>,[]()".contains(next) {
+                            break;
+                        }
+                        end = idx + next.len_utf8();
+                        chars.next();
+                    }
+                }
+                tokens.push(Token {
+                    text: code[start..end].to_string(),
+                    line,
+                });
+            }
+        }
+
+        (tokens, comments)
+    }
+}
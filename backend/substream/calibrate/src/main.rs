@@ -0,0 +1,95 @@
+//! Replays a fixture corpus through `map_candidate_events` under a grid of
+//! params and prints precision and recall per rule and grid point:
+//!
+//! ```text
+//! cargo run --release -- <fixtures dir> ['key=v1|v2|...' ...]
+//! ```
+//!
+//! Each fixture is a `<name>.binpb` block with a `<name>.labels` file listing
+//! the detections it should produce, one `event_type tx_hash` per line.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Error};
+use pulseproof::calibration::{self, Block, Detection, Score};
+
+struct Fixture {
+    name: String,
+    block: Block,
+    labels: Vec<Detection>,
+}
+
+fn load(dir: &Path) -> Result<Vec<Fixture>, Error> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("reading {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "binpb"));
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let block = calibration::decode_block(&fs::read(&path)?)
+                .with_context(|| format!("decoding {}", path.display()))?;
+            let labels_path = path.with_extension("labels");
+            let labels = calibration::parse_labels(
+                &fs::read_to_string(&labels_path)
+                    .with_context(|| format!("reading {}", labels_path.display()))?,
+            )?;
+            Ok(Fixture {
+                name: path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into(),
+                block,
+                labels,
+            })
+        })
+        .collect()
+}
+
+fn ratio(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.3}", v))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn main() -> Result<(), Error> {
+    let mut args = std::env::args().skip(1);
+    let dir = args
+        .next()
+        .ok_or_else(|| anyhow!("usage: substream-calibrate <fixtures dir> [key=v1|v2 ...]"))?;
+    let axes: Vec<String> = args.collect();
+
+    let fixtures = load(Path::new(&dir))?;
+    if fixtures.is_empty() {
+        return Err(anyhow!("no .binpb fixtures in {}", dir));
+    }
+
+    println!("params\trule\ttp\tfp\tfn\tprecision\trecall");
+    for point in calibration::grid(&axes)? {
+        let mut scores: Vec<Score> = Vec::new();
+        for fixture in fixtures.iter() {
+            let found = calibration::replay(&fixture.block, &point)
+                .with_context(|| format!("replaying {}", fixture.name))?;
+            calibration::score(&mut scores, &fixture.labels, &found);
+        }
+        scores.sort_by(|a, b| a.rule.cmp(&b.rule));
+        for score in scores {
+            println!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                if point.is_empty() { "-" } else { &point },
+                score.rule,
+                score.true_positives,
+                score.false_positives,
+                score.false_negatives,
+                ratio(score.precision()),
+                ratio(score.recall()),
+            );
+        }
+    }
+    Ok(())
+}
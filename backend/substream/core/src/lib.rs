@@ -0,0 +1,104 @@
+//! Decoding, scoring and encoding of pulseproof candidate events for services
+//! that run them outside the stream. Every function calls into the code the
+//! substreams modules run, so results match what the stream emits for the
+//! same params.
+//!
+//! ```
+//! use pulseproof_core::{canonical_encoding, decode_log, score_transaction, Config, Log, TransactionTrace};
+//!
+//! // Transfer(address,address,uint256) of 5 units.
+//! let word = |last: u8| {
+//!     let mut word = vec![0; 32];
+//!     word[31] = last;
+//!     word
+//! };
+//! let log = Log {
+//!     address: vec![0xaa; 20],
+//!     topics: vec![
+//!         hex_topic("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+//!         word(1),
+//!         word(2),
+//!     ],
+//!     data: word(5),
+//!     ..Default::default()
+//! };
+//! let tx = TransactionTrace {
+//!     hash: vec![0x11; 32],
+//!     ..Default::default()
+//! };
+//!
+//! let config = Config::parse("large_transfer_units=1000000").unwrap();
+//! let event = decode_log(&config, &tx, &log, 1).unwrap();
+//! assert_eq!(event.event_type, "Transfer");
+//! assert_eq!(event.to, format!("0x{}", "00".repeat(19) + "02"));
+//!
+//! // A single small transfer isn't suspicious.
+//! assert!(score_transaction(&config, &[event.clone()]).is_none());
+//! assert!(!canonical_encoding(&event).is_empty());
+//!
+//! fn hex_topic(hex: &str) -> Vec<u8> {
+//!     (0..hex.len())
+//!         .step_by(2)
+//!         .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+//!         .collect()
+//! }
+//! ```
+//!
+//! # Stability
+//!
+//! This crate follows semver; the substreams package it wraps does not, and
+//! only what is exported here is covered. Within a major version:
+//!
+//! - The signatures of `decode_log`, `score_transaction`,
+//!   `canonical_encoding` and `Config::parse` don't change.
+//! - `CandidateEvent` and `SuspiciousTransaction` only gain fields, with new
+//!   tags, so services built against an older version keep decoding them.
+//!   Build them with `..Default::default()`.
+//! - Params keep their names and meaning; new params default to the current
+//!   behaviour.
+//! - `canonical_encoding` of an event doesn't change for the fields it
+//!   already has, so commitments computed earlier still verify.
+//!
+//! Detections aren't frozen: a minor version may classify more logs, fill
+//! more fields or score differently, the same way a new release of the
+//! substreams package does. `Log` and `TransactionTrace` are re-exported from
+//! `substreams-ethereum`; moving to a version of it whose types differ is a
+//! major version.
+
+pub use pulseproof::embedding::{
+    CandidateEvent, Config, Log, SuspiciousTransaction, TransactionTrace,
+};
+
+/// Decodes one log of `tx` into the event `map_candidate_events` emits for
+/// it, with severity, fingerprint and shadow mode applied. `None` for logs it
+/// doesn't classify and contracts the `contracts` param filters out.
+///
+/// Detections that span several logs or need the rest of the block (swap
+/// legs, flash loans, bridges, dusting, transaction context and ordering)
+/// aren't produced from a single log.
+pub fn decode_log(
+    config: &Config,
+    tx: &TransactionTrace,
+    log: &Log,
+    block_number: u64,
+) -> Option<CandidateEvent> {
+    pulseproof::embedding::decode_log(config, tx, log, block_number)
+}
+
+/// Scores the events of one transaction the way
+/// `map_suspicious_transactions` does, or `None` when the score stays below
+/// the `suspicion_min_score` param. Only events of the transaction of the
+/// first event that has one are scored.
+pub fn score_transaction(
+    config: &Config,
+    events: &[CandidateEvent],
+) -> Option<SuspiciousTransaction> {
+    pulseproof::embedding::score_transaction(config, events)
+}
+
+/// The bytes an event is committed as: its protobuf encoding, which is the
+/// same for equal events. `map_event_commitment` hashes them with keccak256
+/// into the event's Merkle leaf.
+pub fn canonical_encoding(event: &CandidateEvent) -> Vec<u8> {
+    pulseproof::embedding::canonical_encoding(event)
+}
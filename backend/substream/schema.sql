@@ -0,0 +1,65 @@
+-- Table written by the db_out module for substreams-sink-sql (Postgres).
+-- Numeric amounts are decimal strings, so they stay exact past 2^64.
+create table if not exists candidate_events (
+    block_number     bigint  not null,
+    -- Empty on block-wide events.
+    transaction_hash text    not null,
+    log_index        bigint  not null,
+    part             integer not null,
+    tx_index         integer not null,
+    ordering_key     text    not null,
+    contract_address text    not null,
+    event_signature  text    not null,
+    event_type       text    not null,
+    metadata         text    not null,
+    retention_class  text    not null,
+    severity         text    not null,
+    -- Severity of a shadow-mode rule's event, which is emitted at info;
+    -- empty when the rule is enforced.
+    shadow_severity  text    not null,
+    from_address     text    not null,
+    to_address       text    not null,
+    owner            text    not null,
+    spender          text    not null,
+    -- Transfer or Approval value, raw and scaled by the token's decimals;
+    -- null on other events.
+    value            numeric,
+    value_decimal    text,
+    -- Amounts into and out of the pair in a Swap; null on other events.
+    amount0_in       numeric,
+    amount1_in       numeric,
+    amount0_out      numeric,
+    amount1_out      numeric,
+    fingerprint      text    not null,
+    protocol         text    not null,
+    protocol_version text    not null,
+    standard         text    not null,
+    token_symbol     text    not null,
+    -- What a listed token tracks (usd, eth, ...); empty when unpegged.
+    token_peg        text    not null,
+    -- Asset a listed token stands for (itself, the token it is equivalent
+    -- to, or native); empty for unlisted tokens.
+    asset            text    not null,
+    -- What the contract behaved like in its token logs (erc20, nft,
+    -- nonstandard); set on enriched events only.
+    token_class      text    not null,
+    nonstandard_abi  boolean not null,
+    likely_private   boolean not null,
+    flash_funded     boolean not null,
+    -- The transaction is flagged and mints or frees gas tokens or takes a
+    -- large gas refund.
+    gas_token_usage  boolean not null,
+    -- Key shared by both sides of a CrossChainMessage, on every chain the
+    -- substream runs on; null on other events.
+    link_key         text,
+    -- Comma-separated routing keys and tenant ids the routes and tenants
+    -- params tag the event with; empty when untagged.
+    routing_keys     text    not null,
+    tenants          text    not null,
+    config_epoch     text    not null,
+    primary key (block_number, transaction_hash, log_index, part)
+);
+
+create index if not exists candidate_events_contract_idx on candidate_events (contract_address);
+create index if not exists candidate_events_event_type_idx on candidate_events (event_type);
+create index if not exists candidate_events_link_key_idx on candidate_events (link_key);
@@ -0,0 +1,268 @@
+//! Replays a fixture corpus through `map_candidate_events` and `db_out`,
+//! applies the rows to a throwaway Postgres created from `schema.sql`, and
+//! fails when an insert is rejected or a table ends up with other rows than
+//! `db_out` created:
+//!
+//! ```text
+//! cargo run --release -- <fixtures dir> [params]
+//! ```
+//!
+//! Fixtures are the `<name>.binpb` blocks `calibrate/` reads. Needs `docker`;
+//! `cargo test` runs the same check on a synthetic block, and skips it when
+//! no Docker daemon is reachable.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Error};
+use pulseproof::sink_check::{self, Block};
+
+const IMAGE: &str = "postgres:16-alpine";
+const SCHEMA: &str = include_str!("../../schema.sql");
+
+fn load(dir: &Path) -> Result<Vec<(String, Block)>, Error> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("reading {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "binpb"));
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let block = sink_check::decode_block(&fs::read(&path)?)
+                .with_context(|| format!("decoding {}", path.display()))?;
+            let name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into();
+            Ok((name, block))
+        })
+        .collect()
+}
+
+fn docker(args: &[&str]) -> Result<Output, Error> {
+    Command::new("docker")
+        .args(args)
+        .output()
+        .context("running docker")
+}
+
+/// A Postgres container, removed when dropped.
+struct Postgres {
+    id: String,
+}
+
+impl Postgres {
+    fn start() -> Result<Self, Error> {
+        let output = docker(&[
+            "run",
+            "--detach",
+            "--rm",
+            "--env",
+            "POSTGRES_HOST_AUTH_METHOD=trust",
+            IMAGE,
+        ])?;
+        if !output.status.success() {
+            bail!(
+                "docker run {}: {}",
+                IMAGE,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let postgres = Postgres {
+            id: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        };
+        // The image restarts the server once after initializing the
+        // database, so wait for a query to go through rather than the port.
+        for _ in 0..60 {
+            if postgres.psql("select 1;").is_ok() {
+                return Ok(postgres);
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+        bail!("postgres in container {} did not come up", postgres.id)
+    }
+
+    /// Runs `sql` in one transaction, stopping at the first error.
+    fn psql(&self, sql: &str) -> Result<String, Error> {
+        let mut child = Command::new("docker")
+            .args([
+                "exec",
+                "--interactive",
+                &self.id,
+                "psql",
+                "--username=postgres",
+                "--quiet",
+                "--tuples-only",
+                "--no-align",
+                "--single-transaction",
+                "--set=ON_ERROR_STOP=1",
+                "--file=-",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("running docker exec")?;
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("psql has no stdin"))?
+            .write_all(sql.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+impl Drop for Postgres {
+    fn drop(&mut self) {
+        let _ = docker(&["rm", "--force", &self.id]);
+    }
+}
+
+/// Applies the rows `db_out` creates for `fixtures` to a fresh database and
+/// compares the row count of every table with what was inserted.
+fn check(fixtures: &[(String, Block)], params: &str) -> Result<(), Error> {
+    let postgres = Postgres::start()?;
+    postgres.psql(SCHEMA).context("applying schema.sql")?;
+
+    let mut expected: BTreeMap<String, u64> = BTreeMap::new();
+    for (name, block) in fixtures.iter() {
+        let inserts =
+            sink_check::inserts(block, params).with_context(|| format!("replaying {}", name))?;
+        let mut sql = String::new();
+        for (table, insert) in inserts {
+            *expected.entry(table).or_default() += 1;
+            sql.push_str(&insert);
+            sql.push('\n');
+        }
+        postgres
+            .psql(&sql)
+            .with_context(|| format!("applying the rows of {}", name))?;
+    }
+
+    println!("table\texpected\trows");
+    let mut mismatched = Vec::new();
+    for (table, count) in expected.iter() {
+        let rows: u64 = postgres
+            .psql(&format!("select count(*) from {};", table))?
+            .parse()
+            .with_context(|| format!("counting {}", table))?;
+        println!("{}\t{}\t{}", table, count, rows);
+        if rows != *count {
+            mismatched.push(table.as_str());
+        }
+    }
+    if !mismatched.is_empty() {
+        bail!("row counts differ in {}", mismatched.join(", "));
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    let mut args = std::env::args().skip(1);
+    let dir = args
+        .next()
+        .ok_or_else(|| anyhow!("usage: substream-sinkcheck <fixtures dir> [params]"))?;
+    let params = args.next().unwrap_or_default();
+
+    let fixtures = load(Path::new(&dir))?;
+    if fixtures.is_empty() {
+        return Err(anyhow!("no .binpb fixtures in {}", dir));
+    }
+    check(&fixtures, &params)
+}
+
+#[cfg(test)]
+mod tests {
+    use substreams_ethereum::pb::eth::v2 as eth;
+
+    use super::*;
+
+    const TRANSFER_TOPIC: [u8; 32] =
+        hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+    const APPROVAL_TOPIC: [u8; 32] =
+        hex_literal::hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+    const SWAP_TOPIC: [u8; 32] =
+        hex_literal::hex!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+
+    fn word(last: u8) -> Vec<u8> {
+        let mut word = [0u8; 32];
+        word[31] = last;
+        word.to_vec()
+    }
+
+    fn log(index: u32, topics: Vec<Vec<u8>>, data: Vec<Vec<u8>>) -> eth::Log {
+        eth::Log {
+            address: vec![0xaa; 20],
+            topics,
+            data: data.concat(),
+            index,
+            ordinal: index.into(),
+            ..Default::default()
+        }
+    }
+
+    /// One transaction with a Transfer, an Approval and a V2 Swap, so the
+    /// value and swap amount columns are written too.
+    fn block() -> Block {
+        let logs = vec![
+            log(
+                0,
+                vec![TRANSFER_TOPIC.to_vec(), word(1), word(2)],
+                vec![word(5)],
+            ),
+            log(
+                1,
+                vec![APPROVAL_TOPIC.to_vec(), word(1), word(3)],
+                vec![word(7)],
+            ),
+            log(
+                2,
+                vec![SWAP_TOPIC.to_vec(), word(3), word(2)],
+                vec![word(5), word(0), word(0), word(9)],
+            ),
+        ];
+        Block {
+            number: 1,
+            hash: vec![0x22; 32],
+            header: Some(eth::BlockHeader::default()),
+            transaction_traces: vec![eth::TransactionTrace {
+                hash: vec![0x11; 32],
+                from: vec![0x01; 20],
+                to: vec![0xaa; 20],
+                status: eth::TransactionTraceStatus::Succeeded as i32,
+                receipt: Some(eth::TransactionReceipt {
+                    logs,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn docker_daemon() -> bool {
+        docker(&["info"]).is_ok_and(|output| output.status.success())
+    }
+
+    #[test]
+    fn synthetic_block_applies_to_the_schema() -> Result<(), Error> {
+        if !docker_daemon() {
+            eprintln!("skipping the sink check: no Docker daemon");
+            return Ok(());
+        }
+        check(&[("synthetic".to_string(), block())], "")
+    }
+}
@@ -0,0 +1,138 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::u256::U256;
+
+/// Hex-encodes an address taken from an indexed topic, or returns `None` when
+/// the topic is missing or isn't a full 32-byte word.
+pub fn topic_address(log: &eth::Log, index: usize) -> Option<String> {
+    let address = address_bytes(log.topics.get(index)?)?;
+    Some(format!("0x{}", hex::encode(address)))
+}
+
+/// Hex-encodes the log's topic0, or returns an empty string for anonymous
+/// logs.
+pub fn signature(log: &eth::Log) -> String {
+    log.topics
+        .first()
+        .map(|topic| format!("0x{}", hex::encode(topic)))
+        .unwrap_or_default()
+}
+
+/// The low 20 bytes of a 32-byte word or topic, where ABI encoding puts an
+/// address. `None` for anything that isn't a full word.
+pub fn address_bytes(word: &[u8]) -> Option<&[u8]> {
+    let (_, address) = word.split_first_chunk::<12>()?;
+    (address.len() == 20).then_some(address)
+}
+
+/// Returns the `index`-th 32-byte word of ABI-encoded `data`.
+pub fn word(data: &[u8], index: usize) -> Option<&[u8]> {
+    let start = index.checked_mul(32)?;
+    data.get(start..start.checked_add(32)?)
+}
+
+/// Reads a word as a `usize` offset or length, rejecting values that could
+/// not possibly index into calldata.
+pub fn word_usize(data: &[u8], index: usize) -> Option<usize> {
+    let (high, low) = word(data, index)?.split_first_chunk::<24>()?;
+    if high.iter().any(|b| *b != 0) {
+        return None;
+    }
+    usize::try_from(u64::from_be_bytes(low.try_into().ok()?)).ok()
+}
+
+pub fn word_u256(data: &[u8], index: usize) -> Option<U256> {
+    Some(U256::from_big_endian(word(data, index)?))
+}
+
+/// Reads an indexed topic as a uint256, such as a token id. `None` when the
+/// topic is missing or isn't a full 32-byte word.
+pub fn topic_u256(log: &eth::Log, index: usize) -> Option<U256> {
+    let topic = log.topics.get(index).filter(|topic| topic.len() == 32)?;
+    Some(U256::from_big_endian(topic))
+}
+
+pub fn word_address(data: &[u8], index: usize) -> Option<String> {
+    Some(format!(
+        "0x{}",
+        hex::encode(address_bytes(word(data, index)?)?)
+    ))
+}
+
+/// Decodes a dynamic `bytes` value whose head word sits at `index`.
+pub fn word_bytes(data: &[u8], index: usize) -> Option<&[u8]> {
+    let offset = word_usize(data, index)?;
+    let body = data.get(offset..)?;
+    let len = word_usize(body, 0)?;
+    body.get(32..32usize.checked_add(len)?)
+}
+
+/// Decodes a dynamic array whose head word sits at `index`, returning the
+/// element words. Elements are static 32-byte values (addresses, uints).
+pub fn word_array(data: &[u8], index: usize) -> Option<Vec<&[u8]>> {
+    let offset = word_usize(data, index)?;
+    let body = data.get(offset..)?;
+    let len = word_usize(body, 0)?;
+    if len > body.len() / 32 {
+        return None;
+    }
+    (1..=len).map(|i| word(body, i)).collect()
+}
+
+// ERC20 Transfer(address,address,uint256)
+const TRANSFER_TOPIC: [u8; 32] =
+    hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// A decoded ERC20 Transfer. `from` and `to` are 20-byte addresses.
+pub struct Transfer<'a> {
+    pub from: &'a [u8],
+    pub to: &'a [u8],
+    pub amount: U256,
+    /// Set when some of `from`/`to` were not indexed and were read from data.
+    pub nonstandard: bool,
+}
+
+/// Decodes an ERC20 Transfer log, tolerating tokens that leave `from`, or both
+/// `from` and `to`, out of the indexed topics. ERC721 transfers (tokenId as a
+/// fourth topic) and anything malformed return `None`.
+pub fn transfer(log: &eth::Log) -> Option<Transfer<'_>> {
+    if log.topics.first()?.as_slice() != TRANSFER_TOPIC {
+        return None;
+    }
+
+    match log.topics.len() {
+        3 if log.data.len() == 32 => Some(Transfer {
+            from: topic_address_bytes(log, 1)?,
+            to: topic_address_bytes(log, 2)?,
+            amount: word_u256(&log.data, 0)?,
+            nonstandard: false,
+        }),
+        2 if log.data.len() == 64 => Some(Transfer {
+            from: topic_address_bytes(log, 1)?,
+            to: word_address_bytes(&log.data, 0)?,
+            amount: word_u256(&log.data, 1)?,
+            nonstandard: true,
+        }),
+        1 if log.data.len() == 96 => Some(Transfer {
+            from: word_address_bytes(&log.data, 0)?,
+            to: word_address_bytes(&log.data, 1)?,
+            amount: word_u256(&log.data, 2)?,
+            nonstandard: true,
+        }),
+        _ => None,
+    }
+}
+
+fn topic_address_bytes(log: &eth::Log, index: usize) -> Option<&[u8]> {
+    address_bytes(log.topics.get(index)?)
+}
+
+/// Like `word_address` but rejects words with dirty high bytes, which would
+/// mean the data isn't laid out the way the fallback assumes.
+fn word_address_bytes(data: &[u8], index: usize) -> Option<&[u8]> {
+    let (high, address) = word(data, index)?.split_first_chunk::<12>()?;
+    if high.iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(address)
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::DeltaInt64;
+
+use crate::pb::pulseproof::{ActivitySpike, CandidateEvent};
+
+// Two window series, the second offset by half a window, so at any block one
+// of them has counted at least half a window before it.
+const SERIES: [u64; 2] = [0, 1];
+
+/// Window of `series` the block falls in, and the window's first block.
+fn window(series: u64, block_number: u64, window_blocks: u64) -> (u64, u64) {
+    let offset = series * (window_blocks / 2);
+    let window = (block_number + offset) / window_blocks;
+    (window, (window * window_blocks).saturating_sub(offset))
+}
+
+/// Prefix of every key of one window of a series, so the window can be
+/// dropped once the next one starts.
+pub fn window_prefix(series: u64, window: u64) -> String {
+    format!("activity:{}:{}:", series, window)
+}
+
+/// Key counting `kind` events of `contract` in one window of a series.
+pub fn count_key(series: u64, window: u64, kind: &str, contract: &str) -> String {
+    format!("{}{}:{}", window_prefix(series, window), kind, contract)
+}
+
+/// Prefixes to drop at this block: the window each series just left.
+pub fn expired_prefixes(block_number: u64, window_blocks: u64) -> Vec<String> {
+    SERIES
+        .iter()
+        .filter_map(|&series| {
+            let (window, start) = window(series, block_number, window_blocks);
+            (start == block_number && window > 0).then(|| window_prefix(series, window - 1))
+        })
+        .collect()
+}
+
+/// Activity the block's events add: `transfer`, `approval` and `swap` per
+/// emitting contract, and `transfer_out` per `watch`ed sender.
+pub fn counts<'a>(
+    events: &'a [CandidateEvent],
+    watch: &[String],
+) -> BTreeMap<(&'static str, &'a str), i64> {
+    let mut counts = BTreeMap::new();
+    for event in events {
+        let kind = match event.event_type.as_str() {
+            "Transfer" | "LargeTransfer" => "transfer",
+            "Approval" => "approval",
+            "Swap" => "swap",
+            _ => continue,
+        };
+        *counts
+            .entry((kind, event.contract_address.as_str()))
+            .or_default() += 1;
+        if kind == "transfer" && watch.contains(&event.from) {
+            *counts
+                .entry(("transfer_out", event.from.as_str()))
+                .or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Count keys for the block's activity, in every series.
+pub fn keys(block_number: u64, window_blocks: u64, kind: &str, contract: &str) -> Vec<String> {
+    SERIES
+        .iter()
+        .map(|&series| {
+            let (window, _) = window(series, block_number, window_blocks);
+            count_key(series, window, kind, contract)
+        })
+        .collect()
+}
+
+/// Flags contracts whose activity this block is at least `multiple` times
+/// their mean per block so far in the window. Reads the deltas of
+/// `store_contract_activity`: a key's change is this block's count and its
+/// old value the window's count before it. Only the series that has run
+/// longer is read, so the baseline always spans at least half a window.
+/// Activity with no baseline is flagged on `min_count` alone, which every
+/// spike must also reach.
+pub fn spikes(
+    deltas: &[DeltaInt64],
+    block_number: u64,
+    window_blocks: u64,
+    multiple: u64,
+    min_count: u64,
+) -> Vec<ActivitySpike> {
+    let Some((series, (window, start))) = SERIES
+        .iter()
+        .map(|&series| (series, window(series, block_number, window_blocks)))
+        .min_by_key(|(_, (_, start))| *start)
+    else {
+        return Vec::new();
+    };
+    let elapsed = block_number - start;
+    if elapsed == 0 {
+        return Vec::new();
+    }
+    let prefix = window_prefix(series, window);
+
+    deltas
+        .iter()
+        .filter(|d| d.operation != Operation::Delete)
+        .filter_map(|d| {
+            let (kind, contract) = d.key.strip_prefix(&prefix)?.split_once(':')?;
+            let count = (d.new_value - d.old_value).max(0) as u64;
+            let baseline = d.old_value.max(0) as u64;
+            if count < min_count || count * elapsed < multiple.saturating_mul(baseline) {
+                return None;
+            }
+            Some(ActivitySpike {
+                contract: contract.to_string(),
+                kind: kind.to_string(),
+                count,
+                baseline_count: baseline,
+                baseline_blocks: elapsed,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,117 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CandidateEvent;
+use crate::{abi, sender};
+
+// Ownable OwnershipTransferred(address,address)
+const OWNERSHIP_TRANSFERRED_TOPIC: [u8; 32] =
+    hex_literal::hex!("8be0079c531659141344cd1fd0a4f28419497f9722a3daafe3b4186f6b6457e0");
+// EIP-1967 AdminChanged(address,address)
+const ADMIN_CHANGED_TOPIC: [u8; 32] =
+    hex_literal::hex!("7e644d79422f17c01e4894b5f4f588d331ebfa28653d42ae832dc59e38c9798f");
+// AccessControl RoleGranted(bytes32,address,address)
+const ROLE_GRANTED_TOPIC: [u8; 32] =
+    hex_literal::hex!("2f8788117e7eff1d82e926ec794901d17c78024a50270940304540a733656f0d");
+
+// One admin change is routine maintenance; several watched contracts handed
+// over in one transaction is a sweep.
+pub const MIN_CONTRACTS: usize = 2;
+
+pub struct AdminChange {
+    pub contract: String,
+    pub kind: &'static str,
+    pub new_admin: String,
+    pub log_index: u32,
+}
+
+/// Ownership and admin handovers recorded in a transaction's logs.
+pub fn changes(logs: &[eth::Log]) -> Vec<AdminChange> {
+    logs.iter()
+        .filter_map(|log| {
+            let (kind, new_admin) = decode(log)?;
+            Some(AdminChange {
+                contract: format!("0x{}", hex::encode(&log.address)),
+                kind,
+                new_admin,
+                log_index: log.index,
+            })
+        })
+        .collect()
+}
+
+/// Renders admin changes as a JSON array for `metadata`.
+pub fn changes_json(changes: &[AdminChange]) -> String {
+    let entries: Vec<String> = changes
+        .iter()
+        .map(|c| {
+            format!(
+                "{{\"contract\":\"{}\",\"kind\":\"{}\",\"new_admin\":\"{}\"}}",
+                c.contract, c.kind, c.new_admin
+            )
+        })
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+/// Distinct contracts touched by `changes`, sorted.
+pub fn contracts(changes: &[AdminChange]) -> Vec<&str> {
+    let mut contracts: Vec<&str> = changes.iter().map(|c| c.contract.as_str()).collect();
+    contracts.sort_unstable();
+    contracts.dedup();
+    contracts
+}
+
+/// Flags a transaction that hands ownership or admin rights of several
+/// watched contracts to new addresses at once, the signature of a
+/// compromised deployer or multisig key being swept. `log_index` carries the
+/// last admin change.
+pub fn mass_admin_change(
+    tx: &eth::TransactionTrace,
+    logs: &[eth::Log],
+    block_number: u64,
+    watch: &[String],
+) -> Option<CandidateEvent> {
+    let mut changes = changes(logs);
+    changes.retain(|c| watch.contains(&c.contract));
+    let contracts = contracts(&changes).len();
+    if contracts < MIN_CONTRACTS {
+        return None;
+    }
+
+    Some(CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+        block_number,
+        log_index: changes
+            .iter()
+            .map(|c| c.log_index)
+            .max()
+            .unwrap_or_default() as u64,
+        event_type: "MassAdminChange".to_string(),
+        from: sender::sender(tx)
+            .map(|address| format!("0x{}", hex::encode(address)))
+            .unwrap_or_default(),
+        metadata: format!(
+            "{{\"contracts\":{},\"changes\":{}}}",
+            contracts,
+            changes_json(&changes)
+        ),
+        severity: "critical".to_string(),
+        ..Default::default()
+    })
+}
+
+/// The kind of admin change a log records and the address it hands control
+/// to. Only grants of `DEFAULT_ADMIN_ROLE` (the zero role) count for
+/// AccessControl, as lesser roles can't reassign admins.
+fn decode(log: &eth::Log) -> Option<(&'static str, String)> {
+    let topic0 = log.topics.first()?.as_slice();
+    if topic0 == OWNERSHIP_TRANSFERRED_TOPIC {
+        Some(("ownership", abi::topic_address(log, 2)?))
+    } else if topic0 == ADMIN_CHANGED_TOPIC {
+        Some(("proxy_admin", abi::word_address(&log.data, 1)?))
+    } else if topic0 == ROLE_GRANTED_TOPIC && log.topics.get(1)?.iter().all(|b| *b == 0) {
+        Some(("default_admin_role", abi::topic_address(log, 2)?))
+    } else {
+        None
+    }
+}
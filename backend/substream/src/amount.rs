@@ -0,0 +1,63 @@
+use crate::annotations::Annotations;
+use crate::u256::U256;
+
+/// Decimals of a token in the `tokens` param or the curated token list. The
+/// chain's native asset is keyed as `"native"`.
+pub fn token_decimals(annotations: &Annotations, token: &str) -> Option<u8> {
+    if token == "native" {
+        return Some(18);
+    }
+    annotations.token(token, |_, decimals, _| decimals)
+}
+
+/// Renders `raw / 10^decimals` exactly, e.g. `1500000` with 6 decimals is
+/// `"1.5"`. Never uses exponent notation and drops trailing fractional zeros.
+pub fn to_decimal(raw: U256, decimals: u8) -> String {
+    let digits = raw.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (int, frac) = padded.split_at(padded.len() - decimals);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        int.to_string()
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+/// JSON members for an amount of `token`: the raw integer as `"key"` and, when
+/// the token's decimals are known, the scaled value as `"key_decimal"`. Both
+/// are strings so consumers never round-trip them through floats.
+pub fn json_fields(annotations: &Annotations, key: &str, raw: U256, token: &str) -> String {
+    match token_decimals(annotations, token) {
+        Some(decimals) => format!(
+            "\"{}\":\"{}\",\"{}_decimal\":\"{}\"",
+            key,
+            raw,
+            key,
+            to_decimal(raw, decimals)
+        ),
+        None => format!("\"{}\":\"{}\"", key, raw),
+    }
+}
+
+/// Whether `raw` is at least `min_units` whole tokens. Tokens without known
+/// decimals can't be compared and never qualify.
+pub fn at_least_units(annotations: &Annotations, raw: U256, token: &str, min_units: u64) -> bool {
+    let Some(decimals) = token_decimals(annotations, token) else {
+        return false;
+    };
+    raw >= U256::from(min_units).saturating_mul(U256::exp10(decimals as usize))
+}
+
+/// Whether `raw` is more than `units` whole tokens, with the same caveat.
+pub fn above_units(annotations: &Annotations, raw: U256, token: &str, units: u64) -> bool {
+    let Some(decimals) = token_decimals(annotations, token) else {
+        return false;
+    };
+    raw > U256::from(units).saturating_mul(U256::exp10(decimals as usize))
+}
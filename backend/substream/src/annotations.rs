@@ -0,0 +1,133 @@
+use crate::chain::Profile;
+use crate::params::CanonicalToken;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::Tag;
+
+/// One chain's curated token list, protocol registry and labels.
+pub struct Tables {
+    /// `(address, symbol, decimals, peg)`
+    pub tokens: &'static [(&'static str, &'static str, u8, &'static str)],
+    /// `(address, asset)`: wrapped native (`"native"`) and bridged tokens
+    /// with the asset they stand for.
+    pub equivalents: &'static [(&'static str, &'static str)],
+    /// `(address, protocol, protocol_version)`
+    pub protocols: &'static [(&'static str, &'static str, &'static str)],
+    /// `(address, labels)`
+    pub labels: &'static [(&'static str, &'static [&'static str])],
+}
+
+pub mod data {
+    include!(concat!(env!("OUT_DIR"), "/annotations.rs"));
+}
+
+/// Lookups of one chain profile's curated lists, with the `tokens` param
+/// taking precedence over its token list. Built from params by
+/// `Params::annotations` and passed to everything that reads them.
+#[derive(Clone, Copy)]
+pub struct Annotations<'a> {
+    tables: &'static Tables,
+    tokens: &'a [CanonicalToken],
+}
+
+impl<'a> Annotations<'a> {
+    pub fn new(profile: &Profile, tokens: &'a [CanonicalToken]) -> Self {
+        Annotations {
+            tables: profile.annotations,
+            tokens,
+        }
+    }
+
+    /// Reads `(symbol, decimals, peg)` of a token from the `tokens` param, or
+    /// else the chain's curated token list. `peg` is empty for unpegged
+    /// tokens.
+    pub fn token<T>(&self, address: &str, read: impl FnOnce(&str, u8, &str) -> T) -> Option<T> {
+        if let Some(token) = self.tokens.iter().find(|t| t.address == address) {
+            return Some(read(&token.symbol, token.decimals, &token.peg));
+        }
+        let tokens = self.tables.tokens;
+        let i = tokens
+            .binary_search_by(|(a, _, _, _)| (*a).cmp(address))
+            .ok()?;
+        let &(_, symbol, decimals, peg) = tokens.get(i)?;
+        Some(read(symbol, decimals, peg))
+    }
+
+    /// The asset a listed token stands for: the token it is equivalent to,
+    /// `"native"` for the wrapped native token, or else the token itself.
+    /// `None` for tokens the lists don't have.
+    pub fn asset(&self, address: &str) -> Option<String> {
+        if address == "native" {
+            return Some(address.to_string());
+        }
+        let equivalents = self.tables.equivalents;
+        let equivalent = equivalents
+            .binary_search_by(|(a, _)| (*a).cmp(address))
+            .ok()
+            .and_then(|i| equivalents.get(i));
+        if let Some((_, equivalent)) = equivalent {
+            return Some(equivalent.to_string());
+        }
+        self.token(address, |_, _, _| address.to_string())
+    }
+
+    /// Whether two tokens are the same asset, wrapped or bridged. Unlisted
+    /// tokens are only the same as themselves.
+    pub fn same_asset(&self, a: &str, b: &str) -> bool {
+        a == b
+            || self
+                .asset(a)
+                .is_some_and(|of_a| self.asset(b).as_ref() == Some(&of_a))
+    }
+
+    pub fn protocol(&self, address: &str) -> Option<Tag> {
+        let protocols = self.tables.protocols;
+        let i = protocols
+            .binary_search_by(|(a, _, _)| (*a).cmp(address))
+            .ok()?;
+        let &(_, protocol, protocol_version) = protocols.get(i)?;
+        Some(Tag::protocol(protocol, protocol_version))
+    }
+
+    pub fn labels(&self, address: &str) -> &'static [&'static str] {
+        let labels = self.tables.labels;
+        labels
+            .binary_search_by(|(a, _)| (*a).cmp(address))
+            .ok()
+            .and_then(|i| labels.get(i))
+            .map_or(&[], |(_, labels)| labels)
+    }
+
+    /// Fills the token symbol, any registry protocol the decoder didn't
+    /// already set, and the labels of every address on the event.
+    pub fn annotate(&self, event: &mut CandidateEvent) {
+        if let Some((symbol, peg)) = self.token(&event.contract_address, |symbol, _, peg| {
+            (symbol.to_string(), peg.to_string())
+        }) {
+            event.token_symbol = symbol;
+            event.token_peg = peg;
+        }
+        event.asset = self.asset(&event.contract_address).unwrap_or_default();
+        if event.protocol.is_empty() {
+            if let Some(tag) = self.protocol(&event.contract_address) {
+                event.protocol = tag.protocol.to_string();
+                event.protocol_version = tag.protocol_version.to_string();
+            }
+        }
+
+        let mut labels: Vec<String> = Vec::new();
+        for address in [
+            &event.contract_address,
+            &event.from,
+            &event.to,
+            &event.owner,
+            &event.spender,
+        ] {
+            for label in self.labels(address) {
+                if !labels.iter().any(|l| l == label) {
+                    labels.push(label.to_string());
+                }
+            }
+        }
+        event.labels = labels;
+    }
+}
@@ -0,0 +1,81 @@
+use substreams::store::{StoreGet, StoreGetInt64};
+
+use crate::params::Params;
+use crate::pb::pulseproof::CandidateEvent;
+
+/// Key holding the number of the latest block with at least one transaction.
+pub const NONEMPTY_KEY: &str = "nonempty";
+
+/// Key holding the latest block in which a watched contract emitted a log.
+pub fn active_key(contract: &str) -> String {
+    format!("active:{}", contract)
+}
+
+/// Block-production and protocol-liveness anomalies for one block. Empty-run
+/// and silence events fire on the block the run reaches its threshold, so a
+/// single outage is reported once rather than on every block it lasts. They
+/// have no transaction or log, so `log_index` numbers them within the block.
+pub fn anomalies(
+    block_number: u64,
+    gap_seconds: Option<u64>,
+    activity: &StoreGetInt64,
+    params: &Params,
+) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+
+    if let Some(gap) = gap_seconds.filter(|gap| *gap > params.halt_gap_seconds) {
+        events.push(anomaly(
+            block_number,
+            String::new(),
+            format!("{{\"kind\":\"timestamp_gap\",\"gap_seconds\":{}}}", gap),
+        ));
+    }
+
+    if let Some(last) = last_block(activity, NONEMPTY_KEY) {
+        if block_number.saturating_sub(last) == params.empty_block_run {
+            events.push(anomaly(
+                block_number,
+                String::new(),
+                format!(
+                    "{{\"kind\":\"empty_blocks\",\"blocks\":{},\"last_nonempty_block\":{}}}",
+                    params.empty_block_run, last
+                ),
+            ));
+        }
+    }
+
+    for contract in params.watch.iter() {
+        let Some(last) = last_block(activity, &active_key(contract)) else {
+            continue;
+        };
+        if block_number.saturating_sub(last) == params.silent_blocks {
+            events.push(anomaly(
+                block_number,
+                contract.clone(),
+                format!(
+                    "{{\"kind\":\"protocol_silent\",\"blocks\":{},\"last_active_block\":{}}}",
+                    params.silent_blocks, last
+                ),
+            ));
+        }
+    }
+
+    for (position, event) in events.iter_mut().enumerate() {
+        event.log_index = position as u64;
+    }
+    events
+}
+
+fn last_block(activity: &StoreGetInt64, key: &str) -> Option<u64> {
+    activity.get_last(key).map(|number| number.max(0) as u64)
+}
+
+fn anomaly(block_number: u64, contract_address: String, metadata: String) -> CandidateEvent {
+    CandidateEvent {
+        block_number,
+        contract_address,
+        event_type: "ChainAnomaly".to_string(),
+        metadata,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use substreams::store::{StoreGet, StoreGetString};
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::{abi, amount, calls, poisoning, sender};
+
+// Approval(address,address,uint256)
+const APPROVAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+
+/// Key listing the grants `owner` made `spender` on `token` as
+/// `block:ordinal` entries joined by `;`, oldest first.
+pub fn approval_key(owner: &str, token: &str, spender: &str) -> String {
+    format!("approval:{}:{}:{}", owner, token, spender)
+}
+
+pub fn encode_grant(block_number: u64, ordinal: u64) -> String {
+    format!("{}:{};", block_number, ordinal)
+}
+
+pub struct Grant {
+    pub ordinal: u64,
+    pub owner: String,
+    pub token: String,
+    pub spender: String,
+}
+
+/// Nonzero ERC20 approvals in the block, EIP-2612 permits included since they
+/// emit the same log. Revocations grant nothing and are skipped, as are
+/// ERC-721 approvals (token id as a fourth topic).
+pub fn grants(blk: &eth::Block) -> Vec<Grant> {
+    calls::block_logs(blk)
+        .filter(|(_, log)| {
+            log.topics.len() == 3
+                && log.topics.first().is_some_and(|t| *t == APPROVAL_TOPIC)
+                && abi::word_u256(&log.data, 0).is_some_and(|value| !value.is_zero())
+        })
+        .filter_map(|(_, log)| {
+            Some(Grant {
+                ordinal: log.ordinal,
+                owner: abi::topic_address(&log, 1)?,
+                token: format!("0x{}", hex::encode(&log.address)),
+                spender: abi::topic_address(&log, 2)?,
+            })
+        })
+        .collect()
+}
+
+/// Address that executed the call behind each log of `tx`, keyed by ordinal:
+/// the caller of the call that emitted it, or the transaction sender on
+/// blocks without call traces.
+pub fn executors(tx: &eth::TransactionTrace) -> Vec<(u64, Cow<'_, [u8]>)> {
+    if tx.calls.is_empty() {
+        let Some(sender) = sender::sender(tx) else {
+            return Vec::new();
+        };
+        return calls::tx_logs(tx)
+            .0
+            .iter()
+            .map(|log| (log.ordinal, sender.clone()))
+            .collect();
+    }
+    tx.calls
+        .iter()
+        .filter(|call| !call.state_reverted)
+        .flat_map(|call| {
+            call.logs
+                .iter()
+                .map(|log| (log.ordinal, Cow::Borrowed(&call.caller[..])))
+        })
+        .collect()
+}
+
+/// The only grant `owner` ever made `spender` on the token, when it came
+/// before `ordinal` of `block_number` and at most `window_blocks` earlier.
+/// `None` as soon as an earlier grant shows the spender isn't new to the
+/// owner.
+pub fn first_grant(
+    approvals: &StoreGetString,
+    key: &str,
+    block_number: u64,
+    ordinal: u64,
+    window_blocks: u64,
+) -> Option<u64> {
+    let entries = approvals.get_last(key)?;
+    let mut grants = entries.split(';').filter_map(|entry| {
+        let (block, at) = entry.split_once(':')?;
+        Some((block.parse::<u64>().ok()?, at.parse::<u64>().ok()?))
+    });
+    let (block, at) = grants.next()?;
+    // Grants later in this block are already in the store but can't have
+    // enabled the transfer.
+    let before = |(b, o): (u64, u64)| b < block_number || (b == block_number && o < ordinal);
+    if !before((block, at)) || grants.any(before) {
+        return None;
+    }
+    (block_number - block <= window_blocks).then_some(block)
+}
+
+/// `ApprovalDrainCandidate` events for transfers a spender executed out of an
+/// owner's balance within `window_blocks` of the owner's first-ever approval
+/// of that spender on the token: the shape of an approval phishing drain.
+pub fn drains(
+    blk: &eth::Block,
+    approvals: &StoreGetString,
+    window_blocks: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let executors: BTreeMap<u64, Cow<[u8]>> =
+        blk.transaction_traces.iter().flat_map(executors).collect();
+
+    poisoning::transfers(blk)
+        .into_iter()
+        .filter_map(|transfer| {
+            let executor = executors.get(&transfer.ordinal)?;
+            let spender = format!("0x{}", hex::encode(executor));
+            if spender == transfer.from {
+                return None;
+            }
+            let token = format!("0x{}", hex::encode(&transfer.token));
+            let granted = first_grant(
+                approvals,
+                &approval_key(&transfer.from, &token, &spender),
+                blk.number,
+                transfer.ordinal,
+                window_blocks,
+            )?;
+            Some(CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(transfer.tx_hash)),
+                block_number: blk.number,
+                log_index: transfer.log_index as u64,
+                event_type: "ApprovalDrainCandidate".to_string(),
+                owner: transfer.from.clone(),
+                spender,
+                from: transfer.from,
+                to: transfer.to,
+                metadata: format!(
+                    "{{\"approved_block\":{},\"blocks_since_approval\":{},{}}}",
+                    granted,
+                    blk.number - granted,
+                    amount::json_fields(annotations, "amount", transfer.amount, &token)
+                ),
+                contract_address: token,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,272 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::abi;
+use crate::annotations::Annotations;
+use crate::u256::U256;
+
+// Uniswap V2 style Swap(address,uint256,uint256,uint256,uint256,address)
+const SWAP_TOPIC: [u8; 32] =
+    hex_literal::hex!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+
+// A loop needs at least this many pools to be worth reporting; two-pool
+// round trips are plain backruns and far too common to be useful.
+const MIN_POOLS: usize = 3;
+
+/// One pool hop reconstructed from the token transfers around a Swap log.
+struct Hop<'a> {
+    pool: &'a [u8],
+    token_in: &'a [u8],
+    amount_in: U256,
+    token_out: &'a [u8],
+    amount_out: U256,
+    log_index: u32,
+}
+
+pub struct ArbLoop {
+    pub token: Vec<u8>,
+    pub pools: Vec<Vec<u8>>,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub profit: U256,
+    pub log_index: u32,
+}
+
+/// Looks for a chain of at least three consecutive swaps in one transaction
+/// where each hop's output token feeds the next hop and the last hop returns
+/// more of the starting token (or an equivalent one) than the first hop
+/// consumed.
+pub fn detect(logs: &[eth::Log], annotations: &Annotations) -> Option<ArbLoop> {
+    let hops = reconstruct_hops(logs);
+
+    for start in 0..hops.len() {
+        for end in (start + MIN_POOLS - 1)..hops.len() {
+            let Some(window) = hops.get(start..=end).filter(|window| is_chained(window)) else {
+                break;
+            };
+            let (Some(first), Some(last)) = (window.first(), window.last()) else {
+                continue;
+            };
+            if same_asset(annotations, last.token_out, first.token_in)
+                && last.amount_out > first.amount_in
+            {
+                return Some(ArbLoop {
+                    token: first.token_in.to_vec(),
+                    pools: window.iter().map(|h| h.pool.to_vec()).collect(),
+                    amount_in: first.amount_in,
+                    amount_out: last.amount_out,
+                    profit: last.amount_out - first.amount_in,
+                    log_index: last.log_index,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Equivalent tokens share decimals, so a loop may start with a bridged or
+/// wrapped representation and close on another.
+fn same_asset(annotations: &Annotations, a: &[u8], b: &[u8]) -> bool {
+    a == b
+        || annotations.same_asset(
+            &format!("0x{}", hex::encode(a)),
+            &format!("0x{}", hex::encode(b)),
+        )
+}
+
+fn is_chained(hops: &[Hop]) -> bool {
+    hops.windows(2)
+        .all(|pair| matches!(pair, [a, b] if a.token_out == b.token_in))
+}
+
+/// Pairs every Swap log with the transfers into and out of the emitting pool
+/// that happened since that pool's previous swap in the transaction.
+fn reconstruct_hops(logs: &[eth::Log]) -> Vec<Hop<'_>> {
+    let mut hops = Vec::new();
+    let mut consumed = vec![false; logs.len()];
+
+    for (swap_pos, swap) in logs.iter().enumerate() {
+        if swap.topics.first().map(Vec::as_slice) != Some(&SWAP_TOPIC[..]) {
+            continue;
+        }
+
+        let pool = swap.address.as_slice();
+        let mut token_in = None;
+        let mut token_out = None;
+
+        for (log, used) in logs.iter().zip(consumed.iter_mut()).take(swap_pos) {
+            if *used {
+                continue;
+            }
+            let Some(transfer) = abi::transfer(log) else {
+                continue;
+            };
+            let amount = transfer.amount;
+
+            if transfer.to == pool && token_in.is_none() {
+                token_in = Some((log.address.as_slice(), amount));
+                *used = true;
+            } else if transfer.from == pool && token_out.is_none() {
+                token_out = Some((log.address.as_slice(), amount));
+                *used = true;
+            }
+        }
+
+        if let (Some((token_in, amount_in)), Some((token_out, amount_out))) = (token_in, token_out)
+        {
+            hops.push(Hop {
+                pool,
+                token_in,
+                amount_in,
+                token_out,
+                amount_out,
+                log_index: swap.index,
+            });
+        }
+    }
+
+    hops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::Params;
+
+    const TRANSFER_TOPIC: [u8; 32] =
+        hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+    const TRADER: [u8; 20] = [0x01; 20];
+
+    /// One swap: its pool, the token and amount paid in, and the token and
+    /// amount paid out, as address bytes repeated 20 times.
+    type Leg = (u8, u8, u64, u8, u64);
+
+    fn address_topic(address: &[u8]) -> Vec<u8> {
+        let mut topic = vec![0; 12];
+        topic.extend_from_slice(address);
+        topic
+    }
+
+    fn word(value: u64) -> Vec<u8> {
+        let mut word = vec![0; 24];
+        word.extend_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    fn transfer(token: u8, from: &[u8], to: &[u8], amount: u64) -> eth::Log {
+        eth::Log {
+            address: vec![token; 20],
+            topics: vec![
+                TRANSFER_TOPIC.to_vec(),
+                address_topic(from),
+                address_topic(to),
+            ],
+            data: word(amount),
+            ..Default::default()
+        }
+    }
+
+    /// The logs of a transaction swapping through `legs` in order: each pool
+    /// is paid, pays out, then emits its Swap.
+    fn logs(legs: &[Leg]) -> Vec<eth::Log> {
+        let mut logs: Vec<eth::Log> = legs
+            .iter()
+            .flat_map(|&(pool, token_in, amount_in, token_out, amount_out)| {
+                let pool = [pool; 20];
+                [
+                    transfer(token_in, &TRADER, &pool, amount_in),
+                    transfer(token_out, &pool, &TRADER, amount_out),
+                    eth::Log {
+                        address: pool.to_vec(),
+                        topics: vec![SWAP_TOPIC.to_vec()],
+                        data: word(0).repeat(4),
+                        ..Default::default()
+                    },
+                ]
+            })
+            .collect();
+        for (index, log) in logs.iter_mut().enumerate() {
+            log.index = index as u32;
+        }
+        logs
+    }
+
+    fn detect_loop(legs: &[Leg]) -> Option<ArbLoop> {
+        detect(&logs(legs), &Params::default().annotations())
+    }
+
+    #[test]
+    fn reconstructs_one_hop_per_swap() {
+        let logs = logs(&[(0xa1, 0x0a, 100, 0x0b, 200), (0xa2, 0x0b, 200, 0x0c, 300)]);
+        let hops: Vec<_> = reconstruct_hops(&logs)
+            .iter()
+            .map(|h| {
+                (
+                    h.pool.to_vec(),
+                    h.token_in.to_vec(),
+                    h.amount_in,
+                    h.token_out.to_vec(),
+                    h.amount_out,
+                    h.log_index,
+                )
+            })
+            .collect();
+        assert_eq!(
+            hops,
+            [
+                (
+                    vec![0xa1; 20],
+                    vec![0x0a; 20],
+                    U256::from(100),
+                    vec![0x0b; 20],
+                    U256::from(200),
+                    2,
+                ),
+                (
+                    vec![0xa2; 20],
+                    vec![0x0b; 20],
+                    U256::from(200),
+                    vec![0x0c; 20],
+                    U256::from(300),
+                    5,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn three_pool_profitable_loop_fires() {
+        let arb = detect_loop(&[
+            (0xa1, 0x0a, 100, 0x0b, 200),
+            (0xa2, 0x0b, 200, 0x0c, 300),
+            (0xa3, 0x0c, 300, 0x0a, 110),
+        ]);
+        let found = arb.map(|arb| (arb.token, arb.pools, arb.profit, arb.log_index));
+        assert_eq!(
+            found,
+            Some((
+                vec![0x0a; 20],
+                vec![vec![0xa1; 20], vec![0xa2; 20], vec![0xa3; 20]],
+                U256::from(10),
+                8,
+            ))
+        );
+    }
+
+    #[test]
+    fn two_pool_round_trip_does_not_fire() {
+        let arb = detect_loop(&[(0xa1, 0x0a, 100, 0x0b, 200), (0xa2, 0x0b, 200, 0x0a, 110)]);
+        assert!(arb.is_none());
+    }
+
+    #[test]
+    fn broken_chain_does_not_fire() {
+        // The second pool is paid in a token the first didn't pay out.
+        let arb = detect_loop(&[
+            (0xa1, 0x0a, 100, 0x0b, 200),
+            (0xa2, 0x0c, 200, 0x0d, 300),
+            (0xa3, 0x0d, 300, 0x0a, 110),
+        ]);
+        assert!(arb.is_none());
+    }
+}
@@ -0,0 +1,19 @@
+use std::collections::BTreeSet;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::calls;
+
+/// Index key of a log signature, as `blockFilter` queries name it.
+pub fn topic_key(topic0: &[u8]) -> String {
+    format!("evt_sig:0x{}", hex::encode(topic0))
+}
+
+/// One `evt_sig` key per distinct `topic0` logged in the block, sorted.
+/// Anonymous logs have no signature and add nothing.
+pub fn keys(blk: &eth::Block) -> Vec<String> {
+    let topics: BTreeSet<Vec<u8>> = calls::block_logs(blk)
+        .filter_map(|(_, log)| log.topics.first().cloned())
+        .collect();
+    topics.iter().map(|topic| topic_key(topic)).collect()
+}
@@ -0,0 +1,43 @@
+use tiny_keccak::{Hasher, Keccak};
+
+pub const BLOOM_BYTES: usize = 256;
+
+/// 2048-bit bloom using the same bit selection as Ethereum's receipt
+/// `logsBloom`, so sinks can test membership with the bloom code they already
+/// have for headers.
+pub struct AddressBloom([u8; BLOOM_BYTES]);
+
+impl Default for AddressBloom {
+    fn default() -> Self {
+        AddressBloom([0u8; BLOOM_BYTES])
+    }
+}
+
+impl AddressBloom {
+    pub fn insert(&mut self, address: &[u8]) {
+        let mut hash = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(address);
+        keccak.finalize(&mut hash);
+
+        for &[high, low] in hash.as_chunks::<2>().0.iter().take(3) {
+            let bit = (((high as usize) << 8) | low as usize) & (BLOOM_BYTES * 8 - 1);
+            if let Some(byte) = self.0.get_mut(BLOOM_BYTES - 1 - bit / 8) {
+                *byte |= 1 << (bit % 8);
+            }
+        }
+    }
+
+    /// Adds a `0x`-prefixed hex address; empty or malformed values are ignored.
+    pub fn insert_hex(&mut self, address: &str) {
+        if let Ok(bytes) = hex::decode(address.trim_start_matches("0x")) {
+            if bytes.len() == 20 {
+                self.insert(&bytes);
+            }
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
@@ -0,0 +1,263 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::abi;
+use crate::pb::pulseproof::{BridgeMessage, CandidateEvent};
+use crate::taxonomy::Tag;
+use crate::u256::U256;
+
+// LayerZero V2 EndpointV2, deployed at the same address on every EVM chain.
+const LAYERZERO_ENDPOINT: [u8; 20] = hex_literal::hex!("1a44076050125825900e736c501f859c50fe728c");
+// PacketSent(bytes,bytes,address)
+const PACKET_SENT_TOPIC: [u8; 32] =
+    hex_literal::hex!("1ab700d4ced0c005b164c0f789fd09fcbb0156d4c2041b8a3bfbcd961cd1567f");
+// PacketDelivered((uint32,bytes32,uint64),address)
+const PACKET_DELIVERED_TOPIC: [u8; 32] =
+    hex_literal::hex!("3cd5e48f9730b129dc7550f0fcea9c767b7be37837cd10e55eb35f734f4bca04");
+// Hyperlane Mailbox Dispatch(address,uint32,bytes32,bytes)
+const DISPATCH_TOPIC: [u8; 32] =
+    hex_literal::hex!("769f711d20c679153d382254f59892613b58a97cc876b249134ac25c80f9c814");
+// Hyperlane Mailbox Process(uint32,bytes32,address)
+const PROCESS_TOPIC: [u8; 32] =
+    hex_literal::hex!("0d381c2a574ae8f04e213db7cfb4df8df712cdbd427d9868ffef380660ca6574");
+// Hyperlane Mailbox ProcessId(bytes32), logged right after Process.
+const PROCESS_ID_TOPIC: [u8; 32] =
+    hex_literal::hex!("1cae38cdd3d3919489272725a5ae62a4f48b2989b0dae843d3c279fee18073a9");
+// Polygon PoS StateSender on Ethereum, and StateReceiver on Polygon.
+const STATE_SENDER: [u8; 20] = hex_literal::hex!("28e4f3a7f651294b9564800b2d01f35189a5bfbe");
+const STATE_RECEIVER: [u8; 20] = hex_literal::hex!("0000000000000000000000000000000000001001");
+// StateSynced(uint256,address,bytes)
+const STATE_SYNCED_TOPIC: [u8; 32] =
+    hex_literal::hex!("103fed9db65eac19c4d870f49ab7520fe03b99f1838e5996caf47e9e43308392");
+// StateCommitted(uint256,bool)
+const STATE_COMMITTED_TOPIC: [u8; 32] =
+    hex_literal::hex!("5a22725590b0a51c923940223f7458512164b1113359a735e86e7f27f44791ee");
+// Chains the Polygon PoS bridge syncs state between.
+const ETHEREUM_CHAIN_ID: u64 = 1;
+const POLYGON_CHAIN_ID: u64 = 137;
+
+fn keccak(bytes: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(bytes);
+    keccak.finalize(&mut hash);
+    hash
+}
+
+fn be(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |n, b| (n << 8) | *b as u64)
+}
+
+fn word_u64(data: &[u8], index: usize) -> Option<u64> {
+    let word = abi::word_u256(data, index)?;
+    (word <= U256::from(u64::MAX)).then(|| word.as_u64())
+}
+
+/// A 32-byte cross-chain account: an EVM address when it is one, the whole
+/// word otherwise (non-EVM chains).
+fn account(word: &[u8]) -> String {
+    match abi::address_bytes(word) {
+        Some(address) if word.starts_with(&[0; 12]) => format!("0x{}", hex::encode(address)),
+        _ => format!("0x{}", hex::encode(word)),
+    }
+}
+
+fn message(
+    direction: &str,
+    message_id: String,
+    nonce: u64,
+    src_chain_id: u64,
+    dst_chain_id: u64,
+    sender: String,
+    receiver: String,
+) -> BridgeMessage {
+    BridgeMessage {
+        direction: direction.to_string(),
+        message_id,
+        nonce,
+        src_chain_id,
+        dst_chain_id,
+        sender,
+        receiver,
+        ..Default::default()
+    }
+}
+
+fn decode(
+    log: &eth::Log,
+    next: Option<&eth::Log>,
+    chain_id: u64,
+    layerzero_eid: u64,
+) -> Option<(Tag, BridgeMessage)> {
+    if log.topics.iter().any(|topic| topic.len() != 32) {
+        return None;
+    }
+    let topic0 = log.topics.first()?.as_slice();
+
+    if log.address == LAYERZERO_ENDPOINT && topic0 == PACKET_SENT_TOPIC {
+        // Packet v1: version, nonce, srcEid, sender, dstEid, receiver, guid,
+        // message, tightly packed.
+        let packet = abi::word_bytes(&log.data, 0)?.first_chunk::<113>()?;
+        if packet[0] != 1 {
+            return None;
+        }
+        return Some((
+            Tag::protocol("layerzero", "v2"),
+            message(
+                "sent",
+                format!("0x{}", hex::encode(&packet[81..113])),
+                be(&packet[1..9]),
+                be(&packet[9..13]),
+                be(&packet[45..49]),
+                account(&packet[13..45]),
+                account(&packet[49..81]),
+            ),
+        ));
+    }
+    if log.address == LAYERZERO_ENDPOINT && topic0 == PACKET_DELIVERED_TOPIC {
+        let src_eid = u32::try_from(word_u64(&log.data, 0)?).ok()?;
+        let sender = abi::word(&log.data, 1)?;
+        let nonce = word_u64(&log.data, 2)?;
+        let receiver = abi::word(&log.data, 3)?;
+        // The guid the sending endpoint assigned: keccak256 of the packed
+        // nonce, srcEid, sender, dstEid and receiver.
+        let mut header = Vec::with_capacity(80);
+        header.extend_from_slice(&nonce.to_be_bytes());
+        header.extend_from_slice(&src_eid.to_be_bytes());
+        header.extend_from_slice(sender);
+        header.extend_from_slice(&(layerzero_eid as u32).to_be_bytes());
+        header.extend_from_slice(receiver);
+        return Some((
+            Tag::protocol("layerzero", "v2"),
+            message(
+                "received",
+                format!("0x{}", hex::encode(keccak(&header))),
+                nonce,
+                src_eid as u64,
+                layerzero_eid,
+                account(sender),
+                account(receiver),
+            ),
+        ));
+    }
+    if topic0 == DISPATCH_TOPIC {
+        // Message: version, nonce, origin, sender, destination, recipient,
+        // body, tightly packed; its id is its hash.
+        let message_bytes = abi::word_bytes(&log.data, 0)?;
+        let body = message_bytes.first_chunk::<77>()?;
+        return Some((
+            Tag::protocol("hyperlane", "v3"),
+            message(
+                "sent",
+                format!("0x{}", hex::encode(keccak(message_bytes))),
+                be(&body[1..5]),
+                be(&body[5..9]),
+                be(&body[41..45]),
+                account(&body[9..41]),
+                account(&body[45..77]),
+            ),
+        ));
+    }
+    if topic0 == PROCESS_TOPIC && log.topics.len() == 4 {
+        let next = next.filter(|next| {
+            next.address == log.address
+                && next.topics.len() == 2
+                && next.topics.first().is_some_and(|t| *t == PROCESS_ID_TOPIC)
+        })?;
+        return Some((
+            Tag::protocol("hyperlane", "v3"),
+            message(
+                "received",
+                format!("0x{}", hex::encode(next.topics.get(1)?)),
+                0,
+                be(log.topics.get(1)?.get(28..)?),
+                chain_id,
+                account(log.topics.get(2)?),
+                account(log.topics.get(3)?),
+            ),
+        ));
+    }
+    if log.address == STATE_SENDER && topic0 == STATE_SYNCED_TOPIC && log.topics.len() == 3 {
+        let id = abi::topic_u256(log, 1)?;
+        return Some((
+            Tag::protocol("polygon-pos", ""),
+            message(
+                "sent",
+                id.to_string(),
+                0,
+                ETHEREUM_CHAIN_ID,
+                POLYGON_CHAIN_ID,
+                String::new(),
+                account(log.topics.get(2)?),
+            ),
+        ));
+    }
+    if log.address == STATE_RECEIVER && topic0 == STATE_COMMITTED_TOPIC && log.topics.len() == 2 {
+        let id = abi::topic_u256(log, 1)?;
+        return Some((
+            Tag::protocol("polygon-pos", ""),
+            message(
+                "received",
+                id.to_string(),
+                0,
+                ETHEREUM_CHAIN_ID,
+                POLYGON_CHAIN_ID,
+                String::new(),
+                String::new(),
+            ),
+        ));
+    }
+    None
+}
+
+/// Key both sides of a cross-chain message share:
+/// `protocol:src_chain_id:dst_chain_id:message_id`.
+pub fn link_key(protocol: &str, message: &BridgeMessage) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        protocol, message.src_chain_id, message.dst_chain_id, message.message_id
+    )
+}
+
+/// `CrossChainMessage` events for messages the transaction sends or delivers
+/// over LayerZero V2, Hyperlane or the Polygon PoS state sync, with the
+/// message's `link_key` so multi-chain sinks can join the two sides. Chain
+/// ids are the protocol's own: LayerZero endpoint ids, Hyperlane domains (the
+/// EVM chain id on EVM chains), and chain ids for Polygon PoS. The receiving
+/// side's chain comes from the `chain_id` and `layerzero_eid` params. Hyperlane
+/// mailboxes have no canonical address and are matched on topics alone.
+pub fn messages(
+    tx_hash: &[u8],
+    logs: &[eth::Log],
+    block_number: u64,
+    chain_id: u64,
+    layerzero_eid: u64,
+) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+    for (pos, log) in logs.iter().enumerate() {
+        let Some((tag, mut bridge)) = decode(log, logs.get(pos + 1), chain_id, layerzero_eid)
+        else {
+            continue;
+        };
+        bridge.link_key = link_key(tag.protocol, &bridge);
+        let mut event = CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(tx_hash)),
+            block_number,
+            log_index: log.index as u64,
+            contract_address: format!("0x{}", hex::encode(&log.address)),
+            event_signature: abi::signature(log),
+            event_type: "CrossChainMessage".to_string(),
+            from: bridge.sender.clone(),
+            to: bridge.receiver.clone(),
+            bridge: Some(bridge),
+            ..Default::default()
+        };
+        if log.address == STATE_RECEIVER {
+            let success = abi::word_u256(&log.data, 0).is_some_and(|word| !word.is_zero());
+            event.metadata = format!("{{\"success\":{}}}", success);
+        }
+        tag.apply(&mut event);
+        events.push(event);
+    }
+    events
+}
@@ -0,0 +1,135 @@
+//! Entry points for the threshold calibration tool in `calibrate/`. It replays
+//! labelled fixture blocks through `map_candidate_events` under a grid of
+//! params and scores each rule against the labels.
+
+use anyhow::Error;
+use prost::Message;
+use substreams_ethereum::pb::eth::v2 as eth;
+
+pub use substreams_ethereum::pb::eth::v2::Block;
+
+use crate::params::Params;
+
+/// One detection, as `(event_type, transaction_hash)`.
+pub type Detection = (String, String);
+
+/// Matches of one rule against the labels, summed over the fixtures.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub rule: String,
+    pub true_positives: u64,
+    pub false_positives: u64,
+    pub false_negatives: u64,
+}
+
+impl Score {
+    /// `None` when the rule flagged nothing.
+    pub fn precision(&self) -> Option<f64> {
+        let flagged = self.true_positives + self.false_positives;
+        (flagged > 0).then(|| self.true_positives as f64 / flagged as f64)
+    }
+
+    /// `None` when nothing was labelled for the rule.
+    pub fn recall(&self) -> Option<f64> {
+        let labelled = self.true_positives + self.false_negatives;
+        (labelled > 0).then(|| self.true_positives as f64 / labelled as f64)
+    }
+}
+
+/// Decodes a fixture block, a protobuf-encoded `sf.ethereum.type.v2.Block`.
+pub fn decode_block(bytes: &[u8]) -> Result<eth::Block, Error> {
+    Ok(eth::Block::decode(bytes)?)
+}
+
+/// Reads a fixture's labels: one `event_type transaction_hash` per line, the
+/// hash lowercased. Blank lines and `#` comments are skipped.
+pub fn parse_labels(text: &str) -> Result<Vec<Detection>, Error> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(rule), Some(tx), None) => Ok((rule.to_string(), tx.to_ascii_lowercase())),
+                _ => Err(anyhow::anyhow!(
+                    "label {:?} is not `event_type tx_hash`",
+                    line
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Every params string the grid spans. Each axis is a `key=v1|v2|...`
+/// argument; a single value fixes the param for every point.
+pub fn grid(axes: &[String]) -> Result<Vec<String>, Error> {
+    let mut points = vec![String::new()];
+    for axis in axes {
+        let (key, values) = axis
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("axis {:?} is not of the form key=v1|v2", axis))?;
+        points = points
+            .iter()
+            .flat_map(|point| {
+                values.split('|').map(move |value| {
+                    if point.is_empty() {
+                        format!("{}={}", key, value)
+                    } else {
+                        format!("{}&{}={}", point, key, value)
+                    }
+                })
+            })
+            .collect();
+    }
+    // Reject a bad key or value before replaying anything.
+    for point in points.iter() {
+        Params::parse(point)?;
+    }
+    Ok(points)
+}
+
+/// The detections `map_candidate_events` makes on a block under `params`,
+/// one per event type and transaction.
+pub fn replay(blk: &eth::Block, params: &str) -> Result<Vec<Detection>, Error> {
+    let params = Params::parse(params)?;
+    let mut found: Vec<Detection> = crate::candidate_events(&params, blk)
+        .into_iter()
+        .map(|event| (event.event_type, event.transaction_hash))
+        .collect();
+    found.sort();
+    found.dedup();
+    Ok(found)
+}
+
+/// Adds one fixture's detections to the per-rule scores. Rules appear once
+/// they are labelled or flagged on any fixture.
+pub fn score(scores: &mut Vec<Score>, labels: &[Detection], found: &[Detection]) {
+    for detection in found {
+        let labelled = labels.contains(detection);
+        update(scores, &detection.0, |score| {
+            if labelled {
+                score.true_positives += 1;
+            } else {
+                score.false_positives += 1;
+            }
+        });
+    }
+    for label in labels.iter().filter(|label| !found.contains(label)) {
+        update(scores, &label.0, |score| score.false_negatives += 1);
+    }
+}
+
+/// Applies `change` to the score of `rule`, adding the rule first when new.
+fn update(scores: &mut Vec<Score>, rule: &str, change: impl FnOnce(&mut Score)) {
+    match scores.iter_mut().find(|s| s.rule == rule) {
+        Some(score) => change(score),
+        None => {
+            let mut score = Score {
+                rule: rule.to_string(),
+                ..Default::default()
+            };
+            change(&mut score);
+            scores.push(score);
+        }
+    }
+}
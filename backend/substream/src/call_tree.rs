@@ -0,0 +1,75 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CallTreeSummary;
+use crate::u256::{self, U256};
+
+/// `0x` and the first four calldata bytes, or just `0x` for plain value
+/// transfers.
+fn selector(input: &[u8]) -> String {
+    format!("0x{}", hex::encode(input.get(..4).unwrap_or_default()))
+}
+
+/// Structure of a transaction's call tree without the trace payloads: how
+/// deep it goes, how many calls and distinct callees it has, the largest
+/// native value a non-reverted call moved at each depth, and the selectors
+/// from the top-level call down to the deepest call that moved value.
+/// `flagged_by` are the event types that flagged the transaction. Blocks
+/// without call traces give the top-level call only.
+pub fn summary(tx: &eth::TransactionTrace, flagged_by: Vec<String>) -> CallTreeSummary {
+    let mut summary = CallTreeSummary {
+        transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+        flagged_by,
+        ..Default::default()
+    };
+
+    if tx.calls.is_empty() {
+        let value = u256::from_pb(tx.value.as_ref());
+        summary.call_count = 1;
+        summary.unique_contracts = 1;
+        summary.max_value_by_depth = vec![value.to_string()];
+        if !value.is_zero() {
+            summary.value_path = vec![selector(&tx.input)];
+        }
+        return summary;
+    }
+
+    let mut contracts: Vec<&[u8]> = Vec::new();
+    let mut max_values: Vec<U256> = Vec::new();
+    let mut deepest: Option<&eth::Call> = None;
+    for call in tx.calls.iter() {
+        if !contracts.contains(&&call.address[..]) {
+            contracts.push(&call.address);
+        }
+        let depth = call.depth as usize;
+        if max_values.len() <= depth {
+            max_values.resize(depth + 1, U256::zero());
+        }
+        let value = u256::from_pb(call.value.as_ref());
+        if call.state_reverted || value.is_zero() {
+            continue;
+        }
+        if let Some(max) = max_values.get_mut(depth) {
+            *max = (*max).max(value);
+        }
+        if deepest.is_none_or(|d| call.depth > d.depth) {
+            deepest = Some(call);
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut next = deepest;
+    while let Some(call) = next {
+        path.push(selector(&call.input));
+        next = (call.depth > 0)
+            .then(|| tx.calls.iter().find(|c| c.index == call.parent_index))
+            .flatten();
+    }
+    path.reverse();
+
+    summary.max_depth = max_values.len().saturating_sub(1) as u32;
+    summary.call_count = tx.calls.len() as u32;
+    summary.unique_contracts = contracts.len() as u32;
+    summary.max_value_by_depth = max_values.iter().map(U256::to_string).collect();
+    summary.value_path = path;
+    summary
+}
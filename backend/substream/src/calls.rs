@@ -0,0 +1,70 @@
+use std::borrow::Cow;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::sender;
+
+/// Logs of a transaction's non-reverted calls in emission order, for blocks
+/// that carry call traces but no receipts.
+pub fn logs(tx: &eth::TransactionTrace) -> Vec<eth::Log> {
+    let mut logs: Vec<eth::Log> = tx
+        .calls
+        .iter()
+        .filter(|call| !call.state_reverted)
+        .flat_map(|call| call.logs.iter().cloned())
+        .collect();
+    logs.sort_by_key(|log| log.ordinal);
+    logs
+}
+
+/// A transaction's logs with where they came from: its receipt, or on light
+/// blocks, which ship without receipts, the logs recorded on its call traces.
+pub fn tx_logs(tx: &eth::TransactionTrace) -> (Cow<'_, [eth::Log]>, &'static str) {
+    match &tx.receipt {
+        Some(receipt) => (Cow::Borrowed(&receipt.logs[..]), "receipt"),
+        None => (Cow::Owned(logs(tx)), "calls"),
+    }
+}
+
+/// Logs of the block's successful transactions in order, each with its
+/// transaction, read through `tx_logs`. Unlike `Block::logs` it doesn't
+/// panic on transactions without a receipt.
+pub fn block_logs(
+    blk: &eth::Block,
+) -> impl Iterator<Item = (&eth::TransactionTrace, Cow<'_, eth::Log>)> {
+    blk.transaction_traces
+        .iter()
+        .filter(|tx| tx.status == eth::TransactionTraceStatus::Succeeded as i32)
+        .flat_map(|tx| {
+            let logs: Vec<Cow<eth::Log>> = match tx_logs(tx).0 {
+                Cow::Borrowed(logs) => logs.iter().map(Cow::Borrowed).collect(),
+                Cow::Owned(logs) => logs.into_iter().map(Cow::Owned).collect(),
+            };
+            logs.into_iter().map(move |log| (tx, log))
+        })
+}
+
+/// `(call index, caller, callee, calldata)` of one call frame.
+pub type Frame<'a> = (u32, Cow<'a, [u8]>, &'a [u8], &'a [u8]);
+
+/// Non-reverted call frames of a transaction when the block has traces, or
+/// just the top-level call otherwise. The top-level caller is the recovered
+/// transaction sender and is left empty when it can't be determined.
+pub fn frames(tx: &eth::TransactionTrace) -> Vec<Frame<'_>> {
+    if tx.calls.is_empty() {
+        let caller = sender::sender(tx).unwrap_or(Cow::Borrowed(&[]));
+        return vec![(0, caller, &tx.to, &tx.input)];
+    }
+    tx.calls
+        .iter()
+        .filter(|call| !call.state_reverted)
+        .map(|call| {
+            (
+                call.index,
+                Cow::Borrowed(&call.caller[..]),
+                &call.address[..],
+                &call.input[..],
+            )
+        })
+        .collect()
+}
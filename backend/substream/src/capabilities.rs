@@ -0,0 +1,47 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+/// Every transaction carries a receipt with its logs.
+pub const RECEIPTS: u32 = 1 << 0;
+/// Transactions carry their internal call tree.
+pub const CALL_TRACES: u32 = 1 << 1;
+/// Calls carry native balance changes.
+pub const BALANCE_CHANGES: u32 = 1 << 2;
+/// Calls carry storage slot changes.
+pub const STATE_CHANGES: u32 = 1 << 3;
+
+/// What a block's payload contains, so heuristics that need traces or state
+/// diffs can be skipped on blocks that don't have them.
+pub struct Capabilities {
+    /// `"extended"`, `"base"`, or `"hybrid"` for base blocks that still ship
+    /// call traces.
+    pub detail_level: &'static str,
+    pub flags: u32,
+}
+
+impl Capabilities {
+    pub fn has(&self, flag: u32) -> bool {
+        self.flags & flag == flag
+    }
+}
+
+pub fn detect(blk: &eth::Block) -> Capabilities {
+    let mut flags = 0;
+    if blk.transaction_traces.iter().all(|tx| tx.receipt.is_some()) {
+        flags |= RECEIPTS;
+    }
+
+    let detail_level = if blk.detail_level == eth::block::DetailLevel::DetaillevelExtended as i32 {
+        flags |= CALL_TRACES | BALANCE_CHANGES | STATE_CHANGES;
+        "extended"
+    } else if blk.transaction_traces.iter().any(|tx| !tx.calls.is_empty()) {
+        flags |= CALL_TRACES;
+        "hybrid"
+    } else {
+        "base"
+    };
+
+    Capabilities {
+        detail_level,
+        flags,
+    }
+}
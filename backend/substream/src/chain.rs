@@ -0,0 +1,72 @@
+use crate::annotations::{self, Tables};
+use crate::taxonomy::Tag;
+
+// Uniswap V3 Swap(address,address,int256,int256,uint160,uint128,int24), also
+// logged by its forks.
+const UNISWAP_V3_SWAP_TOPIC: [u8; 32] =
+    hex_literal::hex!("c42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67");
+// PancakeSwap V3 Swap(address,address,int256,int256,uint160,uint128,int24,uint128,uint128)
+const PANCAKESWAP_V3_SWAP_TOPIC: [u8; 32] =
+    hex_literal::hex!("19b47279256b2a23a1665c810c8d55a1758940ee09377d4f8d26497a3577dc83");
+
+/// What differs between the chains PulseProof runs on, selected with the
+/// `chain` param.
+pub struct Profile {
+    pub name: &'static str,
+    /// Defaults of the `chain_id` and `layerzero_eid` params.
+    pub chain_id: u64,
+    pub layerzero_eid: u64,
+    /// Swap logs of the chain's DEXes classified as `Swap` on top of the V2
+    /// `Swap` log every chain gets, as `(topic0, protocol, protocol_version)`.
+    pub swaps: &'static [([u8; 32], &'static str, &'static str)],
+    /// The chain's curated token list, protocol registry and labels.
+    pub annotations: &'static Tables,
+}
+
+impl Profile {
+    /// The Swap topic and protocol of a chain-specific swap log, by its raw
+    /// topic0.
+    pub fn swap(&self, topic0: &[u8]) -> Option<(&'static [u8; 32], Tag)> {
+        self.swaps
+            .iter()
+            .find(|(topic, _, _)| topic[..] == *topic0)
+            .map(|(topic, protocol, version)| (topic, Tag::protocol(protocol, version)))
+    }
+}
+
+// Polygon keeps the V2-only classification it had before profiles.
+pub static POLYGON: Profile = Profile {
+    name: "polygon",
+    chain_id: 137,
+    layerzero_eid: 30_109,
+    swaps: &[],
+    annotations: &annotations::data::POLYGON,
+};
+
+pub static BNB: Profile = Profile {
+    name: "bnb",
+    chain_id: 56,
+    layerzero_eid: 30_102,
+    swaps: &[
+        (PANCAKESWAP_V3_SWAP_TOPIC, "pancakeswap", "v3"),
+        (UNISWAP_V3_SWAP_TOPIC, "uniswap", "v3"),
+    ],
+    annotations: &annotations::data::BNB,
+};
+
+pub static ARBITRUM: Profile = Profile {
+    name: "arbitrum",
+    chain_id: 42_161,
+    layerzero_eid: 30_110,
+    swaps: &[(UNISWAP_V3_SWAP_TOPIC, "uniswap", "v3")],
+    annotations: &annotations::data::ARBITRUM,
+};
+
+pub static PROFILES: [&Profile; 3] = [&POLYGON, &BNB, &ARBITRUM];
+
+pub fn profile(name: &str) -> Option<&'static Profile> {
+    PROFILES
+        .iter()
+        .copied()
+        .find(|profile| profile.name == name)
+}
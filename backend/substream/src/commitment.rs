@@ -0,0 +1,130 @@
+use prost::Message;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::pb::pulseproof::{BlockCommitment, CandidateEvent, EventProof, ProofStep};
+
+fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    for part in parts {
+        keccak.update(part);
+    }
+    keccak.finalize(&mut hash);
+    hash
+}
+
+/// Merkle leaf of an event: keccak256 of its fingerprint's bytes.
+pub fn leaf(event: &CandidateEvent) -> [u8; 32] {
+    let fingerprint = hex::decode(event.fingerprint.trim_start_matches("0x")).unwrap_or_default();
+    keccak(&[&fingerprint])
+}
+
+/// Bytes an event is committed as: its protobuf encoding. prost writes fields
+/// in tag order and leaves out default values, so equal events always encode
+/// the same.
+pub fn canonical_encoding(event: &CandidateEvent) -> Vec<u8> {
+    event.encode_to_vec()
+}
+
+/// Merkle leaf committing to the whole event: keccak256 of its
+/// `canonical_encoding`.
+pub fn event_leaf(event: &CandidateEvent) -> [u8; 32] {
+    keccak(&[&canonical_encoding(event)])
+}
+
+/// Every level of the binary keccak256 Merkle tree over `leaves`, from the
+/// leaves up to the root. A level with an odd node carries it up unchanged.
+fn levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+        let next = level
+            .chunks(2)
+            .map(|pair| {
+                pair.iter()
+                    .copied()
+                    .reduce(|left, right| keccak(&[&left, &right]))
+                    .unwrap_or_default()
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Binary keccak256 Merkle root over `leaves` in order. A level with an odd
+/// node carries it up unchanged; no leaves give the zero root.
+pub fn root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
+    levels(leaves)
+        .last()
+        .and_then(|top| top.first().copied())
+        .unwrap_or_default()
+}
+
+/// Siblings from the leaf at `index` up to the root. Levels where the node
+/// is carried up unchanged have no step.
+fn path(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<ProofStep> {
+    let mut steps = Vec::new();
+    let below_root = levels.split_last().map_or(&[][..], |(_, below)| below);
+    for level in below_root {
+        let sibling = index ^ 1;
+        if let Some(node) = level.get(sibling) {
+            steps.push(ProofStep {
+                sibling: format!("0x{}", hex::encode(node)),
+                left: sibling < index,
+            });
+        }
+        index /= 2;
+    }
+    steps
+}
+
+/// Commitment of a block's events for on-chain anchoring. `events_root` is
+/// the Merkle root over `event_leaf` of each event in output order; `root`
+/// binds it to the block as keccak256(block number (8 bytes, big-endian) ‖
+/// block hash ‖ events_root). Each event gets the path proving its leaf is
+/// in `events_root`.
+pub fn block_commitment(
+    events: &[CandidateEvent],
+    block_number: u64,
+    block_hash: &[u8],
+) -> BlockCommitment {
+    let leaves: Vec<[u8; 32]> = events.iter().map(event_leaf).collect();
+    let levels = levels(leaves.clone());
+    let events_root = levels
+        .last()
+        .and_then(|top| top.first().copied())
+        .unwrap_or_default();
+    let proofs = events
+        .iter()
+        .zip(leaves.iter())
+        .enumerate()
+        .map(|(index, (event, leaf))| EventProof {
+            index: index as u32,
+            fingerprint: event.fingerprint.clone(),
+            leaf: format!("0x{}", hex::encode(leaf)),
+            path: path(&levels, index),
+        })
+        .collect();
+
+    BlockCommitment {
+        block_number,
+        block_hash: format!("0x{}", hex::encode(block_hash)),
+        events_root: format!("0x{}", hex::encode(events_root)),
+        root: format!(
+            "0x{}",
+            hex::encode(keccak(&[
+                &block_number.to_be_bytes(),
+                block_hash,
+                &events_root
+            ]))
+        ),
+        leaf_count: events.len() as u32,
+        proofs,
+        ..Default::default()
+    }
+}
+
+/// Root of a block's events, in their output order.
+pub fn block_root(events: &[CandidateEvent]) -> String {
+    format!("0x{}", hex::encode(root(events.iter().map(leaf).collect())))
+}
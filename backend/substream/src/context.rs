@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CandidateEvent;
+use crate::{sender, u256};
+
+/// Stamps every event with the block timestamp and, for events tied to one
+/// of the block's transactions, that transaction's sender, recipient, native
+/// value and gas used.
+pub fn apply(events: &mut [CandidateEvent], blk: &eth::Block) {
+    let timestamp = crate::block_timestamp(blk);
+    let txs: BTreeMap<String, &eth::TransactionTrace> = blk
+        .transaction_traces
+        .iter()
+        .map(|tx| (format!("0x{}", hex::encode(&tx.hash)), tx))
+        .collect();
+
+    for event in events.iter_mut() {
+        event.block_timestamp = timestamp;
+        let Some(tx) = txs.get(&event.transaction_hash) else {
+            continue;
+        };
+        event.tx_from = sender::sender(tx)
+            .map(|address| format!("0x{}", hex::encode(address)))
+            .unwrap_or_default();
+        event.tx_to = if tx.to.is_empty() {
+            String::new()
+        } else {
+            format!("0x{}", hex::encode(&tx.to))
+        };
+        event.tx_value = u256::from_pb(tx.value.as_ref()).to_string();
+        event.tx_gas_used = tx.gas_used;
+    }
+}
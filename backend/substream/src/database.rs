@@ -0,0 +1,105 @@
+use crate::pb::pulseproof::{CandidateEvent, CandidateEvents};
+use crate::pb::sf::substreams::sink::database::v1::{
+    table_change, CompositePrimaryKey, DatabaseChanges, Field, TableChange,
+};
+
+/// Table `schema.sql` declares for candidate events.
+const TABLE: &str = "candidate_events";
+
+/// Primary key: block number, transaction hash (empty on block-wide events),
+/// log index and `part`, which together are unique per event.
+fn primary_key(event: &CandidateEvent) -> CompositePrimaryKey {
+    CompositePrimaryKey {
+        keys: [
+            ("block_number", event.block_number.to_string()),
+            ("transaction_hash", event.transaction_hash.clone()),
+            ("log_index", event.log_index.to_string()),
+            ("part", event.part.to_string()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect(),
+    }
+}
+
+/// One row insert per candidate event, in output order.
+pub fn database_changes(events: &CandidateEvents) -> DatabaseChanges {
+    DatabaseChanges {
+        table_changes: events
+            .events
+            .iter()
+            .enumerate()
+            .map(|(ordinal, event)| TableChange {
+                table: TABLE.to_string(),
+                ordinal: ordinal as u64,
+                operation: table_change::Operation::Create as i32,
+                fields: fields(event, &events.config_epoch),
+                primary_key: Some(table_change::PrimaryKey::CompositePk(primary_key(event))),
+            })
+            .collect(),
+    }
+}
+
+fn fields(event: &CandidateEvent, config_epoch: &str) -> Vec<Field> {
+    let mut columns = vec![
+        ("tx_index", event.tx_index.to_string()),
+        ("ordering_key", event.ordering_key.clone()),
+        ("contract_address", event.contract_address.clone()),
+        ("event_signature", event.event_signature.clone()),
+        ("event_type", event.event_type.clone()),
+        ("metadata", event.metadata.clone()),
+        ("retention_class", event.retention_class.clone()),
+        ("severity", event.severity.clone()),
+        ("shadow_severity", event.shadow_severity.clone()),
+        ("from_address", event.from.clone()),
+        ("to_address", event.to.clone()),
+        ("owner", event.owner.clone()),
+        ("spender", event.spender.clone()),
+        ("fingerprint", event.fingerprint.clone()),
+        ("protocol", event.protocol.clone()),
+        ("protocol_version", event.protocol_version.clone()),
+        ("standard", event.standard.clone()),
+        ("token_symbol", event.token_symbol.clone()),
+        ("token_peg", event.token_peg.clone()),
+        ("asset", event.asset.clone()),
+        ("token_class", event.token_class.clone()),
+        ("nonstandard_abi", event.nonstandard_abi.to_string()),
+        ("likely_private", event.likely_private.to_string()),
+        ("flash_funded", event.flash_funded.to_string()),
+        (
+            "gas_token_usage",
+            event.gas_token_usage.is_some().to_string(),
+        ),
+        ("routing_keys", event.routing_keys.join(",")),
+        ("tenants", event.tenants.join(",")),
+        ("config_epoch", config_epoch.to_string()),
+    ];
+    // Decoded columns are numeric and nullable, so they are only written for
+    // the event kind that has them. Transfers and approvals share `value`.
+    if let Some(transfer) = &event.transfer {
+        columns.push(("value", transfer.value.clone()));
+        columns.push(("value_decimal", transfer.value_decimal.clone()));
+    }
+    if let Some(approval) = &event.approval {
+        columns.push(("value", approval.value.clone()));
+        columns.push(("value_decimal", approval.value_decimal.clone()));
+    }
+    if let Some(bridge) = &event.bridge {
+        columns.push(("link_key", bridge.link_key.clone()));
+    }
+    if let Some(swap) = &event.swap {
+        columns.push(("amount0_in", swap.amount0_in.clone()));
+        columns.push(("amount1_in", swap.amount1_in.clone()));
+        columns.push(("amount0_out", swap.amount0_out.clone()));
+        columns.push(("amount1_out", swap.amount1_out.clone()));
+    }
+
+    columns
+        .into_iter()
+        .map(|(name, new_value)| Field {
+            name: name.to_string(),
+            new_value,
+            old_value: String::new(),
+        })
+        .collect()
+}
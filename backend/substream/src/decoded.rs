@@ -0,0 +1,112 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::{
+    ApprovalParams, GovernanceParams, NftTransferParams, OperatorApprovalParams, SwapParams,
+    TransferParams,
+};
+use crate::u256::U256;
+use crate::{abi, amount, nft};
+
+/// Typed parameters of an ERC20 Transfer, for any of the layouts
+/// `abi::transfer` accepts.
+pub fn transfer(annotations: &Annotations, log: &eth::Log) -> Option<TransferParams> {
+    let transfer = abi::transfer(log)?;
+    let token = format!("0x{}", hex::encode(&log.address));
+    Some(TransferParams {
+        from: format!("0x{}", hex::encode(transfer.from)),
+        to: format!("0x{}", hex::encode(transfer.to)),
+        value: transfer.amount.to_string(),
+        value_decimal: decimal(annotations, transfer.amount, &token),
+    })
+}
+
+/// Typed parameters of an ERC20 `Approval(owner, spender, value)`.
+pub fn approval(annotations: &Annotations, log: &eth::Log) -> Option<ApprovalParams> {
+    if log.topics.len() != 3 {
+        return None;
+    }
+    let value = abi::word_u256(&log.data, 0)?;
+    let token = format!("0x{}", hex::encode(&log.address));
+    Some(ApprovalParams {
+        owner: abi::topic_address(log, 1)?,
+        spender: abi::topic_address(log, 2)?,
+        value: value.to_string(),
+        value_decimal: decimal(annotations, value, &token),
+    })
+}
+
+/// Typed parameters of an ERC-721 `Transfer` or ERC-1155 `TransferSingle` /
+/// `TransferBatch`, one token id and amount per token moved.
+pub fn nft_transfer(log: &eth::Log) -> Option<NftTransferParams> {
+    let items = nft::items(log);
+    let first = items.first()?;
+    Some(NftTransferParams {
+        operator: first.operator.clone().unwrap_or_default(),
+        from: first.from.clone(),
+        to: first.to.clone(),
+        token_ids: items.iter().map(|item| item.id.to_string()).collect(),
+        amounts: items.iter().map(|item| item.amount.to_string()).collect(),
+    })
+}
+
+/// Typed parameters of `ApprovalForAll(owner, operator, approved)`.
+pub fn operator_approval(log: &eth::Log) -> Option<OperatorApprovalParams> {
+    let (owner, operator, approved) = nft::operator_approval(log)?;
+    Some(OperatorApprovalParams {
+        owner,
+        operator,
+        approved,
+    })
+}
+
+/// Typed parameters of a proxy upgrade, admin or ownership handover, or
+/// AccessControl role change classified as `event_type`.
+pub fn governance(event_type: &str, log: &eth::Log) -> Option<GovernanceParams> {
+    match event_type {
+        "ProxyUpgraded" => Some(GovernanceParams {
+            current: abi::topic_address(log, 1)?,
+            ..Default::default()
+        }),
+        "AdminChanged" => Some(GovernanceParams {
+            previous: abi::word_address(&log.data, 0)?,
+            current: abi::word_address(&log.data, 1)?,
+            ..Default::default()
+        }),
+        "OwnershipTransferred" => Some(GovernanceParams {
+            previous: abi::topic_address(log, 1)?,
+            current: abi::topic_address(log, 2)?,
+            ..Default::default()
+        }),
+        "RoleGranted" | "RoleRevoked" if log.topics.len() == 4 => Some(GovernanceParams {
+            role: format!("0x{}", hex::encode(log.topics.get(1)?)),
+            current: abi::topic_address(log, 2)?,
+            sender: abi::topic_address(log, 3)?,
+            ..Default::default()
+        }),
+        _ => None,
+    }
+}
+
+/// Typed parameters of a Uniswap V2 `Swap(sender, amount0In, amount1In,
+/// amount0Out, amount1Out, to)`.
+pub fn swap(log: &eth::Log) -> Option<SwapParams> {
+    if log.topics.len() != 3 {
+        return None;
+    }
+    Some(SwapParams {
+        sender: abi::topic_address(log, 1)?,
+        to: abi::topic_address(log, 2)?,
+        amount0_in: abi::word_u256(&log.data, 0)?.to_string(),
+        amount1_in: abi::word_u256(&log.data, 1)?.to_string(),
+        amount0_out: abi::word_u256(&log.data, 2)?.to_string(),
+        amount1_out: abi::word_u256(&log.data, 3)?.to_string(),
+    })
+}
+
+/// `raw` in whole tokens, or empty when the token's decimals aren't known.
+pub fn decimal(annotations: &Annotations, raw: U256, token: &str) -> String {
+    amount::token_decimals(annotations, token)
+        .map(|decimals| amount::to_decimal(raw, decimals))
+        .unwrap_or_default()
+}
@@ -0,0 +1,40 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy;
+
+/// Emits one `EoaDelegationSet` per applied EIP-7702 authorization in a
+/// type-4 transaction. Authorizations have no log, so `log_index` carries the
+/// authorization's position in the transaction's authorization list.
+pub fn delegation_events(tx: &eth::TransactionTrace, block_number: u64) -> Vec<CandidateEvent> {
+    if tx.r#type != eth::transaction_trace::Type::TrxTypeSetCode as i32 {
+        return Vec::new();
+    }
+
+    tx.set_code_authorizations
+        .iter()
+        .enumerate()
+        .filter(|(_, auth)| !auth.discarded)
+        .filter_map(|(position, auth)| {
+            let authority = auth.authority.as_ref().filter(|a| a.len() == 20)?;
+            // Delegating to the zero address clears an existing designation.
+            let cleared = auth.address.iter().all(|b| *b == 0);
+
+            Some(CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                block_number,
+                log_index: position as u64,
+                contract_address: format!("0x{}", hex::encode(authority)),
+                event_type: "EoaDelegationSet".to_string(),
+                metadata: format!(
+                    "{{\"delegate\":\"0x{}\",\"nonce\":{},\"cleared\":{}}}",
+                    hex::encode(&auth.address),
+                    auth.nonce,
+                    cleared
+                ),
+                standard: taxonomy::EIP7702.to_string(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,67 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::admin::{self, AdminChange};
+use crate::pb::pulseproof::CandidateEvent;
+
+/// Key holding the EOA whose transaction deployed a contract.
+pub fn deployer_key(contract: &str) -> String {
+    format!("deployer:{}", contract)
+}
+
+/// Key holding the latest block an EOA sent a transaction in.
+pub fn activity_key(eoa: &str) -> String {
+    format!("last_tx:{}", eoa)
+}
+
+/// Contracts created by a transaction's successful create calls. Needs call
+/// traces; factory deployments are credited to the transaction's sender.
+pub fn created(tx: &eth::TransactionTrace) -> impl Iterator<Item = (u64, String)> + '_ {
+    tx.calls
+        .iter()
+        .filter(|call| call.call_type == eth::CallType::Create as i32 && !call.state_reverted)
+        .map(|call| {
+            (
+                call.begin_ordinal,
+                format!("0x{}", hex::encode(&call.address)),
+            )
+        })
+}
+
+/// Builds a `DeployerKeyCompromiseCandidate` for admin actions a deployer
+/// took on its own contracts after `dormant_blocks` without a transaction.
+/// Acting on several contracts of its portfolio at once escalates it to
+/// critical.
+pub fn candidate(
+    tx: &eth::TransactionTrace,
+    block_number: u64,
+    deployer: &str,
+    changes: &[AdminChange],
+    dormant_blocks: u64,
+) -> CandidateEvent {
+    let contracts = admin::contracts(changes).len();
+    let severity = if contracts >= admin::MIN_CONTRACTS {
+        "critical"
+    } else {
+        "high"
+    };
+
+    CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+        block_number,
+        log_index: changes
+            .iter()
+            .map(|c| c.log_index)
+            .max()
+            .unwrap_or_default() as u64,
+        event_type: "DeployerKeyCompromiseCandidate".to_string(),
+        from: deployer.to_string(),
+        metadata: format!(
+            "{{\"dormant_blocks\":{},\"contracts\":{},\"changes\":{}}}",
+            dormant_blocks,
+            contracts,
+            admin::changes_json(changes)
+        ),
+        severity: severity.to_string(),
+        ..Default::default()
+    }
+}
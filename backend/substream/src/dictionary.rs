@@ -0,0 +1,344 @@
+use crate::pb::pulseproof::{EnumEntry, EnumSet, RuleDescriptor};
+
+/// `(code, label, description)`. Detections use their event type as rule id,
+/// so the description doubles as the rule's.
+type Entry = (&'static str, &'static str, &'static str);
+
+// Keep in sync with the event types `map_candidate_events` and the detectors
+// emit; a code missing here still reaches sinks, just without a label.
+const EVENT_TYPES: &[Entry] = &[
+    ("Transfer", "Transfer", "ERC-20 token transfer"),
+    ("Approval", "Approval", "ERC-20 allowance set"),
+    ("Swap", "Swap", "DEX pool swap"),
+    (
+        "FlashLoan",
+        "Flash loan",
+        "Uncollateralised loan repaid within the transaction",
+    ),
+    (
+        "ArbLoop",
+        "Arbitrage loop",
+        "Swaps returning to the starting token at a profit",
+    ),
+    (
+        "EoaDelegationSet",
+        "EOA delegation set",
+        "EIP-7702 authorization delegating an account to code",
+    ),
+    (
+        "MassDistribution",
+        "Mass distribution",
+        "One sender paying out to many recipients in a transaction",
+    ),
+    (
+        "DustingCampaign",
+        "Dusting campaign",
+        "Tiny transfers to many addresses",
+    ),
+    (
+        "AddressPoisoningCandidate",
+        "Address poisoning",
+        "Transfer from a look-alike of an address the victim used",
+    ),
+    (
+        "SlippageAbuseCandidate",
+        "Slippage abuse",
+        "Swap executed far below its quoted output",
+    ),
+    (
+        "DonationAttackCandidate",
+        "Donation attack",
+        "Direct donation inflating a vault's share price",
+    ),
+    (
+        "FirstDepositorExploitCandidate",
+        "First depositor exploit",
+        "Early deposit and donation rounding later depositors' shares to zero",
+    ),
+    (
+        "ReadOnlyReentrancyCandidate",
+        "Read-only reentrancy",
+        "View read while the pool it reads was mid-update",
+    ),
+    (
+        "ChainAnomaly",
+        "Chain anomaly",
+        "Block gap, empty block run or watched contract gone silent",
+    ),
+    (
+        "BeaconWithdrawal",
+        "Beacon withdrawal",
+        "Consensus layer withdrawal credited to an address",
+    ),
+    (
+        "MassAdminChange",
+        "Mass admin change",
+        "Admin rights of several watched contracts handed over in one transaction",
+    ),
+    (
+        "DeployerKeyCompromiseCandidate",
+        "Deployer key compromise",
+        "Dormant deployer handing over its contracts' admin rights",
+    ),
+    (
+        "DormantAddressActive",
+        "Dormant address active",
+        "Address moving value out after a long idle period",
+    ),
+    (
+        "VestingUnlock",
+        "Vesting unlock",
+        "Tokens released from a vesting schedule",
+    ),
+    (
+        "LockExpired",
+        "Lock expired",
+        "Time lock on tokens or liquidity ran out",
+    ),
+    (
+        "FlashMint",
+        "Flash mint",
+        "Tokens minted and burned within the transaction",
+    ),
+    (
+        "ExploitRehearsalCandidate",
+        "Exploit rehearsal",
+        "Attack preceded by the sender's low-value probes of the same call",
+    ),
+    (
+        "CustomTopic",
+        "Custom topic",
+        "Log matching a topic the operator asked to watch",
+    ),
+    (
+        "DivergenceDetected",
+        "Divergence detected",
+        "Event root differs from the one a peer operator published",
+    ),
+    (
+        "NovelInteraction",
+        "Novel interaction",
+        "Contract calling another for the first time while moving value",
+    ),
+    (
+        "ApprovalDrainCandidate",
+        "Approval drain",
+        "Allowance spent shortly after it was granted",
+    ),
+    (
+        "ApprovalSpike",
+        "Approval spike",
+        "Approval rate on a spender far above its baseline",
+    ),
+    (
+        "ReentrancySuspect",
+        "Reentrancy",
+        "Contract re-entered before its outer call completed",
+    ),
+    (
+        "LongLivedPermit",
+        "Long-lived permit",
+        "Permit whose deadline is far in the future",
+    ),
+    (
+        "LargeTransfer",
+        "Large transfer",
+        "Transfer above the configured size",
+    ),
+    (
+        "NftDrainCandidate",
+        "NFT drain",
+        "Operator sweeping one owner's NFTs across collections",
+    ),
+    (
+        "NftTransfer",
+        "NFT transfer",
+        "ERC-721 or ERC-1155 token transfer",
+    ),
+    (
+        "ApprovalForAll",
+        "Approval for all",
+        "Operator approved or revoked for a whole collection",
+    ),
+    (
+        "StolenAssetMovement",
+        "Stolen asset movement",
+        "Token reported stolen changed hands",
+    ),
+    (
+        "ProxyUpgraded",
+        "Proxy upgraded",
+        "Proxy now delegates to a new implementation",
+    ),
+    ("AdminChanged", "Admin changed", "Proxy admin replaced"),
+    (
+        "OwnershipTransferred",
+        "Ownership transferred",
+        "Contract owner replaced",
+    ),
+    (
+        "RoleGranted",
+        "Role granted",
+        "Access control role given to an account",
+    ),
+    (
+        "RoleRevoked",
+        "Role revoked",
+        "Access control role taken from an account",
+    ),
+    (
+        "ImplementationSlotChanged",
+        "Implementation slot changed",
+        "Proxy implementation rewritten without an Upgraded log",
+    ),
+    (
+        "TokenBoundAccountCreated",
+        "Token-bound account created",
+        "ERC-6551 account created for an NFT",
+    ),
+    (
+        "RiskyApproval",
+        "Risky approval",
+        "Unlimited or outsized grant, or a permit to an EOA or fresh contract",
+    ),
+    (
+        "SandwichCandidate",
+        "Sandwich",
+        "Swap front-run and back-run by one sender within the block",
+    ),
+    (
+        "CrossChainMessage",
+        "Cross-chain message",
+        "Bridge message sent or delivered, with the key linking both sides",
+    ),
+    (
+        "FailedProbe",
+        "Failed probe",
+        "Reverted transaction touching a monitored contract or topic",
+    ),
+    (
+        "RugPullCandidate",
+        "Rug pull",
+        "Pool deployer moving out liquidity just removed from its pool",
+    ),
+    (
+        "PrecompileAnomaly",
+        "Precompile anomaly",
+        "Transaction calling one precompile, such as ecrecover, thousands of times",
+    ),
+];
+
+const SEVERITIES: &[Entry] = &[
+    ("info", "Info", "Routine activity, kept for context"),
+    (
+        "medium",
+        "Medium",
+        "Worth a look when correlated with other events",
+    ),
+    (
+        "high",
+        "High",
+        "Likely attack or compromise; triage promptly",
+    ),
+    ("critical", "Critical", "Funds at immediate risk"),
+];
+
+const RETENTION_CLASSES: &[Entry] = &[
+    ("hot", "Hot", "Keep queryable for triage"),
+    (
+        "warm",
+        "Warm",
+        "Keep queryable while the grant may still be used",
+    ),
+    ("cold", "Cold", "Can move to cold storage quickly"),
+];
+
+const SOURCES: &[Entry] = &[
+    (
+        "receipt",
+        "Receipt",
+        "Log read from the transaction receipt",
+    ),
+    (
+        "calls",
+        "Call traces",
+        "Log read from the call traces of a block without receipts",
+    ),
+];
+
+const SUSPICION_REASONS: &[Entry] = &[
+    (
+        "flash_swap_approval",
+        "Flash-funded swap and approval",
+        "Flash funding, a swap and an approval in one transaction",
+    ),
+    (
+        "flash_funded",
+        "Flash funded",
+        "Transaction took a flash loan or flash mint",
+    ),
+    (
+        "victim_drain",
+        "Victim drain",
+        "One address sent many transfers in the transaction",
+    ),
+    (
+        "critical_detection",
+        "Critical detection",
+        "A contributing event is critical",
+    ),
+    (
+        "high_detection",
+        "High detection",
+        "A contributing event is high severity",
+    ),
+];
+
+/// Every event type the package emits.
+pub fn event_types() -> impl Iterator<Item = &'static str> {
+    EVENT_TYPES.iter().map(|(code, _, _)| *code)
+}
+
+/// Every rule, with the severity and retention class its events get when the
+/// detector sets none, as `severity` and `retention_class` assign them.
+pub fn rules(
+    severity: fn(&str) -> &'static str,
+    retention_class: fn(&str) -> &'static str,
+) -> Vec<RuleDescriptor> {
+    EVENT_TYPES
+        .iter()
+        .map(|(code, label, description)| RuleDescriptor {
+            rule: code.to_string(),
+            label: label.to_string(),
+            description: description.to_string(),
+            default_severity: severity(code).to_string(),
+            retention_class: retention_class(code).to_string(),
+        })
+        .collect()
+}
+
+fn set(field: &str, entries: &[Entry]) -> EnumSet {
+    EnumSet {
+        field: field.to_string(),
+        entries: entries
+            .iter()
+            .map(|(code, label, description)| EnumEntry {
+                code: code.to_string(),
+                label: label.to_string(),
+                description: description.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Every enum-like string field the package outputs, with the canonical
+/// English label and description of each code.
+pub fn sets() -> Vec<EnumSet> {
+    vec![
+        set("event_type", EVENT_TYPES),
+        set("severity", SEVERITIES),
+        set("retention_class", RETENTION_CLASSES),
+        set("source", SOURCES),
+        set("reasons", SUSPICION_REASONS),
+    ]
+}
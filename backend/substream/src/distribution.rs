@@ -0,0 +1,186 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::{self, Tag};
+use crate::u256::{self, U256};
+use crate::{abi, amount, calls};
+
+// disperseEther(address[],uint256[])
+const DISPERSE_ETHER: [u8; 4] = hex_literal::hex!("e63d38ed");
+// disperseToken(address,address[],uint256[])
+const DISPERSE_TOKEN: [u8; 4] = hex_literal::hex!("c73a2d60");
+// disperseTokenSimple(address,address[],uint256[])
+const DISPERSE_TOKEN_SIMPLE: [u8; 4] = hex_literal::hex!("51ba162c");
+// Gnosis Safe multiSend(bytes)
+const MULTI_SEND: [u8; 4] = hex_literal::hex!("8d80ff0a");
+// ERC20 transfer(address,uint256)
+const ERC20_TRANSFER: [u8; 4] = hex_literal::hex!("a9059cbb");
+
+// Batches smaller than this are ordinary batched payments, not fan-outs.
+const MIN_RECIPIENTS: usize = 10;
+
+/// A batch of payouts of one asset; `token` is `None` for the native asset.
+struct Batch {
+    protocol: Tag,
+    token: Option<String>,
+    transfers: Vec<(String, U256)>,
+}
+
+/// Summarises disperse/multisend fan-outs in a transaction as one
+/// `MassDistribution` event per asset. Uses call frames when the block has
+/// traces and falls back to the top-level calldata otherwise. `log_index`
+/// carries the index of the call that performed the distribution.
+pub fn distribution_events(
+    tx: &eth::TransactionTrace,
+    block_number: u64,
+    detail: bool,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+    for (call_index, caller, contract, input) in calls::frames(tx) {
+        for batch in decode(input) {
+            if batch.transfers.len() < MIN_RECIPIENTS {
+                continue;
+            }
+            let mut event = CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                block_number,
+                log_index: call_index as u64,
+                contract_address: format!("0x{}", hex::encode(contract)),
+                event_type: "MassDistribution".to_string(),
+                from: format!("0x{}", hex::encode(&caller)),
+                metadata: metadata(&batch, detail, annotations),
+                ..Default::default()
+            };
+            batch.protocol.apply(&mut event);
+            if batch.token.is_some() {
+                event.standard = taxonomy::ERC20.to_string();
+            }
+            events.push(event);
+        }
+    }
+    events
+}
+
+fn metadata(batch: &Batch, detail: bool, annotations: &Annotations) -> String {
+    let total = u256::saturating_sum(batch.transfers.iter().map(|(_, amount)| amount));
+    let token = batch.token.as_deref().unwrap_or("native");
+
+    let mut out = format!(
+        "{{\"token\":\"{}\",\"recipients\":{},{}",
+        token,
+        batch.transfers.len(),
+        amount::json_fields(annotations, "total", total, token)
+    );
+    if detail {
+        let transfers: Vec<String> = batch
+            .transfers
+            .iter()
+            .map(|(to, raw)| {
+                format!(
+                    "{{\"to\":\"{}\",{}}}",
+                    to,
+                    amount::json_fields(annotations, "amount", *raw, token)
+                )
+            })
+            .collect();
+        out.push_str(&format!(",\"transfers\":[{}]", transfers.join(",")));
+    }
+    out.push('}');
+    out
+}
+
+fn decode(input: &[u8]) -> Vec<Batch> {
+    let (Some(selector), Some(args)) = (input.get(..4), input.get(4..)) else {
+        return Vec::new();
+    };
+
+    let batch = if selector == DISPERSE_ETHER {
+        disperse(None, args, 0)
+    } else if selector == DISPERSE_TOKEN || selector == DISPERSE_TOKEN_SIMPLE {
+        abi::word_address(args, 0).and_then(|token| disperse(Some(token), args, 1))
+    } else if selector == MULTI_SEND {
+        return multi_send(args);
+    } else {
+        None
+    };
+    batch.into_iter().collect()
+}
+
+fn disperse(token: Option<String>, args: &[u8], recipients_at: usize) -> Option<Batch> {
+    let recipients = abi::word_array(args, recipients_at)?;
+    let values = abi::word_array(args, recipients_at + 1)?;
+    if recipients.len() != values.len() {
+        return None;
+    }
+
+    Some(Batch {
+        protocol: Tag::protocol("disperse", ""),
+        token,
+        transfers: recipients
+            .iter()
+            .zip(values.iter())
+            .map(|(to, value)| {
+                Some((
+                    format!("0x{}", hex::encode(abi::address_bytes(to)?)),
+                    U256::from_big_endian(value),
+                ))
+            })
+            .collect::<Option<_>>()?,
+    })
+}
+
+/// Walks the packed `operation(1) to(20) value(32) length(32) data` entries of
+/// a multiSend payload, grouping plain value sends and ERC20 `transfer` calls
+/// by asset.
+fn multi_send(args: &[u8]) -> Vec<Batch> {
+    let Some(payload) = abi::word_bytes(args, 0) else {
+        return Vec::new();
+    };
+
+    let mut batches: Vec<Batch> = Vec::new();
+    let mut cursor = 0usize;
+    while let Some(header) = payload
+        .get(cursor..)
+        .and_then(|entries| entries.first_chunk::<85>())
+    {
+        let to = &header[1..21];
+        let value = U256::from_big_endian(&header[21..53]);
+        let Some(data_len) = abi::word_usize(&header[53..85], 0) else {
+            break;
+        };
+        let Some(end) = (cursor + 85).checked_add(data_len) else {
+            break;
+        };
+        let Some(data) = payload.get(cursor + 85..end) else {
+            break;
+        };
+        cursor = end;
+
+        let (token, recipient, amount) = if data.is_empty() && !value.is_zero() {
+            (None, format!("0x{}", hex::encode(to)), value)
+        } else if let Some(call) = data
+            .first_chunk::<68>()
+            .filter(|call| call.starts_with(&ERC20_TRANSFER))
+        {
+            let Some(recipient) = abi::word_address(&call[4..], 0) else {
+                continue;
+            };
+            let amount = U256::from_big_endian(&call[36..68]);
+            (Some(format!("0x{}", hex::encode(to))), recipient, amount)
+        } else {
+            continue;
+        };
+
+        match batches.iter_mut().find(|b| b.token == token) {
+            Some(batch) => batch.transfers.push((recipient, amount)),
+            None => batches.push(Batch {
+                protocol: Tag::protocol("safe", ""),
+                token,
+                transfers: vec![(recipient, amount)],
+            }),
+        }
+    }
+    batches
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::{self, Tag};
+use crate::u256::U256;
+use crate::{abi, amount};
+
+// Uniswap V2 pair events
+// Sync(uint112,uint112)
+const SYNC_TOPIC: [u8; 32] =
+    hex_literal::hex!("1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1");
+// Swap(address,uint256,uint256,uint256,uint256,address)
+const SWAP_TOPIC: [u8; 32] =
+    hex_literal::hex!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+// Mint(address,uint256,uint256)
+const MINT_TOPIC: [u8; 32] =
+    hex_literal::hex!("4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f");
+// Burn(address,uint256,uint256,address)
+const BURN_TOPIC: [u8; 32] =
+    hex_literal::hex!("dccd412f0b1252819cb1fd330b93224ca42612892bb3f4f789976e6d81936496");
+// ERC-4626 vault events
+// Deposit(address,address,uint256,uint256)
+const DEPOSIT_TOPIC: [u8; 32] =
+    hex_literal::hex!("dcbc1c05240f31ff3ad067ef1ee35ce4997762752e3a095284754544f4c709d7");
+// Withdraw(address,address,address,uint256,uint256)
+const WITHDRAW_TOPIC: [u8; 32] =
+    hex_literal::hex!("fbde797d201c681b91056529119e0b02407c7bb96a4a2c75c01fc9667232c8db");
+
+struct Donation<'a> {
+    log_index: u32,
+    token: &'a [u8],
+    from: &'a [u8],
+    amount: U256,
+}
+
+#[derive(Default)]
+struct Pool<'a> {
+    /// Inbound transfers not yet accounted for by a mint, deposit or swap.
+    open: Vec<Donation<'a>>,
+    /// Inbound transfers a standalone `sync()` folded into the reserves.
+    donated: Vec<Donation<'a>>,
+}
+
+/// Flags token transfers into a pool or vault that bypass its mint/deposit
+/// path and precede a swap or redemption from it in the same transaction —
+/// the share-price inflation step of first-depositor and rounding exploits.
+///
+/// For V2 pairs a donation is an inbound transfer absorbed by a `Sync` that
+/// no Mint, Burn or Swap follows (a bare `sync()`), and it is flagged at the
+/// pair's next Swap or Burn. For ERC-4626 vaults it is an inbound transfer
+/// from a redeeming party that no `Deposit` by that party accounted for when
+/// the vault emits `Withdraw`.
+pub fn donation_events(
+    tx_hash: &[u8],
+    logs: &[eth::Log],
+    block_number: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let mut pools: BTreeMap<&[u8], Pool> = BTreeMap::new();
+    let mut events = Vec::new();
+
+    for (position, log) in logs.iter().enumerate() {
+        if let Some(transfer) = abi::transfer(log) {
+            if transfer.to != log.address.as_slice() {
+                pools.entry(transfer.to).or_default().open.push(Donation {
+                    log_index: log.index,
+                    token: &log.address,
+                    from: transfer.from,
+                    amount: transfer.amount,
+                });
+            }
+            continue;
+        }
+
+        let Some(topic0) = log.topics.first().map(Vec::as_slice) else {
+            continue;
+        };
+        let Some(pool) = pools.get_mut(log.address.as_slice()) else {
+            continue;
+        };
+
+        let flagged = if topic0 == SYNC_TOPIC {
+            if is_standalone_sync(logs, position) {
+                let open = std::mem::take(&mut pool.open);
+                pool.donated.extend(open);
+            }
+            None
+        } else if topic0 == MINT_TOPIC {
+            pool.open.clear();
+            None
+        } else if topic0 == DEPOSIT_TOPIC {
+            // A deposit only accounts for what its caller paid in.
+            let caller = topic_tail(log, 1);
+            pool.open.retain(|d| Some(d.from) != caller);
+            None
+        } else if topic0 == SWAP_TOPIC || topic0 == BURN_TOPIC {
+            pool.open.clear();
+            let trigger = if topic0 == SWAP_TOPIC { "swap" } else { "burn" };
+            Some((trigger, std::mem::take(&mut pool.donated)))
+        } else if topic0 == WITHDRAW_TOPIC {
+            // Only the redeeming parties' own transfers count; vaults refilled
+            // from their strategies mid-withdrawal receive inbound transfers
+            // too.
+            let parties = [topic_tail(log, 1), topic_tail(log, 2), topic_tail(log, 3)];
+            let (donations, rest) = std::mem::take(&mut pool.open)
+                .into_iter()
+                .partition(|d| parties.contains(&Some(d.from)));
+            pool.open = rest;
+            Some(("withdraw", donations))
+        } else {
+            None
+        };
+
+        if let Some((trigger, donations)) = flagged.filter(|(_, d)| !d.is_empty()) {
+            events.push(candidate(
+                tx_hash,
+                log,
+                block_number,
+                trigger,
+                &donations,
+                annotations,
+            ));
+        }
+    }
+
+    events
+}
+
+fn topic_tail(log: &eth::Log, index: usize) -> Option<&[u8]> {
+    abi::address_bytes(log.topics.get(index)?)
+}
+
+/// V2 pairs emit Sync right before Mint, Burn and Swap; a Sync the pair's
+/// next log doesn't follow up on came from calling `sync()` directly.
+fn is_standalone_sync(logs: &[eth::Log], position: usize) -> bool {
+    let Some((sync, later)) = logs.get(position..).and_then(<[_]>::split_first) else {
+        return false;
+    };
+    let next = later.iter().find(|l| l.address == sync.address);
+    !next
+        .and_then(|l| l.topics.first())
+        .is_some_and(|t| *t == MINT_TOPIC || *t == BURN_TOPIC || *t == SWAP_TOPIC)
+}
+
+fn candidate(
+    tx_hash: &[u8],
+    log: &eth::Log,
+    block_number: u64,
+    trigger: &str,
+    donations: &[Donation],
+    annotations: &Annotations,
+) -> CandidateEvent {
+    let listed: Vec<String> = donations
+        .iter()
+        .map(|d| {
+            let token = format!("0x{}", hex::encode(d.token));
+            format!(
+                "{{\"token\":\"{}\",\"from\":\"0x{}\",{},\"log_index\":{}}}",
+                token,
+                hex::encode(d.from),
+                amount::json_fields(annotations, "amount", d.amount, &token),
+                d.log_index
+            )
+        })
+        .collect();
+
+    let mut event = CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(tx_hash)),
+        block_number,
+        log_index: log.index as u64,
+        contract_address: format!("0x{}", hex::encode(&log.address)),
+        event_type: "DonationAttackCandidate".to_string(),
+        from: donations
+            .first()
+            .map(|d| format!("0x{}", hex::encode(d.from)))
+            .unwrap_or_default(),
+        metadata: format!(
+            "{{\"trigger\":\"{}\",\"donations\":[{}]}}",
+            trigger,
+            listed.join(",")
+        ),
+        ..Default::default()
+    };
+    if trigger == "withdraw" {
+        Tag::standard(taxonomy::ERC4626).apply(&mut event);
+    } else {
+        Tag::protocol("uniswap", "v2").apply(&mut event);
+    }
+    event
+}
@@ -0,0 +1,90 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::u256::{self, U256};
+use crate::{amount, poisoning, sender};
+
+/// One movement of value out of an address: an ERC20 transfer, or a
+/// transaction's top-level native value (`token` is `"native"`).
+pub struct Move<'a> {
+    pub tx_hash: &'a [u8],
+    pub log_index: u32,
+    pub ordinal: u64,
+    pub token: String,
+    pub from: String,
+    pub to: String,
+    pub amount: U256,
+}
+
+/// Key holding the latest block an address moved value out in.
+pub fn activity_key(address: &str) -> String {
+    format!("last_move:{}", address)
+}
+
+pub fn moves(blk: &eth::Block) -> Vec<Move<'_>> {
+    let mut out: Vec<Move> = poisoning::transfers(blk)
+        .into_iter()
+        .map(|t| Move {
+            tx_hash: t.tx_hash,
+            log_index: t.log_index,
+            ordinal: t.ordinal,
+            token: format!("0x{}", hex::encode(&t.token)),
+            from: t.from,
+            to: t.to,
+            amount: t.amount,
+        })
+        .collect();
+
+    for tx in blk.transaction_traces.iter() {
+        let value = u256::from_pb(tx.value.as_ref());
+        if value.is_zero() || tx.status != eth::TransactionTraceStatus::Succeeded as i32 {
+            continue;
+        }
+        let Some(sender) = sender::sender(tx) else {
+            continue;
+        };
+        out.push(Move {
+            tx_hash: &tx.hash,
+            log_index: 0,
+            ordinal: tx.begin_ordinal,
+            token: "native".to_string(),
+            from: format!("0x{}", hex::encode(sender)),
+            to: format!("0x{}", hex::encode(&tx.to)),
+            amount: value,
+        });
+    }
+    out
+}
+
+/// Whether a move is at least `min_units` whole tokens.
+pub fn is_large(m: &Move, min_units: u64, annotations: &Annotations) -> bool {
+    amount::at_least_units(annotations, m.amount, &m.token, min_units)
+}
+
+pub fn candidate(
+    m: &Move,
+    block_number: u64,
+    dormant_blocks: u64,
+    annotations: &Annotations,
+) -> CandidateEvent {
+    CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(m.tx_hash)),
+        block_number,
+        log_index: m.log_index as u64,
+        contract_address: if m.token == "native" {
+            String::new()
+        } else {
+            m.token.clone()
+        },
+        event_type: "DormantAddressActive".to_string(),
+        from: m.from.clone(),
+        to: m.to.clone(),
+        metadata: format!(
+            "{{\"dormant_blocks\":{},{}}}",
+            dormant_blocks,
+            amount::json_fields(annotations, "amount", m.amount, &m.token)
+        ),
+        ..Default::default()
+    }
+}
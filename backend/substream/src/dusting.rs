@@ -0,0 +1,79 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy;
+use crate::u256::U256;
+use crate::{abi, amount, calls};
+
+#[derive(Default)]
+struct Campaign<'a> {
+    recipients: BTreeSet<&'a [u8]>,
+    transfers: u64,
+    total: U256,
+    last_tx: &'a [u8],
+    last_log_index: u32,
+}
+
+/// Flags a sender pushing dust amounts of one token to at least
+/// `min_recipients` distinct addresses within the block, the shape of
+/// address-poisoning and dusting campaigns. One `DustingCampaign` event is
+/// emitted per (token, sender), anchored at the campaign's last transfer.
+pub fn dusting_events(
+    blk: &eth::Block,
+    max_amount: u64,
+    min_recipients: usize,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let max_amount = U256::from(max_amount);
+    let logs: Vec<_> = blk
+        .transaction_traces
+        .iter()
+        .map(|tx| (tx, calls::tx_logs(tx).0))
+        .collect();
+    let mut campaigns: BTreeMap<(&[u8], &[u8]), Campaign> = BTreeMap::new();
+
+    for (tx, logs) in logs.iter() {
+        for log in logs.iter() {
+            let Some(transfer) = abi::transfer(log) else {
+                continue;
+            };
+            if transfer.amount > max_amount {
+                continue;
+            }
+
+            let campaign = campaigns.entry((&log.address, transfer.from)).or_default();
+            campaign.recipients.insert(transfer.to);
+            campaign.transfers += 1;
+            campaign.total = campaign.total.saturating_add(transfer.amount);
+            campaign.last_tx = &tx.hash;
+            campaign.last_log_index = log.index;
+        }
+    }
+
+    campaigns
+        .into_iter()
+        .filter(|(_, c)| c.recipients.len() >= min_recipients)
+        .map(|((token, from), c)| {
+            let token = format!("0x{}", hex::encode(token));
+            CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(c.last_tx)),
+                block_number: blk.number,
+                log_index: c.last_log_index as u64,
+                event_type: "DustingCampaign".to_string(),
+                from: format!("0x{}", hex::encode(from)),
+                metadata: format!(
+                    "{{\"recipients\":{},\"transfers\":{},{}}}",
+                    c.recipients.len(),
+                    c.transfers,
+                    amount::json_fields(annotations, "total", c.total, &token)
+                ),
+                contract_address: token,
+                standard: taxonomy::ERC20.to_string(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,70 @@
+//! Entry points behind the stable decoding API in `core/`. They run the code
+//! the modules run, so a service embedding them decodes and scores exactly
+//! what the stream emits.
+
+use anyhow::Error;
+use substreams_ethereum::pb::eth::v2 as eth;
+
+pub use crate::pb::pulseproof::{CandidateEvent, SuspiciousTransaction};
+pub use substreams_ethereum::pb::eth::v2::{Log, TransactionTrace};
+
+use crate::params::Params;
+
+/// Params in the `key=value&...` form the modules take.
+#[derive(Default)]
+pub struct Config(Params);
+
+impl Config {
+    /// Fails on unknown params and values a module would reject.
+    pub fn parse(params: &str) -> Result<Self, Error> {
+        Params::parse(params).map(Config)
+    }
+}
+
+/// The event `map_candidate_events` emits for `log` of `tx`, finished and
+/// with shadow-mode rules applied. Swap legs and the annotations that need
+/// the rest of the block (transaction context, ordering, traces) are left
+/// out. Logs of a transaction without a receipt are taken to come from its
+/// call traces.
+pub fn decode_log(
+    config: &Config,
+    tx: &eth::TransactionTrace,
+    log: &eth::Log,
+    block_number: u64,
+) -> Option<CandidateEvent> {
+    let source = if tx.receipt.is_some() {
+        "receipt"
+    } else {
+        "calls"
+    };
+    let mut event = crate::log_event(&config.0, block_number, tx, log, source)?;
+    crate::finish(&mut event, &config.0.annotations());
+    crate::shadow(std::slice::from_mut(&mut event), &config.0);
+    Some(event)
+}
+
+/// Scores the events of the transaction of the first event with one the way
+/// `map_suspicious_transactions` does, or `None` when it stays below
+/// `suspicion_min_score`. Events of other transactions are ignored.
+pub fn score_transaction(
+    config: &Config,
+    events: &[CandidateEvent],
+) -> Option<SuspiciousTransaction> {
+    let hash = events
+        .iter()
+        .map(|e| &e.transaction_hash)
+        .find(|hash| !hash.is_empty())?;
+    let group: Vec<CandidateEvent> = events
+        .iter()
+        .filter(|e| e.transaction_hash == *hash)
+        .cloned()
+        .collect();
+    crate::suspicion::suspicious_transactions(&group, &config.0)
+        .into_iter()
+        .next()
+}
+
+/// The bytes `map_event_commitment` hashes into an event's Merkle leaf.
+pub fn canonical_encoding(event: &CandidateEvent) -> Vec<u8> {
+    crate::commitment::canonical_encoding(event)
+}
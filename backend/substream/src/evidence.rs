@@ -0,0 +1,101 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::{CandidateEvent, EvidenceBundle, RawLog, RuleProvenance, TxMetadata};
+use crate::{calls, fingerprint, sender, u256};
+
+/// Builds a self-contained bundle for `incident`: every event of its
+/// transaction, the transaction's raw logs and metadata, and where the
+/// detection came from.
+pub fn bundle(
+    incident: &CandidateEvent,
+    tx: &eth::TransactionTrace,
+    events: &[CandidateEvent],
+) -> EvidenceBundle {
+    let logs = calls::tx_logs(tx).0.into_owned();
+
+    let contributing: Vec<CandidateEvent> = events
+        .iter()
+        .filter(|e| e.transaction_hash == incident.transaction_hash)
+        .cloned()
+        .collect();
+
+    EvidenceBundle {
+        incident_id: fingerprint::incident_id(
+            contributing.iter().map(|e| e.fingerprint.as_str()),
+            contributing.iter().map(|e| e.event_type.as_str()),
+        ),
+        incident_fingerprint: incident.fingerprint.clone(),
+        incident: Some(incident.clone()),
+        contributing,
+        raw_logs: logs.iter().map(raw_log).collect(),
+        tx: Some(TxMetadata {
+            hash: format!("0x{}", hex::encode(&tx.hash)),
+            from: sender::sender(tx)
+                .map(|address| format!("0x{}", hex::encode(address)))
+                .unwrap_or_default(),
+            to: format!("0x{}", hex::encode(&tx.to)),
+            nonce: tx.nonce,
+            value: u256::from_pb(tx.value.as_ref()).to_string(),
+            input: format!("0x{}", hex::encode(&tx.input)),
+            gas_used: tx.gas_used,
+            status: eth::TransactionTraceStatus::try_from(tx.status)
+                .map(|status| status.as_str_name().to_string())
+                .unwrap_or_default(),
+        }),
+        rule: Some(RuleProvenance {
+            rule: incident.event_type.clone(),
+            module: "map_candidate_events".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }),
+    }
+}
+
+pub fn raw_log(log: &eth::Log) -> RawLog {
+    RawLog {
+        log_index: log.index,
+        address: format!("0x{}", hex::encode(&log.address)),
+        topics: log
+            .topics
+            .iter()
+            .map(|t| format!("0x{}", hex::encode(t)))
+            .collect(),
+        data: format!("0x{}", hex::encode(&log.data)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(tx: &str, event_type: &str, fingerprint: &str) -> CandidateEvent {
+        CandidateEvent {
+            transaction_hash: tx.to_string(),
+            event_type: event_type.to_string(),
+            fingerprint: fingerprint.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn incidents_of_one_transaction_share_an_id() {
+        let tx = eth::TransactionTrace {
+            hash: vec![0x11; 32],
+            ..Default::default()
+        };
+        let hash = format!("0x{}", "11".repeat(32));
+        let events = [
+            event(&hash, "FlashLoan", "0xaa"),
+            event(&hash, "PriceManipulation", "0xbb"),
+            event("0x22", "FlashLoan", "0xcc"),
+        ];
+        let [first, second, other] = &events;
+
+        let id = bundle(first, &tx, &events).incident_id;
+        assert_eq!(bundle(second, &tx, &events).incident_id, id);
+        assert_eq!(
+            id,
+            fingerprint::incident_id(["0xbb", "0xaa"], ["PriceManipulation", "FlashLoan"])
+        );
+        assert_ne!(bundle(other, &tx, &events).incident_id, id);
+    }
+}
@@ -0,0 +1,145 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CandidateEvent;
+use crate::{abi, sender};
+
+// Error(string) and Panic(uint256) revert selectors.
+const ERROR_SELECTOR: [u8; 4] = hex_literal::hex!("08c379a0");
+const PANIC_SELECTOR: [u8; 4] = hex_literal::hex!("4e487b71");
+
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Decodes revert data: the message of `Error(string)`, the code of
+/// `Panic(uint256)`, or the selector of a custom error.
+fn revert_reason(output: &[u8]) -> Option<String> {
+    let (selector, args) = output.split_first_chunk::<4>()?;
+    if *selector == ERROR_SELECTOR {
+        let message = abi::word_bytes(args, 0)?;
+        return Some(String::from_utf8_lossy(message).into_owned());
+    }
+    if *selector == PANIC_SELECTOR {
+        return Some(format!("Panic(0x{:x})", abi::word_u256(args, 0)?));
+    }
+    Some(format!("0x{}", hex::encode(selector)))
+}
+
+/// The reason the transaction failed: its revert data when it has any, or
+/// the failure the node reported on the first failed call (out of gas, bad
+/// instruction).
+fn reason(tx: &eth::TransactionTrace) -> Option<String> {
+    let root = tx.calls.first();
+    let output = if tx.return_data.is_empty() {
+        root.map_or(&[][..], |call| &call.return_data[..])
+    } else {
+        &tx.return_data[..]
+    };
+    revert_reason(output).or_else(|| {
+        tx.calls
+            .iter()
+            .find(|call| call.status_failed && !call.failure_reason.is_empty())
+            .map(|call| call.failure_reason.clone())
+    })
+}
+
+/// Emits `FailedProbe` for reverted or failed transactions that called a
+/// `watch`ed contract, or whose reverted calls logged one of `topics` or
+/// logged from a watched contract: the probing an attacker does before the
+/// exploit that works. `contract_address` is the first watched contract
+/// touched (or the first monitored log's emitter), `log_index` the call
+/// index of that touch. `metadata` has the `status`, the decoded `reason`
+/// (`null` when there is none), and the `touched` contracts and `topics`
+/// the transaction would have emitted. Without call traces only the
+/// transaction's `to` is checked.
+pub fn failed_probes(blk: &eth::Block, watch: &[String], topics: &[String]) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+    for tx in blk.transaction_traces.iter() {
+        let status = if tx.status == eth::TransactionTraceStatus::Reverted as i32 {
+            "reverted"
+        } else if tx.status == eth::TransactionTraceStatus::Failed as i32 {
+            "failed"
+        } else {
+            continue;
+        };
+
+        let mut touched: Vec<(String, u32)> = Vec::new();
+        let mut emitted: Vec<String> = Vec::new();
+        let mut touch = |address: String, index: u32| {
+            if !touched.iter().any(|(a, _)| *a == address) {
+                touched.push((address, index));
+            }
+        };
+        if tx.calls.is_empty() {
+            let to = format!("0x{}", hex::encode(&tx.to));
+            if watch.contains(&to) {
+                touch(to, 0);
+            }
+        }
+        for call in tx.calls.iter() {
+            let address = format!("0x{}", hex::encode(&call.address));
+            if watch.contains(&address) {
+                touch(address, call.index);
+            }
+            for log in call.logs.iter() {
+                let emitter = format!("0x{}", hex::encode(&log.address));
+                let topic0 = log
+                    .topics
+                    .first()
+                    .map(|t| format!("0x{}", hex::encode(t)))
+                    .unwrap_or_default();
+                if topics.contains(&topic0) && !emitted.contains(&topic0) {
+                    emitted.push(topic0.clone());
+                }
+                if watch.contains(&emitter) || topics.contains(&topic0) {
+                    touch(emitter, call.index);
+                }
+            }
+        }
+        let Some((contract, call_index)) = touched.first().cloned() else {
+            continue;
+        };
+
+        let list = |items: Vec<&String>| {
+            items
+                .iter()
+                .map(|item| format!("\"{}\"", item))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let reason = reason(tx)
+            .map(|reason| json_string(&reason))
+            .unwrap_or_else(|| "null".to_string());
+        events.push(CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+            block_number: blk.number,
+            log_index: call_index as u64,
+            contract_address: contract,
+            event_type: "FailedProbe".to_string(),
+            from: sender::sender(tx)
+                .map(|sender| format!("0x{}", hex::encode(sender)))
+                .unwrap_or_default(),
+            to: format!("0x{}", hex::encode(&tx.to)),
+            metadata: format!(
+                "{{\"status\":\"{}\",\"reason\":{},\"touched\":[{}],\"topics\":[{}]}}",
+                status,
+                reason,
+                list(touched.iter().map(|(address, _)| address).collect()),
+                list(emitted.iter().collect()),
+            ),
+            ..Default::default()
+        });
+    }
+    events
+}
@@ -0,0 +1,128 @@
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::pb::pulseproof::CandidateEvent;
+
+/// Stable identifier for a candidate event: keccak256 over the transaction
+/// hash, the big-endian log index and the event type. The event type is part
+/// of the preimage because heuristic events reuse the log index of the log
+/// that completed them. Block-wide events have no transaction and take the
+/// big-endian block number in its place, so they don't collide with the same
+/// position of every other block.
+pub fn fingerprint(event: &CandidateEvent) -> String {
+    if event.transaction_hash.is_empty() {
+        return of(
+            &event.block_number.to_be_bytes(),
+            event.log_index,
+            &event.event_type,
+        );
+    }
+    let tx_hash = hex::decode(event.transaction_hash.trim_start_matches("0x")).unwrap_or_default();
+    of(&tx_hash, event.log_index, &event.event_type)
+}
+
+/// Fingerprint of the event `event_type` at `log_index` of the transaction
+/// `tx_hash`, for referencing events that are built elsewhere.
+pub fn of(tx_hash: &[u8], log_index: u64, event_type: &str) -> String {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(tx_hash);
+    keccak.update(&log_index.to_be_bytes());
+    keccak.update(event_type.as_bytes());
+    keccak.finalize(&mut hash);
+
+    format!("0x{}", hex::encode(hash))
+}
+
+/// Identifier of an incident: keccak256 over its contributing events'
+/// fingerprints and the ids of the rules that fired, each sorted and
+/// deduplicated so the id depends only on the sets, never on emission order.
+/// Every entry is length-prefixed, keeping distinct sets from colliding by
+/// concatenation. Operators running the same package derive the same id.
+pub fn incident_id<'a>(
+    fingerprints: impl IntoIterator<Item = &'a str>,
+    rules: impl IntoIterator<Item = &'a str>,
+) -> String {
+    let mut fingerprints: Vec<&str> = fingerprints.into_iter().collect();
+    fingerprints.sort_unstable();
+    fingerprints.dedup();
+    let mut rules: Vec<&str> = rules.into_iter().collect();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    for part in [fingerprints, rules] {
+        keccak.update(&(part.len() as u64).to_be_bytes());
+        for entry in part {
+            keccak.update(&(entry.len() as u64).to_be_bytes());
+            keccak.update(entry.as_bytes());
+        }
+    }
+    keccak.finalize(&mut hash);
+
+    format!("0x{}", hex::encode(hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = "0xaa";
+    const B: &str = "0xbb";
+
+    #[test]
+    fn incident_id_ignores_order() {
+        assert_eq!(
+            incident_id([A, B], ["flash_loan", "large_transfer"]),
+            incident_id([B, A], ["large_transfer", "flash_loan"]),
+        );
+    }
+
+    #[test]
+    fn incident_id_collapses_duplicates() {
+        assert_eq!(
+            incident_id([A, B, A], ["flash_loan", "flash_loan"]),
+            incident_id([A, B], ["flash_loan"]),
+        );
+    }
+
+    #[test]
+    fn incident_id_keeps_fingerprints_and_rules_apart() {
+        assert_ne!(incident_id([A], []), incident_id([], [A]));
+        assert_ne!(incident_id([A, B], []), incident_id([A], [B]));
+        // Length prefixes keep concatenations from colliding.
+        assert_ne!(incident_id(["0xaa0xbb"], []), incident_id([A, B], []));
+    }
+
+    #[test]
+    fn incident_id_matches_its_preimage() {
+        let mut preimage = Vec::new();
+        preimage.extend(2u64.to_be_bytes());
+        for entry in [A, B] {
+            preimage.extend(4u64.to_be_bytes());
+            preimage.extend(entry.as_bytes());
+        }
+        preimage.extend(1u64.to_be_bytes());
+        preimage.extend(10u64.to_be_bytes());
+        preimage.extend(b"flash_loan");
+        let mut hash = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(&preimage);
+        keccak.finalize(&mut hash);
+
+        assert_eq!(
+            incident_id([B, A], ["flash_loan"]),
+            format!("0x{}", hex::encode(hash))
+        );
+    }
+
+    /// Pinned so a change to the preimage, which would give every operator
+    /// new ids for the same incidents, fails here first.
+    #[test]
+    fn incident_id_is_stable() {
+        assert_eq!(
+            incident_id([B, A], ["flash_loan"]),
+            "0x1ecfc3aaed107a281a702ecd6d40908dd6ef6a2e0253e0bbd17eebaebbfb6bdf"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::Tag;
+use crate::u256::U256;
+use crate::{abi, amount};
+
+// Aave V2 FlashLoan(address,address,address,uint256,uint256,uint16)
+const AAVE_V2_TOPIC: [u8; 32] =
+    hex_literal::hex!("631042c832b07452973831137f2d73e395028b44b250dedc5abb0ee766e168ac");
+// Aave V3 FlashLoan(address,address,address,uint256,uint8,uint256,uint16)
+const AAVE_V3_TOPIC: [u8; 32] =
+    hex_literal::hex!("efefaba5e921573100900a3ad9cf29f222d995fb3b6045797eaea7521bd8d6f0");
+// Balancer FlashLoan(address,address,uint256,uint256). Maker's DssFlash shares
+// the signature but indexes only the receiver.
+const BALANCER_TOPIC: [u8; 32] =
+    hex_literal::hex!("0d7d75e01ab95780d3cd1c8ec0dd6c2ce19e3a20427eec8bf53283b6fb8e95f0");
+// Uniswap V3 Flash(address,address,uint256,uint256,uint256,uint256)
+const UNISWAP_V3_TOPIC: [u8; 32] =
+    hex_literal::hex!("bdbdb71d7860376ba52b25a5028beea23581364a40522f6bcfb86bb1f2dca633");
+
+/// One borrowed asset. `token` is `None` until it is known: Uniswap V3 logs
+/// leave the pool's tokens out, so they are read off the outgoing transfers.
+struct Leg {
+    token: Option<Vec<u8>>,
+    amount: U256,
+    premium: U256,
+}
+
+struct Loan {
+    lender: Vec<u8>,
+    /// Contract the lender calls back.
+    callee: Vec<u8>,
+    /// Address the borrowed funds are sent to.
+    recipient: Vec<u8>,
+    legs: Vec<Leg>,
+    /// An Aave V3 loan not repaid in the same transaction but opened as debt.
+    as_debt: bool,
+    protocol: Tag,
+}
+
+/// Emits `FlashLoan` for Aave V2/V3, Balancer and Uniswap V3 flash loans.
+/// The loan is `cycled` when the lender called back into the borrower (seen
+/// in call traces, so never on blocks without them) and every borrowed
+/// amount, which left some address for the recipient before the loan's log,
+/// went back to that same address afterwards.
+pub fn flash_loan_events(
+    tx: &eth::TransactionTrace,
+    logs: &[eth::Log],
+    block_number: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+    for (pos, log) in logs.iter().enumerate() {
+        let Some(mut loan) = decode(log) else {
+            continue;
+        };
+        let before = || logs.iter().take(pos);
+
+        let mut repaid = !loan.as_debt;
+        for leg in loan.legs.iter_mut() {
+            // Borrowed funds leave the lender (or, on Aave, the asset's
+            // aToken) for the recipient before the loan is logged.
+            let lent = before().enumerate().find_map(|(at, t)| {
+                let transfer = abi::transfer(t)?;
+                (transfer.to == loan.recipient
+                    && transfer.amount == leg.amount
+                    && leg.token.as_ref().is_none_or(|token| *token == t.address)
+                    && (leg.token.is_some() || transfer.from == loan.lender))
+                    .then(|| (at, t.address.clone(), transfer.from.to_vec()))
+            });
+            let Some((at, token, source)) = lent else {
+                repaid = false;
+                continue;
+            };
+            leg.token = Some(token.clone());
+            repaid &= before().skip(at + 1).any(|t| {
+                abi::transfer(t).is_some_and(|back| {
+                    t.address == token && back.to == source && back.amount >= leg.amount
+                })
+            });
+        }
+        let callback = tx.calls.iter().any(|call| {
+            !call.state_reverted && call.caller == loan.lender && call.address == loan.callee
+        });
+
+        let legs: Vec<String> = loan
+            .legs
+            .iter()
+            .map(|leg| {
+                let token = leg
+                    .token
+                    .as_ref()
+                    .map(|t| format!("0x{}", hex::encode(t)))
+                    .unwrap_or_default();
+                format!(
+                    "{{\"token\":\"{}\",{},{}}}",
+                    token,
+                    amount::json_fields(annotations, "amount", leg.amount, &token),
+                    amount::json_fields(annotations, "premium", leg.premium, &token)
+                )
+            })
+            .collect();
+        let mut event = CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+            block_number,
+            log_index: log.index as u64,
+            contract_address: format!("0x{}", hex::encode(&loan.lender)),
+            event_signature: abi::signature(log),
+            event_type: "FlashLoan".to_string(),
+            from: format!("0x{}", hex::encode(&loan.callee)),
+            to: format!("0x{}", hex::encode(&loan.recipient)),
+            metadata: format!(
+                "{{\"legs\":[{}],\"callback\":{},\"cycled\":{}}}",
+                legs.join(","),
+                callback,
+                callback && repaid
+            ),
+            ..Default::default()
+        };
+        loan.protocol.apply(&mut event);
+        events.push(event);
+    }
+    events
+}
+
+fn decode(log: &eth::Log) -> Option<Loan> {
+    let topic0 = log.topics.first()?.as_slice();
+    let topic = |index: usize| -> Option<Vec<u8>> {
+        abi::address_bytes(log.topics.get(index)?).map(<[u8]>::to_vec)
+    };
+
+    if topic0 == AAVE_V2_TOPIC && log.topics.len() == 4 {
+        let target = topic(1)?;
+        return Some(Loan {
+            lender: log.address.clone(),
+            callee: target.clone(),
+            recipient: target,
+            legs: vec![Leg {
+                token: Some(topic(3)?),
+                amount: abi::word_u256(&log.data, 0)?,
+                premium: abi::word_u256(&log.data, 1)?,
+            }],
+            as_debt: false,
+            protocol: Tag::protocol("aave", "v2"),
+        });
+    }
+    if topic0 == AAVE_V3_TOPIC && log.topics.len() == 4 {
+        let target = topic(1)?;
+        return Some(Loan {
+            lender: log.address.clone(),
+            callee: target.clone(),
+            recipient: target,
+            legs: vec![Leg {
+                token: Some(topic(2)?),
+                amount: abi::word_u256(&log.data, 1)?,
+                premium: abi::word_u256(&log.data, 3)?,
+            }],
+            as_debt: !abi::word_u256(&log.data, 2)?.is_zero(),
+            protocol: Tag::protocol("aave", "v3"),
+        });
+    }
+    if topic0 == BALANCER_TOPIC && log.topics.len() == 3 {
+        let recipient = topic(1)?;
+        return Some(Loan {
+            lender: log.address.clone(),
+            callee: recipient.clone(),
+            recipient,
+            legs: vec![Leg {
+                token: Some(topic(2)?),
+                amount: abi::word_u256(&log.data, 0)?,
+                premium: abi::word_u256(&log.data, 1)?,
+            }],
+            as_debt: false,
+            protocol: Tag::protocol("balancer", "v2"),
+        });
+    }
+    if topic0 == UNISWAP_V3_TOPIC && log.topics.len() == 3 {
+        let (amount0, amount1) = (abi::word_u256(&log.data, 0)?, abi::word_u256(&log.data, 1)?);
+        let (paid0, paid1) = (abi::word_u256(&log.data, 2)?, abi::word_u256(&log.data, 3)?);
+        return Some(Loan {
+            lender: log.address.clone(),
+            callee: topic(1)?,
+            recipient: topic(2)?,
+            legs: [(amount0, paid0), (amount1, paid1)]
+                .into_iter()
+                .filter(|(amount, _)| !amount.is_zero())
+                .map(|(amount, premium)| Leg {
+                    token: None,
+                    amount,
+                    premium,
+                })
+                .collect(),
+            as_debt: false,
+            protocol: Tag::protocol("uniswap", "v3"),
+        });
+    }
+    None
+}
@@ -0,0 +1,103 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::{self, Tag};
+use crate::u256::U256;
+use crate::{abi, amount};
+
+// Maker DssFlash FlashLoan(address,address,uint256,uint256)
+const DSS_FLASH_TOPIC: [u8; 32] =
+    hex_literal::hex!("0d7d75e01ab95780d3cd1c8ec0dd6c2ce19e3a20427eec8bf53283b6fb8e95f0");
+
+const ZERO_ADDRESS: [u8; 20] = [0u8; 20];
+
+struct FlashMint {
+    log_index: u32,
+    token: Vec<u8>,
+    receiver: Vec<u8>,
+    amount: U256,
+    fee: U256,
+    protocol: Tag,
+}
+
+/// Emits `FlashMint` for tokens minted and burned back inside one
+/// transaction: Maker's `DssFlash` (which logs the mint itself) and ERC-3156
+/// flash-mintable tokens, recognised by a mint to a receiver that the same
+/// receiver burns at least in full after running other code. `log_index`
+/// carries the repaying burn, or the DssFlash log.
+pub fn flash_mint_events(
+    tx_hash: &[u8],
+    logs: &[eth::Log],
+    block_number: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let mut mints: Vec<FlashMint> = logs.iter().filter_map(dss_flash).collect();
+
+    for (mint_pos, mint_log) in logs.iter().enumerate() {
+        let Some(mint) = abi::transfer(mint_log) else {
+            continue;
+        };
+        if mint.from != ZERO_ADDRESS || mints.iter().any(|m| m.token == mint_log.address) {
+            continue;
+        }
+        // The receiver's callback has to run between the mint and the burn.
+        let repaid = logs.iter().skip(mint_pos + 2).find_map(|log| {
+            let burn = abi::transfer(log)?;
+            (log.address == mint_log.address
+                && burn.from == mint.to
+                && burn.to == ZERO_ADDRESS
+                && burn.amount >= mint.amount)
+                .then_some((log.index, burn.amount))
+        });
+        if let Some((log_index, burned)) = repaid {
+            mints.push(FlashMint {
+                log_index,
+                token: mint_log.address.clone(),
+                receiver: mint.to.to_vec(),
+                amount: mint.amount,
+                fee: burned - mint.amount,
+                protocol: Tag::default(),
+            });
+        }
+    }
+
+    mints
+        .into_iter()
+        .map(|mint| {
+            let token = format!("0x{}", hex::encode(&mint.token));
+            let mut event = CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(tx_hash)),
+                block_number,
+                log_index: mint.log_index as u64,
+                event_type: "FlashMint".to_string(),
+                to: format!("0x{}", hex::encode(&mint.receiver)),
+                metadata: format!(
+                    "{{{},{}}}",
+                    amount::json_fields(annotations, "amount", mint.amount, &token),
+                    amount::json_fields(annotations, "fee", mint.fee, &token)
+                ),
+                contract_address: token,
+                ..Default::default()
+            };
+            mint.protocol.apply(&mut event);
+            event.standard = taxonomy::ERC20.to_string();
+            event
+        })
+        .collect()
+}
+
+fn dss_flash(log: &eth::Log) -> Option<FlashMint> {
+    // Balancer's FlashLoan has the same signature but also indexes the token.
+    if log.topics.first()?.as_slice() != DSS_FLASH_TOPIC || log.topics.len() != 2 {
+        return None;
+    }
+    Some(FlashMint {
+        log_index: log.index,
+        token: abi::address_bytes(abi::word(&log.data, 0)?)?.to_vec(),
+        receiver: abi::address_bytes(log.topics.get(1)?)?.to_vec(),
+        amount: abi::word_u256(&log.data, 1)?,
+        fee: abi::word_u256(&log.data, 2)?,
+        protocol: Tag::protocol("maker", ""),
+    })
+}
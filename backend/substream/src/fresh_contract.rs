@@ -0,0 +1,64 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::FreshContractInteraction;
+use crate::{calls, deployer, sender};
+
+/// Calls to `watch`ed contracts made in the block a contract was deployed,
+/// by the contract itself or in a transaction of its deployer, from the
+/// deploying transaction on; constructors calling a protocol are caught in
+/// the deploying transaction itself. One interaction per transaction,
+/// contract deployed and watched contract, on the first such call. Needs call
+/// traces to see deployments, and to see calls below the top level.
+pub fn interactions(blk: &eth::Block, watch: &[String]) -> Vec<FreshContractInteraction> {
+    let mut out: Vec<FreshContractInteraction> = Vec::new();
+    if watch.is_empty() {
+        return out;
+    }
+    let hex = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+    // (deployer, contract, deploying transaction) so far in the block.
+    let mut deployed: Vec<(String, String, String)> = Vec::new();
+
+    for tx in blk.transaction_traces.iter() {
+        if tx.status != eth::TransactionTraceStatus::Succeeded as i32 {
+            continue;
+        }
+        let Some(sender) = sender::sender(tx).map(|sender| hex(&sender)) else {
+            continue;
+        };
+        let tx_hash = hex(&tx.hash);
+        for (_, contract) in deployer::created(tx) {
+            deployed.push((sender.clone(), contract, tx_hash.clone()));
+        }
+
+        for (call_index, caller, callee, _) in calls::frames(tx) {
+            let protocol = hex(callee);
+            if !watch.contains(&protocol) {
+                continue;
+            }
+            let caller = hex(&caller);
+            for (deployer, contract, deploy_tx) in deployed.iter() {
+                if *contract != caller && *deployer != sender {
+                    continue;
+                }
+                let seen = out.iter().any(|i| {
+                    i.transaction_hash == tx_hash
+                        && i.contract == *contract
+                        && i.protocol == protocol
+                });
+                if seen {
+                    continue;
+                }
+                out.push(FreshContractInteraction {
+                    deployer: deployer.clone(),
+                    contract: contract.clone(),
+                    deploy_tx: deploy_tx.clone(),
+                    transaction_hash: tx_hash.clone(),
+                    protocol: protocol.clone(),
+                    call_index,
+                    same_transaction: *deploy_tx == tx_hash,
+                });
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,397 @@
+//! Entry points for the cargo-fuzz targets in `fuzz/`. Each one turns raw
+//! fuzzer bytes into the block shapes the handlers see and runs every decoder
+//! over them; any panic here would take down a whole block in production.
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::params::Params;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::{
+    abi, approvals, arb_loop, block_index, calls, distribution, donation, dusting, holders, nft,
+    permit2, poisoning, slippage, token_bound, token_metadata, token_shape, vault,
+};
+
+/// Reads a sequence of logs packed as
+/// `topic_count(1) address(20) topics(32 * topic_count) data_len(2) data`,
+/// stopping at the first truncated entry. Topic counts run up to 7 so the
+/// decoders also see more topics than any event has.
+fn logs(mut bytes: &[u8]) -> Vec<eth::Log> {
+    let mut logs = Vec::new();
+    while let Some((&count, rest)) = bytes.split_first() {
+        let topics_len = (count as usize % 8) * 32;
+        let Some((address, rest)) = rest.split_at_checked(20) else {
+            break;
+        };
+        let Some((topics, rest)) = rest.split_at_checked(topics_len) else {
+            break;
+        };
+        let Some((len, rest)) = rest.split_first_chunk::<2>() else {
+            break;
+        };
+        let (data, rest) = rest
+            .split_at_checked(u16::from_be_bytes(*len) as usize)
+            .unwrap_or((rest, &[]));
+
+        logs.push(eth::Log {
+            address: address.to_vec(),
+            topics: topics.chunks(32).map(<[u8]>::to_vec).collect(),
+            data: data.to_vec(),
+            index: logs.len() as u32,
+            ordinal: logs.len() as u64,
+            ..Default::default()
+        });
+        bytes = rest;
+    }
+    logs
+}
+
+/// Fuzzer bytes read front to back; every read past the end is `None`.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let (head, rest) = self.0.split_at_checked(len)?;
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        self.take(1)?.first().copied()
+    }
+
+    /// A byte string prefixed with its length, up to 255 bytes.
+    fn sized(&mut self) -> Option<Vec<u8>> {
+        let len = self.byte()? as usize;
+        self.take(len).map(<[u8]>::to_vec)
+    }
+
+    /// `address(sized) topic_count(1) topic(sized)* data(sized)`. Topics and
+    /// addresses can have any length, unlike in the logs `logs` reads.
+    fn log(&mut self, index: &mut u32) -> Option<eth::Log> {
+        let address = self.sized()?;
+        let topics = (0..self.byte()? % 8)
+            .map(|_| self.sized())
+            .collect::<Option<_>>()?;
+        let log = eth::Log {
+            address,
+            topics,
+            data: self.sized()?,
+            index: *index,
+            ordinal: *index as u64,
+            ..Default::default()
+        };
+        *index += 1;
+        Some(log)
+    }
+
+    fn logs(&mut self, index: &mut u32) -> Option<Vec<eth::Log>> {
+        (0..self.byte()? % 8).map(|_| self.log(index)).collect()
+    }
+
+    /// `depth(1) flags(1) address caller input value return_data (all sized)
+    /// logs`, then `key old new` (sized) when flag bit 2 adds a storage
+    /// change. Bits 0 and 1 mark the call reverted and failed.
+    fn call(&mut self, index: u32, log_index: &mut u32) -> Option<eth::Call> {
+        let depth = self.byte()? as u32;
+        let flags = self.byte()?;
+        let mut call = eth::Call {
+            index,
+            depth,
+            state_reverted: flags & 1 != 0,
+            status_failed: flags & 2 != 0,
+            address: self.sized()?,
+            caller: self.sized()?,
+            input: self.sized()?,
+            value: Some(eth::BigInt {
+                bytes: self.sized()?,
+            }),
+            return_data: self.sized()?,
+            logs: self.logs(log_index)?,
+            ..Default::default()
+        };
+        if flags & 4 != 0 {
+            call.storage_changes.push(eth::StorageChange {
+                address: call.address.clone(),
+                key: self.sized()?,
+                old_value: self.sized()?,
+                new_value: self.sized()?,
+                ..Default::default()
+            });
+        }
+        Some(call)
+    }
+
+    /// `status(1) hash from to input return_data public_key (all sized)
+    /// receipt_logs call_count(1) calls`. A status with bit 3 set leaves the
+    /// receipt out.
+    fn transaction(&mut self, index: u32, log_index: &mut u32) -> Option<eth::TransactionTrace> {
+        let status = self.byte()?;
+        let mut tx = eth::TransactionTrace {
+            index,
+            status: (status & 3) as i32,
+            hash: self.sized()?,
+            from: self.sized()?,
+            to: self.sized()?,
+            input: self.sized()?,
+            return_data: self.sized()?,
+            public_key: self.sized()?,
+            ..Default::default()
+        };
+        let logs = self.logs(log_index)?;
+        if status & 8 == 0 {
+            tx.receipt = Some(eth::TransactionReceipt {
+                logs,
+                ..Default::default()
+            });
+        }
+        tx.calls = (0..self.byte()? % 8)
+            .map(|call| self.call(call as u32 + 1, log_index))
+            .collect::<Option<_>>()?;
+        Some(tx)
+    }
+}
+
+/// Reads a block as `flags(1)` (bit 0 adds a header) followed by
+/// transactions until the bytes run out, dropping a truncated last one.
+fn block(bytes: &[u8]) -> eth::Block {
+    let mut reader = Reader(bytes);
+    let flags = reader.byte().unwrap_or_default();
+    let mut blk = eth::Block {
+        number: 1,
+        header: (flags & 1 != 0).then(eth::BlockHeader::default),
+        ..Default::default()
+    };
+    let mut log_index = 0;
+    while let Some(tx) = reader.transaction(blk.transaction_traces.len() as u32, &mut log_index) {
+        blk.transaction_traces.push(tx);
+    }
+    blk
+}
+
+/// Runs every store-free detector `map_candidate_events` runs over a fuzzed
+/// block whose transactions can lack receipts and call traces, and whose
+/// topics, addresses and words can have any length, along with the block
+/// index and the log walks of the store modules, which see the same blocks.
+/// Each block also runs with every receipt stripped, as light blocks ship,
+/// so successful transactions without a receipt come up on every input.
+pub fn blocks(bytes: &[u8]) {
+    let blk = block(bytes);
+    let mut light = blk.clone();
+    for tx in light.transaction_traces.iter_mut() {
+        tx.receipt = None;
+    }
+
+    for blk in [blk, light] {
+        let _ = run_block(&blk);
+    }
+}
+
+/// Runs the store-free detectors and store log walks `blocks` drives over
+/// one block, returning the events of `map_candidate_events`.
+fn run_block(blk: &eth::Block) -> Vec<CandidateEvent> {
+    let params = Params::default();
+    let events = crate::candidate_events(&params, blk);
+    let _ = block_index::keys(blk);
+    let _ = approvals::grants(blk);
+    let _ = nft::operator_grants(blk);
+    let _ = holders::balance_changes(blk, &[]);
+    let _ = token_bound::creations(blk);
+    let _ = token_metadata::unlisted_tokens(blk, &params.annotations());
+    let _ = token_shape::counts(blk);
+    for (_, log) in calls::block_logs(blk) {
+        let _ = permit2::change(&log, u64::MAX);
+        let _ = vault::deposit(&log);
+        let _ = token_bound::created(&log);
+    }
+    events
+}
+
+/// Runs the log decoders and the per-block log heuristics over fuzzed logs.
+pub fn log_decoders(bytes: &[u8]) {
+    let logs = logs(bytes);
+    let params = Params::default();
+    let annotations = params.annotations();
+
+    for log in logs.iter() {
+        let _ = abi::transfer(log);
+        for index in 0..log.topics.len() + 1 {
+            let _ = abi::topic_address(log, index);
+        }
+        let _ = permit2::change(log, u64::MAX);
+    }
+    let _ = arb_loop::detect(&logs, &annotations);
+    let _ = donation::donation_events(&[], &logs, 0, &annotations);
+
+    let blk = eth::Block {
+        transaction_traces: vec![eth::TransactionTrace {
+            receipt: Some(eth::TransactionReceipt {
+                logs,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let _ = dusting::dusting_events(&blk, u64::MAX, 1, &annotations);
+    let _ = poisoning::transfers(&blk);
+}
+
+/// Runs the ABI helpers and the calldata decoders over fuzzed calldata.
+pub fn calldata_decoders(bytes: &[u8]) {
+    for index in 0..4 {
+        let _ = abi::word(bytes, index);
+        let _ = abi::word_usize(bytes, index);
+        let _ = abi::word_address(bytes, index);
+        let _ = abi::word_array(bytes, index);
+        let _ = abi::word_bytes(bytes, index);
+    }
+
+    let tx = eth::TransactionTrace {
+        input: bytes.to_vec(),
+        ..Default::default()
+    };
+    let params = Params::default();
+    let annotations = params.annotations();
+    let _ = distribution::distribution_events(&tx, 0, true, &annotations);
+    let _ = slippage::slippage_events(&tx, 0, &annotations);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(last: u8) -> Vec<u8> {
+        let mut word = [0u8; 32];
+        word[31] = last;
+        word.to_vec()
+    }
+
+    /// A Transfer of 5 units from `0x…01` to `0x…02`.
+    fn transfer(index: u32, ordinal: u64) -> eth::Log {
+        eth::Log {
+            address: vec![0xaa; 20],
+            topics: vec![crate::TRANSFER_TOPIC.to_vec(), word(1), word(2)],
+            data: word(5),
+            index,
+            ordinal,
+            ..Default::default()
+        }
+    }
+
+    fn transaction(
+        receipt: Option<Vec<eth::Log>>,
+        call_logs: Vec<eth::Log>,
+    ) -> eth::TransactionTrace {
+        eth::TransactionTrace {
+            hash: vec![0x11; 32],
+            from: vec![0x01; 20],
+            status: eth::TransactionTraceStatus::Succeeded as i32,
+            receipt: receipt.map(|logs| eth::TransactionReceipt {
+                logs,
+                ..Default::default()
+            }),
+            calls: vec![eth::Call {
+                index: 1,
+                logs: call_logs,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn block(number: u64, transaction_traces: Vec<eth::TransactionTrace>) -> eth::Block {
+        eth::Block {
+            number,
+            header: Some(eth::BlockHeader::default()),
+            transaction_traces,
+            ..Default::default()
+        }
+    }
+
+    fn transfers_of(events: &[CandidateEvent]) -> Vec<&CandidateEvent> {
+        events
+            .iter()
+            .filter(|e| e.event_type == "Transfer")
+            .collect()
+    }
+
+    #[test]
+    fn empty_blocks_have_no_events() {
+        assert!(run_block(&eth::Block::default()).is_empty());
+        assert!(run_block(&block(1, Vec::new())).is_empty());
+        assert!(run_block(&block(1, vec![transaction(None, Vec::new())])).is_empty());
+    }
+
+    #[test]
+    fn successful_transactions_without_a_receipt_read_their_call_logs() {
+        let events = run_block(&block(1, vec![transaction(None, vec![transfer(0, 0)])]));
+        let transfers = transfers_of(&events);
+        assert_eq!(transfers.len(), 1);
+        assert!(transfers.iter().all(|e| e.source == "calls"));
+
+        let events = run_block(&block(
+            1,
+            vec![transaction(
+                Some(vec![transfer(0, 0)]),
+                vec![transfer(0, 0)],
+            )],
+        ));
+        assert!(transfers_of(&events).iter().all(|e| e.source == "receipt"));
+    }
+
+    /// Malformed transfers still classify by topic but carry no decoded
+    /// amounts.
+    #[test]
+    fn truncated_topics_and_data_are_not_decoded() {
+        let truncated = [
+            eth::Log {
+                topics: Vec::new(),
+                ..transfer(0, 0)
+            },
+            eth::Log {
+                topics: vec![crate::TRANSFER_TOPIC.to_vec()],
+                ..transfer(1, 1)
+            },
+            eth::Log {
+                topics: vec![crate::TRANSFER_TOPIC.to_vec(), vec![1; 5], word(2)],
+                ..transfer(2, 2)
+            },
+            eth::Log {
+                topics: vec![crate::TRANSFER_TOPIC[..31].to_vec(), word(1), word(2)],
+                ..transfer(3, 3)
+            },
+            eth::Log {
+                data: vec![5; 3],
+                ..transfer(4, 4)
+            },
+            eth::Log {
+                address: vec![0xaa; 3],
+                data: Vec::new(),
+                ..transfer(5, 5)
+            },
+        ];
+        for log in truncated {
+            let events = run_block(&block(
+                1,
+                vec![transaction(Some(vec![log.clone()]), Vec::new())],
+            ));
+            assert!(events.iter().all(|e| e.transfer.is_none()), "{:?}", log);
+            let events = run_block(&block(1, vec![transaction(None, vec![log])]));
+            assert!(events.iter().all(|e| e.transfer.is_none()));
+        }
+    }
+
+    #[test]
+    fn maximal_indices_and_numbers_do_not_overflow() {
+        let mut tx = transaction(Some(vec![transfer(u32::MAX, u64::MAX)]), Vec::new());
+        tx.index = u32::MAX;
+        tx.begin_ordinal = u64::MAX;
+        tx.end_ordinal = u64::MAX;
+        let events = run_block(&block(u64::MAX, vec![tx]));
+        let transfers = transfers_of(&events);
+        assert_eq!(transfers.len(), 1);
+        assert!(transfers.iter().all(|e| e.log_index == u64::from(u32::MAX)
+            && e.block_number == u64::MAX
+            && e.transfer.is_some()));
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::DeltaInt64;
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::GasAnomaly;
+use crate::sender;
+
+/// Key summing the gas used by the transactions that called a contract.
+pub fn gas_key(contract: &str) -> String {
+    format!("gas_used:{}", contract)
+}
+
+/// Key counting the transactions that called a contract.
+pub fn count_key(contract: &str) -> String {
+    format!("gas_txs:{}", contract)
+}
+
+/// Successful contract calls of the block (transactions with a recipient and
+/// calldata), as `(transaction, target)`.
+fn calls(blk: &eth::Block) -> impl Iterator<Item = (&eth::TransactionTrace, String)> {
+    blk.transaction_traces
+        .iter()
+        .filter(|tx| tx.status == eth::TransactionTraceStatus::Succeeded as i32)
+        .filter(|tx| !tx.to.is_empty() && !tx.input.is_empty())
+        .map(|tx| (tx, format!("0x{}", hex::encode(&tx.to))))
+}
+
+/// Gas used and transactions per target contract in the block.
+pub fn totals(blk: &eth::Block) -> BTreeMap<String, (i64, i64)> {
+    let mut totals: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+    for (tx, contract) in calls(blk) {
+        let total = totals.entry(contract).or_default();
+        total.0 += tx.gas_used as i64;
+        total.1 += 1;
+    }
+    totals
+}
+
+/// Transactions that used at least `multiple` times the average gas of the
+/// earlier transactions calling the same contract, once the contract has
+/// `min_samples` of them. Reads the deltas of `store_contract_gas`: a key's
+/// old value is its total before this block, so a block's own transactions
+/// never raise their baseline.
+pub fn anomalies(
+    blk: &eth::Block,
+    deltas: &[DeltaInt64],
+    multiple: u64,
+    min_samples: u64,
+) -> Vec<GasAnomaly> {
+    let before = |key: String| {
+        deltas
+            .iter()
+            .find(|d| d.key == key && d.operation != Operation::Delete)
+            .map(|d| d.old_value.max(0) as u64)
+            .unwrap_or_default()
+    };
+
+    let mut out = Vec::new();
+    for (tx, contract) in calls(blk) {
+        let samples = before(count_key(&contract));
+        if samples == 0 || samples < min_samples {
+            continue;
+        }
+        let total = before(gas_key(&contract));
+        if (tx.gas_used as u128) * (samples as u128) < (multiple as u128) * (total as u128) {
+            continue;
+        }
+        out.push(GasAnomaly {
+            transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+            contract,
+            from: sender::sender(tx)
+                .map(|sender| format!("0x{}", hex::encode(sender)))
+                .unwrap_or_default(),
+            gas_used: tx.gas_used,
+            average_gas: total / samples,
+            samples,
+        });
+    }
+    out
+}
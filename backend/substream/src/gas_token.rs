@@ -0,0 +1,102 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::{CandidateEvent, GasTokenUsage};
+
+// GST1, GST2 and 1inch CHI, each at the same address on every chain it was
+// deployed to.
+const GAS_TOKENS: [[u8; 20]; 3] = [
+    hex_literal::hex!("88d60255f917e3eb94eae199d827dad837fac4cb"),
+    hex_literal::hex!("0000000000b3f879cb30fe243b4dfee438691c04"),
+    hex_literal::hex!("0000000000004946c0e9f43f4dee607b0ef1fa1c"),
+];
+// mint(uint256)
+const MINT_SELECTOR: [u8; 4] = hex_literal::hex!("a0712d68");
+// free(uint256), freeUpTo(uint256), freeFrom(address,uint256),
+// freeFromUpTo(address,uint256)
+const FREE_SELECTORS: [[u8; 4]; 4] = [
+    hex_literal::hex!("d8ccd0f3"),
+    hex_literal::hex!("6366b936"),
+    hex_literal::hex!("5f2e2b45"),
+    hex_literal::hex!("079d229f"),
+];
+// Self-destructs in one transaction that look like freeing gas tokens of an
+// unlisted contract: each freed GST2 or CHI token is one.
+const MIN_SELF_DESTRUCTS: u32 = 10;
+
+/// Gas token activity of a transaction: mints and frees of the known gas
+/// tokens, self-destructs, and the storage refund the node reported. `None`
+/// when there's no mint or free, fewer than `MIN_SELF_DESTRUCTS`
+/// self-destructs, and a refund under `min_refund_percent` of the gas used.
+/// Self-destructs and refunds need call traces and gas changes.
+pub fn usage(tx: &eth::TransactionTrace, min_refund_percent: u64) -> Option<GasTokenUsage> {
+    let mut usage = GasTokenUsage::default();
+    for call in tx.calls.iter().filter(|call| !call.state_reverted) {
+        if call.suicide {
+            usage.self_destructs += 1;
+        }
+        if !GAS_TOKENS.contains(&<[u8; 20]>::try_from(&call.address[..]).unwrap_or_default()) {
+            continue;
+        }
+        let selector = call.input.get(..4).unwrap_or_default();
+        let kind = if selector == MINT_SELECTOR {
+            &mut usage.mints
+        } else if FREE_SELECTORS.iter().any(|free| selector == free) {
+            &mut usage.frees
+        } else {
+            continue;
+        };
+        *kind += 1;
+        let token = format!("0x{}", hex::encode(&call.address));
+        if !usage.tokens.contains(&token) {
+            usage.tokens.push(token);
+        }
+    }
+    usage.refund = tx
+        .calls
+        .iter()
+        .flat_map(|call| call.gas_changes.iter())
+        .filter(|change| change.reason == eth::gas_change::Reason::TxRefunds as i32)
+        .map(|change| change.new_value.saturating_sub(change.old_value))
+        .sum();
+
+    let large_refund = tx.gas_used > 0
+        && (usage.refund as u128) * 100 >= (min_refund_percent as u128) * (tx.gas_used as u128);
+    (usage.mints > 0
+        || usage.frees > 0
+        || usage.self_destructs >= MIN_SELF_DESTRUCTS
+        || (usage.refund > 0 && large_refund))
+        .then_some(usage)
+}
+
+/// Sets `gas_token_usage` on every event of the transactions with a `high`
+/// or `critical` event that show gas token activity.
+pub fn annotate(events: &mut [CandidateEvent], blk: &eth::Block, min_refund_percent: u64) {
+    let mut flagged: Vec<&str> = events
+        .iter()
+        .filter(|e| e.severity == "high" || e.severity == "critical")
+        .map(|e| e.transaction_hash.as_str())
+        .filter(|hash| !hash.is_empty())
+        .collect();
+    flagged.sort_unstable();
+    flagged.dedup();
+    let usages: Vec<(String, GasTokenUsage)> = blk
+        .transaction_traces
+        .iter()
+        .filter_map(|tx| {
+            let hash = format!("0x{}", hex::encode(&tx.hash));
+            if !flagged.contains(&hash.as_str()) {
+                return None;
+            }
+            Some((hash, usage(tx, min_refund_percent)?))
+        })
+        .collect();
+
+    for event in events.iter_mut() {
+        if let Some((_, usage)) = usages
+            .iter()
+            .find(|(hash, _)| *hash == event.transaction_hash)
+        {
+            event.gas_token_usage = Some(usage.clone());
+        }
+    }
+}
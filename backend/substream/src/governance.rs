@@ -0,0 +1,88 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::abi;
+use crate::pb::pulseproof::{CandidateEvent, GovernanceParams};
+
+// EIP-1967 Upgraded(address)
+const UPGRADED_TOPIC: [u8; 32] =
+    hex_literal::hex!("bc7cd75a20ee27fd9adebab32041f755214dbc6bffa90cc0225b39da2e5c2d3b");
+// bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)
+const IMPLEMENTATION_SLOT: [u8; 32] =
+    hex_literal::hex!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// First value and latest write of a proxy's implementation slot in a
+/// transaction.
+struct SlotWrite<'a> {
+    proxy: &'a [u8],
+    old: &'a [u8],
+    new: &'a [u8],
+    call_index: u32,
+}
+
+/// Emits `ImplementationSlotChanged` for proxies whose EIP-1967
+/// implementation slot the transaction rewrote without emitting the matching
+/// `Upgraded` log: an upgrade that hid from log-only monitoring. Needs state
+/// changes. `log_index` carries the index of the call that last wrote the
+/// slot.
+pub fn silent_upgrades(
+    tx: &eth::TransactionTrace,
+    logs: &[eth::Log],
+    block_number: u64,
+) -> Vec<CandidateEvent> {
+    let mut writes: Vec<SlotWrite> = Vec::new();
+    for call in tx.calls.iter().filter(|call| !call.state_reverted) {
+        for change in call.storage_changes.iter() {
+            if change.key != IMPLEMENTATION_SLOT {
+                continue;
+            }
+            match writes.iter_mut().find(|w| w.proxy == change.address) {
+                Some(write) => {
+                    write.new = &change.new_value;
+                    write.call_index = call.index;
+                }
+                None => writes.push(SlotWrite {
+                    proxy: &change.address,
+                    old: &change.old_value,
+                    new: &change.new_value,
+                    call_index: call.index,
+                }),
+            }
+        }
+    }
+
+    writes
+        .into_iter()
+        .filter(|write| write.old != write.new && write.new.len() == 32)
+        .filter(|write| {
+            !logs.iter().any(|log| {
+                log.address == write.proxy
+                    && log.topics.first().map(Vec::as_slice) == Some(&UPGRADED_TOPIC[..])
+                    && log.topics.get(1).map(Vec::as_slice) == Some(write.new)
+            })
+        })
+        .map(|write| {
+            let address = |word| {
+                abi::address_bytes(word)
+                    .map(|address| format!("0x{}", hex::encode(address)))
+                    .unwrap_or_default()
+            };
+            let previous = address(write.old);
+            let current = address(write.new);
+            CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                block_number,
+                log_index: write.call_index as u64,
+                contract_address: format!("0x{}", hex::encode(write.proxy)),
+                event_type: "ImplementationSlotChanged".to_string(),
+                from: previous.clone(),
+                to: current.clone(),
+                governance: Some(GovernanceParams {
+                    previous,
+                    current,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
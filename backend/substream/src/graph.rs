@@ -0,0 +1,98 @@
+use crate::pb::pulseproof::{CandidateEvent, CandidateEvents};
+use crate::pb::sf::substreams::sink::entity::v1::{
+    entity_change, value, Array, EntityChange, EntityChanges, Field, Value,
+};
+
+/// Entity name the subgraph schema declares for candidate events.
+const ENTITY: &str = "CandidateEvent";
+
+/// Stable entity id: transaction hash (or block number for block-wide
+/// events), log index and `part`, which together are unique per event.
+pub fn entity_id(event: &CandidateEvent) -> String {
+    let scope = if event.transaction_hash.is_empty() {
+        event.block_number.to_string()
+    } else {
+        event.transaction_hash.clone()
+    };
+    format!("{}-{}-{}", scope, event.log_index, event.part)
+}
+
+/// One create per candidate event, in output order.
+pub fn entity_changes(events: &CandidateEvents) -> EntityChanges {
+    EntityChanges {
+        entity_changes: events
+            .events
+            .iter()
+            .enumerate()
+            .map(|(ordinal, event)| EntityChange {
+                entity: ENTITY.to_string(),
+                id: entity_id(event),
+                ordinal: ordinal as u64,
+                operation: entity_change::Operation::Create as i32,
+                fields: fields(event, &events.config_epoch),
+            })
+            .collect(),
+    }
+}
+
+fn fields(event: &CandidateEvent, config_epoch: &str) -> Vec<Field> {
+    let string = |name: &str, value: &str| field(name, value::Typed::String(value.to_string()));
+    let bigint = |name: &str, value: u64| field(name, value::Typed::Bigint(value.to_string()));
+    let boolean = |name: &str, value: bool| field(name, value::Typed::Bool(value));
+    let strings = |name: &str, values: &[String]| {
+        let value = values
+            .iter()
+            .map(|value| Value {
+                typed: Some(value::Typed::String(value.clone())),
+            })
+            .collect();
+        field(name, value::Typed::Array(Array { value }))
+    };
+
+    vec![
+        string("transactionHash", &event.transaction_hash),
+        bigint("blockNumber", event.block_number),
+        bigint("logIndex", event.log_index),
+        bigint("txIndex", event.tx_index as u64),
+        bigint("part", event.part as u64),
+        string("orderingKey", &event.ordering_key),
+        string("contractAddress", &event.contract_address),
+        string("eventSignature", &event.event_signature),
+        string("eventType", &event.event_type),
+        string("metadata", &event.metadata),
+        string("retentionClass", &event.retention_class),
+        string("severity", &event.severity),
+        string("shadowSeverity", &event.shadow_severity),
+        string("from", &event.from),
+        string("to", &event.to),
+        string("owner", &event.owner),
+        string("spender", &event.spender),
+        string("fingerprint", &event.fingerprint),
+        string("protocol", &event.protocol),
+        string("protocolVersion", &event.protocol_version),
+        string("standard", &event.standard),
+        string("tokenSymbol", &event.token_symbol),
+        string("tokenPeg", &event.token_peg),
+        string("asset", &event.asset),
+        string("tokenClass", &event.token_class),
+        boolean("nonstandardAbi", event.nonstandard_abi),
+        boolean("likelyPrivate", event.likely_private),
+        boolean("flashFunded", event.flash_funded),
+        boolean("gasTokenUsage", event.gas_token_usage.is_some()),
+        string(
+            "linkKey",
+            event.bridge.as_ref().map_or("", |bridge| &bridge.link_key),
+        ),
+        strings("routingKeys", &event.routing_keys),
+        strings("tenants", &event.tenants),
+        string("configEpoch", config_epoch),
+    ]
+}
+
+fn field(name: &str, typed: value::Typed) -> Field {
+    Field {
+        name: name.to_string(),
+        new_value: Some(Value { typed: Some(typed) }),
+        old_value: None,
+    }
+}
@@ -0,0 +1,107 @@
+use substreams::scalar::BigInt;
+use substreams::store::{DeltaBigInt, StoreGet, StoreGetBigInt};
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::NftHolderSnapshot;
+use crate::{calls, nft};
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Key of `holder`'s balance of `collection` in `store_nft_balances`.
+pub fn balance_key(collection: &str, holder: &str) -> String {
+    format!("balance:{}:{}", collection, holder)
+}
+
+/// Keys of a collection's totals in `store_nft_holders`: addresses holding a
+/// nonzero balance, tokens held, and the sum of squared balances.
+pub fn holders_key(collection: &str) -> String {
+    format!("holders:{}", collection)
+}
+
+pub fn supply_key(collection: &str) -> String {
+    format!("supply:{}", collection)
+}
+
+pub fn squares_key(collection: &str) -> String {
+    format!("squares:{}", collection)
+}
+
+/// Balance changes the block's ERC-721 and ERC-1155 transfers of watched
+/// collections make, as `(ordinal, balance key, change)`. Mints and burns
+/// only move the non-zero side.
+pub fn balance_changes(blk: &eth::Block, watch: &[String]) -> Vec<(u64, String, BigInt)> {
+    let mut out = Vec::new();
+    for (_, log) in calls::block_logs(blk) {
+        for item in nft::items(&log) {
+            if !watch.contains(&item.collection) || item.amount.is_zero() {
+                continue;
+            }
+            let mut bytes = [0u8; 32];
+            item.amount.to_big_endian(&mut bytes);
+            let amount = BigInt::from_unsigned_bytes_be(&bytes);
+            if item.from != ZERO_ADDRESS {
+                let key = balance_key(&item.collection, &item.from);
+                out.push((item.ordinal, key, amount.neg()));
+            }
+            if item.to != ZERO_ADDRESS {
+                out.push((
+                    item.ordinal,
+                    balance_key(&item.collection, &item.to),
+                    amount,
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// What a balance delta adds to its collection's totals: a holder gained or
+/// lost, the supply change and the change in squared balance.
+pub fn total_changes(delta: &DeltaBigInt) -> Vec<(String, BigInt)> {
+    let Some(collection) = delta
+        .key
+        .strip_prefix("balance:")
+        .and_then(|rest| rest.split_once(':'))
+        .map(|(collection, _)| collection)
+    else {
+        return Vec::new();
+    };
+    let zero = BigInt::zero();
+    let (old, new) = (&delta.old_value, &delta.new_value);
+    let mut out = vec![
+        (supply_key(collection), new - old),
+        (squares_key(collection), new * new - old * old),
+    ];
+    if *old <= zero && *new > zero {
+        out.push((holders_key(collection), BigInt::one()));
+    } else if *old > zero && *new <= zero {
+        out.push((holders_key(collection), BigInt::one().neg()));
+    }
+    out
+}
+
+/// Current totals of a collection. `concentration_bps` is the Herfindahl
+/// index of the holders' shares in basis points: 10000 when one address holds
+/// everything, `10000 / n` when `n` hold equal amounts. The top holder's share
+/// is at most its square root. Balances the collection moved before the
+/// start block make the totals inconsistent; the index then pins at 10000
+/// and a negative holder count reads as zero, rather than aborting the block.
+pub fn snapshot(collection: &str, totals: &StoreGetBigInt) -> NftHolderSnapshot {
+    let supply = totals.get_last(supply_key(collection)).unwrap_or_default();
+    let squares = totals.get_last(squares_key(collection)).unwrap_or_default();
+    let concentration_bps = if supply.is_zero() {
+        0
+    } else {
+        let bps = squares * 10_000u32 / (&supply * &supply);
+        u64::try_from(&bps).unwrap_or(u64::MAX).min(10_000) as u32
+    };
+    NftHolderSnapshot {
+        collection: collection.to_string(),
+        holders: totals
+            .get_last(holders_key(collection))
+            .and_then(|holders| u64::try_from(&holders).ok())
+            .unwrap_or_default(),
+        supply: supply.to_string(),
+        concentration_bps,
+    }
+}
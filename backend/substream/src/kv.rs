@@ -0,0 +1,38 @@
+use prost::Message;
+
+use crate::pb::pulseproof::{CandidateEvent, CandidateEvents};
+use crate::pb::sf::substreams::sink::kv::v1::{kv_operation, KvOperation, KvOperations};
+
+/// Key of an event: `block_number:transaction_hash:log_index`, with `:part`
+/// appended for the later parts of events sharing a position (the hash is
+/// empty on block-wide events). A block's events share the `block_number:`
+/// prefix.
+pub fn key(event: &CandidateEvent) -> String {
+    let key = format!(
+        "{}:{}:{}",
+        event.block_number, event.transaction_hash, event.log_index
+    );
+    if event.part == 0 {
+        key
+    } else {
+        format!("{}:{}", key, event.part)
+    }
+}
+
+/// One set per candidate event, in output order, with the protobuf-encoded
+/// `CandidateEvent` as value.
+pub fn kv_operations(events: &CandidateEvents) -> KvOperations {
+    KvOperations {
+        operations: events
+            .events
+            .iter()
+            .enumerate()
+            .map(|(ordinal, event)| KvOperation {
+                key: key(event),
+                value: event.encode_to_vec(),
+                ordinal: ordinal as u64,
+                r#type: kv_operation::Type::Set as i32,
+            })
+            .collect(),
+    }
+}
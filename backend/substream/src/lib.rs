@@ -1,111 +1,2378 @@
+// The handler macros expand params handlers into exported functions taking raw
+// pointers from the substreams runtime.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+// A panic aborts the module and stalls the stream at the block that caused
+// it, so block data is read through checked accessors (`get`, `first`,
+// `split_first_chunk`, the `abi` decoders) and nothing unwraps.
+#![deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::unreachable,
+    clippy::unwrap_used
+)]
+
 use substreams::errors::Error;
 use substreams_ethereum::pb::eth::v2 as eth;
-use hex;
 
+mod abi;
+mod activity;
+mod admin;
+mod amount;
+mod annotations;
+mod anomaly;
+mod approvals;
+mod arb_loop;
+mod block_index;
+mod bloom;
+mod bridge;
+#[cfg(feature = "calibration")]
+pub mod calibration;
+mod call_tree;
+mod calls;
+mod capabilities;
+mod chain;
+mod commitment;
+mod context;
+mod database;
+mod decoded;
+mod delegation;
+mod deployer;
+mod dictionary;
+mod distribution;
+mod donation;
+mod dormant;
+mod dusting;
+#[cfg(feature = "embedding")]
+pub mod embedding;
+mod evidence;
+mod failed_probe;
+mod fingerprint;
+mod flash_loan;
+mod flash_mint;
+mod fresh_contract;
+#[cfg(any(feature = "fuzzing", test))]
+pub mod fuzzing;
+mod gas_anomaly;
+mod gas_token;
+mod governance;
+mod graph;
+mod holders;
+mod kv;
+mod metrics;
+mod nft;
+mod novelty;
+mod ordering;
+mod params;
+mod params_blob;
+mod pause;
+#[allow(dead_code, clippy::all)]
 mod pb;
+mod permit2;
+mod permit_deadline;
+mod poisoning;
+mod precompile;
+mod private_flow;
+mod reentrancy;
+mod rehearsal;
+mod retention;
+mod risky_approval;
+mod routing;
+mod rug_pull;
+mod sandwich;
+mod sender;
+mod setup;
+#[cfg(feature = "sink-check")]
+pub mod sink_check;
+mod slippage;
+mod stolen;
+mod suspicion;
+mod taxonomy;
+mod token_bound;
+mod token_metadata;
+mod token_shape;
+mod trace;
+mod u256;
+mod unknown_topics;
+mod vault;
+mod velocity;
+mod verifiable;
+mod vesting;
+mod withdrawal;
+
+use std::collections::BTreeMap;
+
+use substreams::pb::sf::substreams::index::v1::Keys;
+use substreams::pb::substreams::{store_delta, Clock};
+use substreams::store::{
+    Appender, DeltaBigInt, DeltaInt64, Deltas, StoreAdd, StoreAddBigInt, StoreAddInt64,
+    StoreAppend, StoreDelete, StoreGet, StoreGetBigInt, StoreGetInt64, StoreGetString, StoreNew,
+    StoreSet, StoreSetIfNotExists, StoreSetIfNotExistsInt64, StoreSetIfNotExistsString,
+    StoreSetInt64, StoreSetString,
+};
+
+use annotations::Annotations;
+use u256::U256;
+
+use pb::sf::substreams::sink::database::v1::DatabaseChanges;
+use pb::sf::substreams::sink::entity::v1::EntityChanges;
+use pb::sf::substreams::sink::kv::v1::KvOperations;
+
+use pb::pulseproof::{
+    ActionRecommendations, ActivitySpikes, AddressIndex, AddressIndexEntry, BlockCommitment,
+    BlockSummary, CallTreeSummaries, CandidateEvent, CandidateEvents, ConfigDescriptor, Coverage,
+    DecoderMetrics, EnumDictionary, EvidenceBundles, FreshContractInteractions, GasAnomalies,
+    NftHolderSnapshots, OutputMetrics, RuleCardinalities, StalePermit2Allowances,
+    SuspiciousTransactions, TopicCount, UnknownTopicDigest, VerifiableAlerts,
+};
+
+// ERC20 canonical topics
+const TRANSFER_TOPIC: [u8; 32] =
+    hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+const APPROVAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+// Uniswap V2 Swap(address,uint256,uint256,uint256,uint256,address)
+const SWAP_TOPIC: [u8; 32] =
+    hex_literal::hex!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+// ERC-1155 and ERC-721/1155 operator topics
+const TRANSFER_SINGLE_TOPIC: [u8; 32] =
+    hex_literal::hex!("c3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62");
+const TRANSFER_BATCH_TOPIC: [u8; 32] =
+    hex_literal::hex!("4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb");
+const APPROVAL_FOR_ALL_TOPIC: [u8; 32] =
+    hex_literal::hex!("17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31");
+// Proxy, Ownable and AccessControl governance topics
+const UPGRADED_TOPIC: [u8; 32] =
+    hex_literal::hex!("bc7cd75a20ee27fd9adebab32041f755214dbc6bffa90cc0225b39da2e5c2d3b");
+const ADMIN_CHANGED_TOPIC: [u8; 32] =
+    hex_literal::hex!("7e644d79422f17c01e4894b5f4f588d331ebfa28653d42ae832dc59e38c9798f");
+const OWNERSHIP_TRANSFERRED_TOPIC: [u8; 32] =
+    hex_literal::hex!("8be0079c531659141344cd1fd0a4f28419497f9722a3daafe3b4186f6b6457e0");
+const ROLE_GRANTED_TOPIC: [u8; 32] =
+    hex_literal::hex!("2f8788117e7eff1d82e926ec794901d17c78024a50270940304540a733656f0d");
+const ROLE_REVOKED_TOPIC: [u8; 32] =
+    hex_literal::hex!("f6391f5c32d9c69d2a47ea670b442974b53935d1edc7fd64eb21e047a839171b");
+
+/// Maps a log's raw topic0 to the signature and event type it is emitted
+/// under, on every chain; chain profiles add their swap topics. Matching
+/// bytes keeps logs that get dropped from being hex-encoded.
+fn classify(topic0: &[u8]) -> Option<(&'static [u8; 32], &'static str)> {
+    if topic0 == TRANSFER_TOPIC {
+        Some((&TRANSFER_TOPIC, "Transfer"))
+    } else if topic0 == APPROVAL_TOPIC {
+        Some((&APPROVAL_TOPIC, "Approval"))
+    } else if topic0 == SWAP_TOPIC {
+        Some((&SWAP_TOPIC, "Swap"))
+    } else if topic0 == TRANSFER_SINGLE_TOPIC {
+        Some((&TRANSFER_SINGLE_TOPIC, "NftTransfer"))
+    } else if topic0 == TRANSFER_BATCH_TOPIC {
+        Some((&TRANSFER_BATCH_TOPIC, "NftTransfer"))
+    } else if topic0 == APPROVAL_FOR_ALL_TOPIC {
+        Some((&APPROVAL_FOR_ALL_TOPIC, "ApprovalForAll"))
+    } else if topic0 == UPGRADED_TOPIC {
+        Some((&UPGRADED_TOPIC, "ProxyUpgraded"))
+    } else if topic0 == ADMIN_CHANGED_TOPIC {
+        Some((&ADMIN_CHANGED_TOPIC, "AdminChanged"))
+    } else if topic0 == OWNERSHIP_TRANSFERRED_TOPIC {
+        Some((&OWNERSHIP_TRANSFERRED_TOPIC, "OwnershipTransferred"))
+    } else if topic0 == ROLE_GRANTED_TOPIC {
+        Some((&ROLE_GRANTED_TOPIC, "RoleGranted"))
+    } else if topic0 == ROLE_REVOKED_TOPIC {
+        Some((&ROLE_REVOKED_TOPIC, "RoleRevoked"))
+    } else {
+        None
+    }
+}
+
+/// Event type an ERC20 transfer is emitted under: `LargeTransfer` once it
+/// moves more than `large_units` whole tokens (0 disables the split).
+fn transfer_type(
+    annotations: &Annotations,
+    log: &eth::Log,
+    transfer: &abi::Transfer,
+    large_units: u64,
+) -> &'static str {
+    if large_units == 0 {
+        return "Transfer";
+    }
+    let token = format!("0x{}", hex::encode(&log.address));
+    if amount::above_units(annotations, transfer.amount, &token, large_units) {
+        "LargeTransfer"
+    } else {
+        "Transfer"
+    }
+}
+
+/// Storage tier hint for sinks: raw high-volume token events can go to cold
+/// storage quickly, grants stay queryable longer, and heuristic detections are
+/// kept hot for triage.
+fn retention_class(event_type: &str) -> &'static str {
+    match event_type {
+        "Transfer" | "NftTransfer" | "Swap" | "CustomTopic" => "cold",
+        "Approval" | "ApprovalForAll" | "TokenBoundAccountCreated" | "CrossChainMessage" => "warm",
+        _ => "hot",
+    }
+}
+
+/// Default triage priority of an event type, for events whose detector didn't
+/// assign one.
+fn severity(event_type: &str) -> &'static str {
+    match event_type {
+        "Transfer"
+        | "Approval"
+        | "Swap"
+        | "NftTransfer"
+        | "ApprovalForAll"
+        | "FlashLoan"
+        | "FlashMint"
+        | "BeaconWithdrawal"
+        | "CustomTopic"
+        | "TokenBoundAccountCreated"
+        | "CrossChainMessage" => "info",
+        "ArbLoop"
+        | "EoaDelegationSet"
+        | "MassDistribution"
+        | "DustingCampaign"
+        | "ChainAnomaly"
+        | "VestingUnlock"
+        | "LockExpired"
+        | "LongLivedPermit"
+        | "LargeTransfer"
+        | "ProxyUpgraded"
+        | "AdminChanged"
+        | "OwnershipTransferred"
+        | "RoleGranted"
+        | "RoleRevoked"
+        | "RiskyApproval"
+        | "SandwichCandidate"
+        | "FailedProbe"
+        | "PrecompileAnomaly" => "medium",
+        _ => "high",
+    }
+}
+
+/// Fields derived from the rest of the event, filled once every decoder and
+/// heuristic has run.
+fn finish(event: &mut CandidateEvent, annotations: &Annotations) {
+    event.retention_class = retention_class(&event.event_type).to_string();
+    if event.severity.is_empty() {
+        event.severity = severity(&event.event_type).to_string();
+    }
+    annotations.annotate(event);
+    event.fingerprint = fingerprint::fingerprint(event);
+}
+
+/// Drops events of rules in shadow mode to `info`, keeping the severity they
+/// would have had in `shadow_severity`.
+fn shadow(events: &mut [CandidateEvent], params: &params::Params) {
+    for event in events.iter_mut() {
+        if params.rule_mode(&event.event_type) == params::RuleMode::Shadow {
+            event.shadow_severity = std::mem::replace(&mut event.severity, "info".to_string());
+        }
+    }
+}
 
-use pb::pulseproof::{CandidateEvent, CandidateEvents};
+/// Runs the events a map detects through what every emitted event gets:
+/// derived fields, shadow-mode rules, transaction context, routing keys and
+/// tenants, traces and block order. The caller sets the output's `config_epoch`.
+fn emit(
+    mut events: Vec<CandidateEvent>,
+    blk: &eth::Block,
+    params: &params::Params,
+) -> CandidateEvents {
+    let annotations = params.annotations();
+    for event in events.iter_mut() {
+        finish(event, &annotations);
+    }
+    shadow(&mut events, params);
+    context::apply(&mut events, blk);
+    routing::route(&mut events, params);
+    trace::attach(&mut events, blk, params);
+    ordering::order(&mut events, blk);
+    CandidateEvents {
+        events,
+        ..Default::default()
+    }
+}
 
-// ERC20 canonical topics (paste exact hex strings)
-const TRANSFER_TOPIC: &str =
-    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
-const APPROVAL_TOPIC: &str =
-    "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
-const SWAP_TOPIC: &str = "0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1";
-const PERMIT_TOPIC: &str = "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
-const FLASHLOAN_TOPIC: &str = "0x8a8c523c5f1c4d3f7f";
+/// Attaches the raw payload a decoded log event carries, as selected by the
+/// `raw_payload` param.
+fn raw_payload(
+    event: &mut CandidateEvent,
+    log: &eth::Log,
+    tx: &eth::TransactionTrace,
+    mode: params::RawPayload,
+) {
+    if mode == params::RawPayload::Omit {
+        return;
+    }
+    event.topics = log
+        .topics
+        .iter()
+        .map(|t| format!("0x{}", hex::encode(t)))
+        .collect();
+    event.data = log.data.clone();
+    if mode == params::RawPayload::Full {
+        event.tx_input = tx.input.clone();
+    }
+}
 
+#[substreams::handlers::map]
+fn index_events(blk: eth::Block) -> Result<Keys, Error> {
+    Ok(Keys {
+        keys: block_index::keys(&blk),
+    })
+}
 
 #[substreams::handlers::map]
-fn map_candidate_events(blk: eth::Block) -> Result<CandidateEvents, Error> {
+fn map_candidate_events(params: String, blk: eth::Block) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+
+    Ok(CandidateEvents {
+        events: candidate_events(&params, &blk),
+        config_epoch,
+    })
+}
+
+/// The signature and event type `map_candidate_events` decodes a log as under
+/// `params`: the topics every chain classifies, the chain profile's swap
+/// topics and the `topics` param's CustomTopics.
+fn log_type<'a>(params: &params::Params, log: &'a eth::Log) -> Option<(&'a [u8], &'static str)> {
+    let topic0 = log.topics.first().map(Vec::as_slice).unwrap_or_default();
+    let classified =
+        classify(topic0).or(params.chain.swap(topic0).map(|(topic, _)| (topic, "Swap")));
+    match classified {
+        // ERC-721 shares the signature and indexes the token id too.
+        Some((signature, "Transfer")) if log.topics.len() == 4 => {
+            Some((&signature[..], "NftTransfer"))
+        }
+        Some((signature, event_type)) => Some((&signature[..], event_type)),
+        None if params.custom_topic(topic0) => Some((topic0, "CustomTopic")),
+        None => None,
+    }
+}
+
+/// Decodes a log `map_candidate_events` classifies into its event, without
+/// `finish`. `None` for logs it doesn't classify and contracts `params`
+/// filters out. Swap legs are left to the caller, which sees the
+/// transaction's other logs.
+fn log_event(
+    params: &params::Params,
+    block_number: u64,
+    tx: &eth::TransactionTrace,
+    log: &eth::Log,
+    source: &str,
+) -> Option<CandidateEvent> {
+    let topic0 = log.topics.first().map(Vec::as_slice).unwrap_or_default();
+    let chain_swap = params.chain.swap(topic0);
+    let (signature, event_type) = log_type(params, log)?;
+    // Only logs that matched get hex-encoded.
+    let contract_addr = format!("0x{}", hex::encode(&log.address));
+    if !params.allows(&contract_addr) {
+        return None;
+    }
+    let annotations = params.annotations();
+
+    let mut event = CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+        block_number,
+        log_index: log.index as u64,
+        contract_address: contract_addr,
+        event_signature: format!("0x{}", hex::encode(signature)),
+        event_type: event_type.to_string(),
+        source: source.to_string(),
+        ..Default::default()
+    };
+    raw_payload(&mut event, log, tx, params.raw_payload);
+
+    match event_type {
+        "Transfer" => match abi::transfer(log) {
+            Some(transfer) => {
+                event.from = format!("0x{}", hex::encode(transfer.from));
+                event.to = format!("0x{}", hex::encode(transfer.to));
+                event.nonstandard_abi = transfer.nonstandard;
+                event.event_type =
+                    transfer_type(&annotations, log, &transfer, params.large_transfer_units)
+                        .to_string();
+                event.transfer = decoded::transfer(&annotations, log);
+            }
+            None => {
+                event.from = abi::topic_address(log, 1).unwrap_or_default();
+                event.to = abi::topic_address(log, 2).unwrap_or_default();
+            }
+        },
+        "Approval" => {
+            event.owner = abi::topic_address(log, 1).unwrap_or_default();
+            event.spender = abi::topic_address(log, 2).unwrap_or_default();
+            event.approval = decoded::approval(&annotations, log);
+        }
+        "NftTransfer" => {
+            event.nft_transfer = decoded::nft_transfer(log);
+            if let Some(transfer) = &event.nft_transfer {
+                event.from = transfer.from.clone();
+                event.to = transfer.to.clone();
+            }
+        }
+        "ProxyUpgraded"
+        | "AdminChanged"
+        | "OwnershipTransferred"
+        | "RoleGranted"
+        | "RoleRevoked" => {
+            event.governance = decoded::governance(event_type, log);
+            if let Some(governance) = &event.governance {
+                event.from = governance.previous.clone();
+                event.to = governance.current.clone();
+            }
+        }
+        "ApprovalForAll" => {
+            event.operator_approval = decoded::operator_approval(log);
+            if let Some(approval) = &event.operator_approval {
+                event.owner = approval.owner.clone();
+                event.spender = approval.operator.clone();
+            }
+        }
+        // Chain-specific swap logs have their own layouts.
+        "Swap" if chain_swap.is_none() => {
+            event.swap = decoded::swap(log);
+            if let Some(swap) = &event.swap {
+                event.from = swap.sender.clone();
+                event.to = swap.to.clone();
+            }
+        }
+        _ => {}
+    }
+    taxonomy::for_log(event_type, log).apply(&mut event);
+    if let Some((_, tag)) = chain_swap {
+        tag.apply(&mut event);
+    }
+    Some(event)
+}
+
+/// The events `map_candidate_events` emits for a block, finished and ordered.
+fn candidate_events(params: &params::Params, blk: &eth::Block) -> Vec<CandidateEvent> {
+    let capabilities = capabilities::detect(blk);
+    let annotations = params.annotations();
     let mut events_vec: Vec<CandidateEvent> = Vec::new();
-    
+
     for tx in blk.transaction_traces.iter() {
-        let tx_hash = format!("0x{}", hex::encode(&tx.hash));
+        let tx_start = events_vec.len();
+
+        events_vec.extend(delegation::delegation_events(tx, blk.number));
+        events_vec.extend(distribution::distribution_events(
+            tx,
+            blk.number,
+            params.mass_distribution_detail,
+            &annotations,
+        ));
+        events_vec.extend(slippage::slippage_events(tx, blk.number, &annotations));
+        // Without call traces the stack of open calls can't be rebuilt.
+        if capabilities.has(capabilities::CALL_TRACES) {
+            events_vec.extend(reentrancy::reentrancy_events(tx, blk.number));
+            events_vec.extend(reentrancy::reentrant_calls(tx, blk.number));
+            events_vec.extend(precompile::anomalies(
+                tx,
+                blk.number,
+                params.precompile_min_calls,
+            ));
+        }
+
+        // Light blocks ship without receipts; fall back to the logs recorded on
+        // the call traces so the transaction isn't skipped outright.
+        let (logs, source) = calls::tx_logs(tx);
+        // Transfers not yet attributed to a swap, as (log index, from, to,
+        // event type).
+        let mut unattributed: Vec<(u32, &[u8], &[u8], &str)> = Vec::new();
+
+        for log in logs.iter() {
+            // Swap legs are tracked whether or not the transfer is emitted.
+            if let Some(transfer) = abi::transfer(log) {
+                let event_type =
+                    transfer_type(&annotations, log, &transfer, params.large_transfer_units);
+                unattributed.push((log.index, transfer.from, transfer.to, event_type));
+            }
+
+            let Some(mut event) = log_event(params, blk.number, tx, log, source) else {
+                continue;
+            };
+            if event.event_type == "Swap" {
+                // A swap's legs are the transfers into and out of the pool
+                // since its previous swap in the transaction.
+                unattributed.retain(|(index, from, to, event_type)| {
+                    if *from != log.address && *to != log.address {
+                        return true;
+                    }
+                    event.leg_fingerprints.push(fingerprint::of(
+                        &tx.hash,
+                        *index as u64,
+                        event_type,
+                    ));
+                    false
+                })
+            }
+
+            events_vec.push(event);
+        }
+
+        events_vec.extend(donation::donation_events(
+            &tx.hash,
+            &logs,
+            blk.number,
+            &annotations,
+        ));
+
+        events_vec.extend(flash_loan::flash_loan_events(
+            tx,
+            &logs,
+            blk.number,
+            &annotations,
+        ));
+        events_vec.extend(flash_mint::flash_mint_events(
+            &tx.hash,
+            &logs,
+            blk.number,
+            &annotations,
+        ));
+        events_vec.extend(bridge::messages(
+            &tx.hash,
+            &logs,
+            blk.number,
+            params.chain_id,
+            params.layerzero_eid,
+        ));
+        events_vec.extend(vesting::vesting_events(
+            &tx.hash,
+            &logs,
+            blk.number,
+            &annotations,
+        ));
+        events_vec.extend(permit_deadline::long_lived_permits(
+            tx,
+            &logs,
+            blk.number,
+            block_timestamp(blk),
+            params.permit_max_lifetime_seconds,
+            &annotations,
+        ));
+        if capabilities.has(capabilities::STATE_CHANGES) {
+            events_vec.extend(governance::silent_upgrades(tx, &logs, blk.number));
+        }
+        events_vec.extend(stolen::movements(
+            &tx.hash,
+            &logs,
+            blk.number,
+            &params.stolen_tokens,
+        ));
+        events_vec.extend(admin::mass_admin_change(
+            tx,
+            &logs,
+            blk.number,
+            &params.watch,
+        ));
+
+        if let Some(arb) = arb_loop::detect(&logs, &annotations) {
+            let pools: Vec<String> = arb
+                .pools
+                .iter()
+                .map(|p| format!("\"0x{}\"", hex::encode(p)))
+                .collect();
+            let token = format!("0x{}", hex::encode(&arb.token));
+            let mut event = CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                block_number: blk.number,
+                log_index: arb.log_index as u64,
+                event_signature: String::new(),
+                event_type: "ArbLoop".to_string(),
+                metadata: format!(
+                    "{{\"pools\":[{}],{},{},{}}}",
+                    pools.join(","),
+                    amount::json_fields(&annotations, "amount_in", arb.amount_in, &token),
+                    amount::json_fields(&annotations, "amount_out", arb.amount_out, &token),
+                    amount::json_fields(&annotations, "profit", arb.profit, &token)
+                ),
+                contract_address: token,
+                ..Default::default()
+            };
+            // Hops are reconstructed from V2 Swap logs only.
+            taxonomy::Tag::protocol("uniswap", "v2").apply(&mut event);
+            events_vec.push(event);
+        }
+
+        // Flash-minted and flash-borrowed capital both fund attacks the same
+        // way, so either marks the whole transaction.
+        let tx_events = events_vec.get_mut(tx_start..).unwrap_or_default();
+        if tx_events
+            .iter()
+            .any(|e| e.event_type == "FlashMint" || e.event_type == "FlashLoan")
+        {
+            for event in tx_events.iter_mut() {
+                event.flash_funded = true;
+            }
+        }
+
+        if private_flow::likely_private(tx, blk.header.as_ref()) {
+            for event in tx_events.iter_mut() {
+                event.likely_private = true;
+            }
+        }
+    }
+
+    events_vec.extend(dusting::dusting_events(
+        blk,
+        params.dust_max_amount,
+        params.dust_min_recipients,
+        &annotations,
+    ));
+
+    if capabilities.has(capabilities::BALANCE_CHANGES) {
+        events_vec.extend(withdrawal::withdrawal_events(
+            blk,
+            &params.watch,
+            &annotations,
+        ));
+    }
+
+    let mut events = emit(events_vec, blk, params).events;
+    gas_token::annotate(&mut events, blk, params.gas_refund_min_percent);
+    events
+}
+
+#[substreams::handlers::store]
+fn store_tokens_seen(params: String, blk: eth::Block, store: StoreSetIfNotExistsInt64) {
+    // The chain's token list decides which tokens are unlisted.
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    for (ordinal, token) in token_metadata::unlisted_tokens(&blk, &params.annotations()) {
+        store.set_if_not_exists(
+            ordinal,
+            token_metadata::token_key(&token),
+            &(blk.number as i64),
+        );
+    }
+}
+
+#[substreams::handlers::store]
+fn store_token_metadata(seen: Deltas<DeltaInt64>, store: StoreSetString) {
+    // Deltas only appear the first time a token is seen, so each token is
+    // resolved once.
+    let tokens: Vec<String> = seen
+        .deltas
+        .iter()
+        .filter_map(|d| d.key.strip_prefix("token:").map(str::to_string))
+        .collect();
+    if tokens.is_empty() {
+        return;
+    }
+    for (token, metadata) in token_metadata::resolve(&tokens) {
+        store.set(0, token_metadata::token_key(&token), &metadata);
+    }
+}
+
+#[substreams::handlers::map]
+fn map_enriched_events(
+    params: String,
+    mut events: CandidateEvents,
+    tokens: StoreGetString,
+    shapes: StoreGetInt64,
+) -> Result<CandidateEvents, Error> {
+    events.config_epoch = params::config_epoch(&params, &events.config_epoch);
+    let params = params::Params::parse(&params)?;
+    let annotations = params.annotations();
+    for event in events.events.iter_mut() {
+        let class = token_shape::class(&shapes, &event.contract_address);
+        if token_shape::route(event, class) {
+            event.severity.clear();
+            finish(event, &annotations);
+        }
+        // Amounts of tokens that aren't ERC20-like may be token ids.
+        if class.is_empty() || class == "erc20" {
+            token_metadata::enrich(event, &tokens, &annotations);
+        }
+    }
+    Ok(events)
+}
+
+#[substreams::handlers::store]
+fn store_token_shapes(blk: eth::Block, store: StoreAddInt64) {
+    for ((contract, shape), count) in token_shape::counts(&blk) {
+        store.add(0, token_shape::shape_key(&contract, shape), count);
+    }
+}
+
+#[substreams::handlers::map]
+fn map_block_summary(blk: eth::Block, events: CandidateEvents) -> Result<BlockSummary, Error> {
+    let capabilities = capabilities::detect(&blk);
+    let block_timestamp = block_timestamp(&blk);
+    let mut address_bloom = bloom::AddressBloom::default();
+    for event in events.events.iter() {
+        for address in [
+            &event.contract_address,
+            &event.from,
+            &event.to,
+            &event.owner,
+            &event.spender,
+        ] {
+            address_bloom.insert_hex(address);
+        }
+    }
+
+    Ok(BlockSummary {
+        block_number: blk.number,
+        block_hash: format!("0x{}", hex::encode(&blk.hash)),
+        event_count: events.events.len() as u64,
+        address_bloom: address_bloom.into_bytes(),
+        detail_level: capabilities.detail_level.to_string(),
+        capabilities: capabilities.flags,
+        config_epoch: events.config_epoch,
+        shadow_count: events
+            .events
+            .iter()
+            .filter(|e| !e.shadow_severity.is_empty())
+            .count() as u64,
+        block_timestamp,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_address_index(clock: Clock, events: CandidateEvents) -> Result<AddressIndex, Error> {
+    let mut by_address: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for event in events.events.iter() {
+        for address in [
+            &event.contract_address,
+            &event.from,
+            &event.to,
+            &event.owner,
+            &event.spender,
+        ] {
+            if address.is_empty() {
+                continue;
+            }
+            let fingerprints = by_address.entry(address).or_default();
+            if !fingerprints.contains(&event.fingerprint) {
+                fingerprints.push(event.fingerprint.clone());
+            }
+        }
+    }
+
+    Ok(AddressIndex {
+        block_number: clock.number,
+        entries: by_address
+            .into_iter()
+            .map(|(address, fingerprints)| AddressIndexEntry {
+                address: address.to_string(),
+                fingerprints,
+            })
+            .collect(),
+        config_epoch: events.config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_decoder_metrics(params: String, blk: eth::Block) -> Result<DecoderMetrics, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let annotations = params.annotations();
+    let mut meter = metrics::Meter::default();
+
+    for tx in blk.transaction_traces.iter() {
+        meter.measure("delegation", || {
+            delegation::delegation_events(tx, blk.number)
+        });
+        meter.measure("distribution", || {
+            distribution::distribution_events(
+                tx,
+                blk.number,
+                params.mass_distribution_detail,
+                &annotations,
+            )
+        });
+        meter.measure("slippage", || {
+            slippage::slippage_events(tx, blk.number, &annotations)
+        });
+        meter.measure("reentrancy", || {
+            reentrancy::reentrancy_events(tx, blk.number)
+        });
+        meter.measure("reentrant_calls", || {
+            reentrancy::reentrant_calls(tx, blk.number)
+        });
+        meter.measure("private_flow", || {
+            private_flow::likely_private(tx, blk.header.as_ref())
+        });
+
+        let (logs, _) = calls::tx_logs(tx);
+        meter.measure("classify", || {
+            logs.iter()
+                .filter(|log| log_type(&params, log).is_some())
+                .count()
+        });
+        meter.measure("donation", || {
+            donation::donation_events(&tx.hash, &logs, blk.number, &annotations)
+        });
+        meter.measure("arb_loop", || arb_loop::detect(&logs, &annotations));
+    }
+    meter.measure("dusting", || {
+        dusting::dusting_events(
+            &blk,
+            params.dust_max_amount,
+            params.dust_min_recipients,
+            &annotations,
+        )
+    });
+
+    Ok(DecoderMetrics {
+        block_number: blk.number,
+        metrics: meter.into_metrics(),
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_rule_triggers(
+    params: String,
+    clock: Clock,
+    events: CandidateEvents,
+    store: StoreSetIfNotExistsInt64,
+) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    // Once a window starts, the one before the previous has been reported.
+    if let Some(expired) = retention::expired(clock.number, params.rule_metrics_blocks) {
+        store.delete_prefix(0, &metrics::window_prefix(expired));
+    }
+    let window = retention::window(clock.number, params.rule_metrics_blocks);
+    for event in events.events.iter() {
+        for key in metrics::triggers(window, event) {
+            store.set_if_not_exists(0, key, &1);
+        }
+    }
+}
+
+#[substreams::handlers::store]
+fn store_rule_cardinality(
+    params: String,
+    clock: Clock,
+    triggers: Deltas<DeltaInt64>,
+    store: StoreAddInt64,
+) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    if let Some(expired) = retention::expired(clock.number, params.rule_metrics_blocks) {
+        store.delete_prefix(0, &metrics::window_prefix(expired));
+    }
+    // Every created trigger key is an address triggering the rule for the
+    // first time in the window.
+    for delta in triggers
+        .deltas
+        .iter()
+        .filter(|d| d.operation == store_delta::Operation::Create)
+    {
+        if let Some(key) = metrics::trigger_count_key(&delta.key) {
+            store.add(delta.ordinal, key, 1);
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_rule_cardinality(
+    params: String,
+    clock: Clock,
+    counts: StoreGetInt64,
+) -> Result<RuleCardinalities, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let window = clock.number / params.rule_metrics_blocks;
+    let reported = window.saturating_sub(1);
+    let rules = if clock.number.is_multiple_of(params.rule_metrics_blocks) && window >= 1 {
+        metrics::cardinality(reported, &counts)
+    } else {
+        Vec::new()
+    };
+
+    Ok(RuleCardinalities {
+        block_number: clock.number,
+        window_start: reported * params.rule_metrics_blocks,
+        rules,
+        config_epoch,
+    })
+}
+
+// Unknown topics listed per block in map_coverage.
+const COVERAGE_TOP_UNKNOWN: usize = 10;
+
+#[substreams::handlers::map]
+fn map_coverage(params: String, blk: eth::Block) -> Result<Coverage, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let mut seen = 0u64;
+    let mut decoded = 0u64;
+    let mut unknown: BTreeMap<String, u64> = BTreeMap::new();
+
+    for (_, log) in calls::block_logs(&blk) {
+        seen += 1;
+        if log_type(&params, &log).is_some() {
+            decoded += 1;
+        } else {
+            let topic0 = log.topics.first().map(Vec::as_slice).unwrap_or_default();
+            // Anonymous logs are counted under an empty topic.
+            let topic0 = match topic0 {
+                [] => String::new(),
+                topic0 => format!("0x{}", hex::encode(topic0)),
+            };
+            *unknown.entry(topic0).or_default() += 1;
+        }
+    }
+
+    let mut top_unknown_topics: Vec<TopicCount> = unknown
+        .into_iter()
+        .map(|(topic0, count)| TopicCount { topic0, count })
+        .collect();
+    // Stable sort keeps ties in topic order, so the output is deterministic.
+    top_unknown_topics.sort_by_key(|t| std::cmp::Reverse(t.count));
+    top_unknown_topics.truncate(COVERAGE_TOP_UNKNOWN);
+
+    Ok(Coverage {
+        block_number: blk.number,
+        logs_seen: seen,
+        logs_decoded: decoded,
+        logs_dropped: seen - decoded,
+        top_unknown_topics,
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_unknown_topics(params: String, blk: eth::Block, store: StoreAddInt64) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    for (_, log) in calls::block_logs(&blk) {
+        // Anonymous logs have no signature to write a decoder for.
+        let Some(topic0) = log.topics.first() else {
+            continue;
+        };
+        if log_type(&params, &log).is_some() {
+            continue;
+        }
+        let contract = format!("0x{}", hex::encode(&log.address));
+        if params.watch.contains(&contract) {
+            let topic0 = format!("0x{}", hex::encode(topic0));
+            store.add(
+                log.ordinal,
+                unknown_topics::count_key(&contract, &topic0),
+                1,
+            );
+        }
+    }
+}
+
+#[substreams::handlers::store]
+fn store_unknown_topic_index(counts: Deltas<DeltaInt64>, store: StoreAppend<String>) {
+    // A count leaving zero is the first sighting of that topic on the contract.
+    for delta in counts.deltas.iter().filter(|d| d.old_value == 0) {
+        if let Some((contract, topic0)) = unknown_topics::parse_count_key(&delta.key) {
+            store.append(
+                delta.ordinal,
+                unknown_topics::index_key(contract),
+                topic0.to_string(),
+            );
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_unknown_topic_digest(
+    params: String,
+    clock: Clock,
+    timestamps: Deltas<DeltaInt64>,
+    counts: StoreGetInt64,
+    index: StoreGetString,
+) -> Result<UnknownTopicDigest, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let period = |seconds: i64| seconds.max(0) as u64 / unknown_topics::DIGEST_PERIOD_SECONDS;
+
+    // Only the first block of each period carries a digest.
+    let contracts = match timestamps.deltas.last() {
+        Some(delta)
+            if delta.old_value > 0 && period(delta.old_value) != period(delta.new_value) =>
+        {
+            params
+                .watch
+                .iter()
+                .filter_map(|contract| unknown_topics::digest(contract, &counts, &index))
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+    let period_start = clock
+        .timestamp
+        .map(|t| period(t.seconds) * unknown_topics::DIGEST_PERIOD_SECONDS)
+        .unwrap_or_default();
+
+    Ok(UnknownTopicDigest {
+        block_number: clock.number,
+        period_start,
+        contracts,
+        config_epoch,
+    })
+}
+
+fn block_timestamp(blk: &eth::Block) -> u64 {
+    blk.header
+        .as_ref()
+        .and_then(|h| h.timestamp.as_ref())
+        .map(|t| t.seconds as u64)
+        .unwrap_or_default()
+}
+
+#[substreams::handlers::store]
+fn store_block_timestamp(clock: Clock, store: StoreSetInt64) {
+    let seconds = clock.timestamp.map(|t| t.seconds).unwrap_or_default();
+    store.set(0, "timestamp", &seconds);
+}
+
+#[substreams::handlers::store]
+fn store_permit2_allowances(blk: eth::Block, store: StoreSetString) {
+    let timestamp = block_timestamp(&blk);
+    for (_, log) in calls::block_logs(&blk) {
+        match permit2::change(&log, timestamp) {
+            Some(permit2::Change::Set {
+                key,
+                amount,
+                expiration,
+            }) => store.set(
+                log.ordinal,
+                &key,
+                &permit2::encode_allowance(&amount, expiration),
+            ),
+            Some(permit2::Change::Revoke { key }) => store.delete_prefix(log.ordinal as i64, &key),
+            None => {}
+        }
+    }
+}
+
+#[substreams::handlers::store]
+fn store_permit2_expiry_lengths(
+    blk: eth::Block,
+    timestamps: Deltas<DeltaInt64>,
+    store: StoreAddInt64,
+) {
+    for bucket in permit2_expired_buckets(&timestamps) {
+        store.delete_prefix(0, &permit2::expiry_key(bucket));
+    }
+    for (ordinal, key, _) in permit2::expiry_entries(&blk, block_timestamp(&blk)) {
+        store.add(ordinal, key, 1);
+    }
+}
+
+#[substreams::handlers::store]
+fn store_permit2_expiries(
+    blk: eth::Block,
+    timestamps: Deltas<DeltaInt64>,
+    lengths: StoreGetInt64,
+    store: StoreSetString,
+) {
+    for bucket in permit2_expired_buckets(&timestamps) {
+        store.delete_prefix(0, &permit2::expiry_key(bucket));
+    }
+    let entries = permit2::expiry_entries(&blk, block_timestamp(&blk));
+    for (ordinal, key, entry) in
+        retention::entry_keys(entries, |list| retention::length(&lengths, list))
+    {
+        store.set(ordinal, key, &entry);
+    }
+}
+
+/// Expiry buckets to drop as the block timestamp moves on.
+fn permit2_expired_buckets(timestamps: &Deltas<DeltaInt64>) -> std::ops::Range<u64> {
+    match timestamps.deltas.last() {
+        Some(delta) => {
+            permit2::expired_buckets(delta.old_value.max(0) as u64, delta.new_value.max(0) as u64)
+        }
+        None => 0..0,
+    }
+}
+
+#[substreams::handlers::map]
+fn map_stale_permit2_allowances(
+    params: String,
+    clock: Clock,
+    timestamps: Deltas<DeltaInt64>,
+    expiry_lengths: StoreGetInt64,
+    expiries: StoreGetString,
+    allowances: StoreGetString,
+) -> Result<StalePermit2Allowances, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let stale = match timestamps.deltas.last() {
+        Some(delta) if delta.old_value > 0 => permit2::stale_allowances(
+            delta.old_value as u64,
+            delta.new_value as u64,
+            &retention::Lists {
+                lengths: expiry_lengths,
+                entries: expiries,
+            },
+            &allowances,
+            &params.annotations(),
+        ),
+        _ => Vec::new(),
+    };
+
+    Ok(StalePermit2Allowances {
+        block_number: clock.number,
+        allowances: stale,
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_counterparties(params: String, blk: eth::Block, store: StoreSetString) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    let dust_max = U256::from(params.dust_max_amount);
+    if let Some(window) = retention::expired(blk.number, params.counterparty_window_blocks) {
+        store.delete_prefix(0, &poisoning::window_prefix(window));
+    }
+    let window = retention::window(blk.number, params.counterparty_window_blocks);
+
+    for transfer in poisoning::transfers(&blk) {
+        if transfer.amount <= dust_max || transfer.from == transfer.to {
+            continue;
+        }
+        for (owner, counterparty) in [
+            (&transfer.from, &transfer.to),
+            (&transfer.to, &transfer.from),
+        ] {
+            store.set(
+                transfer.ordinal,
+                poisoning::lookalike_key(window, owner, counterparty),
+                counterparty,
+            );
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_address_poisoning(
+    params: String,
+    blk: eth::Block,
+    counterparties: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let dust_max = U256::from(params.dust_max_amount);
+    let mut events = Vec::new();
+
+    // A dust transfer in either direction between a victim and an address
+    // sharing the visible characters of someone the victim really transacts
+    // with is the poisoning fingerprint.
+    for transfer in poisoning::transfers(&blk) {
+        if transfer.amount > dust_max {
+            continue;
+        }
+        for (victim, lookalike) in [
+            (&transfer.to, &transfer.from),
+            (&transfer.from, &transfer.to),
+        ] {
+            // The current window's counterparty is the latest when there is one.
+            let Some(impersonated) = retention::live(blk.number, params.counterparty_window_blocks)
+                .rev()
+                .find_map(|window| {
+                    counterparties.get_last(poisoning::lookalike_key(window, victim, lookalike))
+                })
+            else {
+                continue;
+            };
+            if &impersonated != lookalike {
+                events.push(poisoning::candidate(
+                    &transfer,
+                    blk.number,
+                    victim,
+                    lookalike,
+                    &impersonated,
+                    &params.annotations(),
+                ));
+                break;
+            }
+        }
+    }
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_vault_first_deposits(blk: eth::Block, store: StoreSetIfNotExistsString) {
+    for (_, log) in calls::block_logs(&blk) {
+        let Some(deposit) = vault::deposit(&log) else {
+            continue;
+        };
+        store.set_if_not_exists(
+            deposit.ordinal,
+            vault::first_deposit_key(&deposit.vault),
+            &vault::encode_record(&vault::Record {
+                block_number: blk.number,
+                account: deposit.owner,
+                assets: deposit.assets,
+                shares: deposit.shares,
+            }),
+        );
+    }
+}
+
+#[substreams::handlers::store]
+fn store_vault_donations(blk: eth::Block, first_deposits: StoreGetString, store: StoreSetString) {
+    for tx in blk.transaction_traces.iter() {
+        let (logs, _) = calls::tx_logs(tx);
+
+        // Only vaults that already have depositors can be inflated.
+        let mut vaults: Vec<&[u8]> = Vec::new();
+        for log in logs.iter() {
+            let Some(transfer) = abi::transfer(log) else {
+                continue;
+            };
+            let to = format!("0x{}", hex::encode(transfer.to));
+            if !vaults.contains(&transfer.to)
+                && first_deposits
+                    .get_last(vault::first_deposit_key(&to))
+                    .is_some()
+            {
+                vaults.push(transfer.to);
+            }
+        }
+
+        for vault_address in vaults {
+            let key = vault::donation_key(&format!("0x{}", hex::encode(vault_address)));
+            for (ordinal, donor, assets) in vault::donations(&logs, vault_address) {
+                store.set(
+                    ordinal,
+                    &key,
+                    &vault::encode_record(&vault::Record {
+                        block_number: blk.number,
+                        account: donor,
+                        assets,
+                        shares: U256::zero(),
+                    }),
+                );
+            }
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_first_depositor_exploits(
+    params: String,
+    blk: eth::Block,
+    first_deposits: StoreGetString,
+    donations: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let mut events = Vec::new();
+
+    for tx in blk.transaction_traces.iter() {
+        for deposit in calls::tx_logs(tx).0.iter().filter_map(vault::deposit) {
+            let Some(first) = first_deposits
+                .get_last(vault::first_deposit_key(&deposit.vault))
+                .and_then(|v| vault::decode_record(&v))
+            else {
+                continue;
+            };
+            let Some(donation) = donations
+                .get_last(vault::donation_key(&deposit.vault))
+                .and_then(|v| vault::decode_record(&v))
+            else {
+                continue;
+            };
+            if vault::exploit(
+                &deposit,
+                blk.number,
+                &first,
+                &donation,
+                params.first_depositor_window_blocks,
+            ) {
+                events.push(vault::candidate(
+                    &tx.hash, blk.number, &deposit, &first, &donation,
+                ));
+            }
+        }
+    }
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_chain_activity(params: String, blk: eth::Block, store: StoreSetInt64) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    let number = blk.number as i64;
+    if !blk.transaction_traces.is_empty() {
+        store.set(0, anomaly::NONEMPTY_KEY, &number);
+    }
+    for (_, log) in calls::block_logs(&blk) {
+        let contract = format!("0x{}", hex::encode(&log.address));
+        if params.watch.contains(&contract) {
+            store.set(log.ordinal, anomaly::active_key(&contract), &number);
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_chain_anomalies(
+    params: String,
+    blk: eth::Block,
+    timestamps: Deltas<DeltaInt64>,
+    activity: StoreGetInt64,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let gap_seconds = match timestamps.deltas.last() {
+        Some(delta) if delta.old_value > 0 => {
+            Some(delta.new_value.saturating_sub(delta.old_value).max(0) as u64)
+        }
+        _ => None,
+    };
+
+    let events = anomaly::anomalies(blk.number, gap_seconds, &activity, &params);
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_contract_deployers(blk: eth::Block, store: StoreSetIfNotExistsString) {
+    for tx in blk.transaction_traces.iter() {
+        let Some(sender) = sender::sender(tx) else {
+            continue;
+        };
+        let sender = format!("0x{}", hex::encode(sender));
+        for (ordinal, contract) in deployer::created(tx) {
+            store.set_if_not_exists(ordinal, deployer::deployer_key(&contract), &sender);
+        }
+    }
+}
+
+#[substreams::handlers::store]
+fn store_eoa_activity(blk: eth::Block, store: StoreSetInt64) {
+    for tx in blk.transaction_traces.iter() {
+        if let Some(sender) = sender::sender(tx) {
+            let key = deployer::activity_key(&format!("0x{}", hex::encode(sender)));
+            store.set(tx.begin_ordinal, key, &(blk.number as i64));
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_deployer_key_compromise(
+    params: String,
+    blk: eth::Block,
+    deployers: StoreGetString,
+    activity: Deltas<DeltaInt64>,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let mut events = Vec::new();
+
+    for tx in blk.transaction_traces.iter() {
+        let Some(sender) = sender::sender(tx) else {
+            continue;
+        };
+        let sender = format!("0x{}", hex::encode(sender));
+
+        let mut changes = admin::changes(&calls::tx_logs(tx).0);
+        changes.retain(|c| {
+            deployers
+                .get_last(deployer::deployer_key(&c.contract))
+                .as_deref()
+                == Some(&sender)
+        });
+        if changes.is_empty() {
+            continue;
+        }
+
+        // The first delta of the block still holds the sender's activity
+        // before this block; 0 means it was never seen since the start block.
+        let key = deployer::activity_key(&sender);
+        let Some(last_active) = activity
+            .deltas
+            .iter()
+            .find(|d| d.key == key)
+            .map(|d| d.old_value.max(0) as u64)
+            .filter(|last| *last > 0)
+        else {
+            continue;
+        };
+        let dormant_blocks = blk.number.saturating_sub(last_active);
+        if dormant_blocks >= params.deployer_dormancy_blocks {
+            events.push(deployer::candidate(
+                tx,
+                blk.number,
+                &sender,
+                &changes,
+                dormant_blocks,
+            ));
+        }
+    }
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_holder_activity(blk: eth::Block, store: StoreSetInt64) {
+    for m in dormant::moves(&blk) {
+        store.set(
+            m.ordinal,
+            dormant::activity_key(&m.from),
+            &(blk.number as i64),
+        );
+    }
+}
+
+#[substreams::handlers::map]
+fn map_dormant_addresses(
+    params: String,
+    blk: eth::Block,
+    activity: Deltas<DeltaInt64>,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let mut events = Vec::new();
+    let mut flagged: Vec<String> = Vec::new();
 
-        if let Some(receipt) = &tx.receipt {
-            for log in receipt.logs.iter() {
-                let topic0 = log.topics.get(0).map(|t| format!("0x{}", hex::encode(t))).unwrap_or_default();
-                let topics_vec: Vec<String> = log
-                    .topics
-                    .iter()
-                    .map(|t| format!("0x{}", hex::encode(t)))
-                    .collect();
-                let data_hex = format!("0x{}", hex::encode(&log.data));
-
-                let block_number = blk.number;
-                let log_index = log.index as u64;
-                let contract_addr = format!("0x{}", hex::encode(&log.address));
-
-                if topic0 == TRANSFER_TOPIC {
-                    events_vec.push(CandidateEvent {
-                        transaction_hash: tx_hash.clone(),
-                        block_number,
-                        log_index,
-                        contract_address: contract_addr.clone(),
-                        event_signature: TRANSFER_TOPIC.to_string(),
-                        event_type: "Transfer".to_string(),
-                        metadata: format!("{{\"topics\":{:?},\"data\":\"{}\"}}",topics_vec, data_hex),
-                    });
-                } else if topic0 == APPROVAL_TOPIC {
-                    events_vec.push(CandidateEvent {
-                        transaction_hash: tx_hash.clone(),
-                        block_number,
-                        log_index,
-                        contract_address: contract_addr.clone(),
-                        event_signature: APPROVAL_TOPIC.to_string(),
-                        event_type: "Approval".to_string(),
-                        metadata: format!(
-                            "{{\"topics\":{:?},\"data\":\"{}\"}}",
-                            topics_vec, data_hex
-                        ),
-                    });
-                } else if topic0 == SWAP_TOPIC {
-                    events_vec.push(CandidateEvent {
-                        transaction_hash: tx_hash.clone(),
-                        block_number,
-                        log_index,
-                        contract_address: contract_addr.clone(),
-                        event_signature: SWAP_TOPIC.to_string(),
-                        event_type: "Swap".to_string(),
-                        metadata: format!(
-                            "{{\"topics\":{:?},\"data\":\"{}\"}}",
-                            topics_vec, data_hex
-                        ),
-                    });
-                } else if topic0 == PERMIT_TOPIC {
-                    events_vec.push(CandidateEvent {
-                        transaction_hash: tx_hash.clone(),
-                        block_number,
-                        log_index,
-                        contract_address: contract_addr.clone(),
-                        event_signature: PERMIT_TOPIC.to_string(),
-                        event_type: "Permit".to_string(),
-                        metadata: format!(
-                            "{{\"topics\":{:?},\"data\":\"{}\"}}",
-                            topics_vec, data_hex
-                        ),
-                    });
-
-                
-                } else if topic0 == FLASHLOAN_TOPIC {
-                    events_vec.push(CandidateEvent {
-                        transaction_hash: tx_hash.clone(),
-                        block_number,
-                        log_index,
-                        contract_address: contract_addr.clone(),
-                        event_signature: FLASHLOAN_TOPIC.to_string(),
-                        event_type: "FlashLoan".to_string(),
-                        metadata: format!(
-                            "{{\"topics\":{:?},\"data\":\"{}\"}}",
-                            topics_vec, data_hex
-                        ),
-                    });
+    for m in dormant::moves(&blk) {
+        if flagged.contains(&m.from)
+            || !dormant::is_large(&m, params.whale_min_units, &params.annotations())
+        {
+            continue;
+        }
+        // The first delta of the block still holds the activity before it.
+        let key = dormant::activity_key(&m.from);
+        let Some(last_active) = activity
+            .deltas
+            .iter()
+            .find(|d| d.key == key)
+            .map(|d| d.old_value.max(0) as u64)
+            .filter(|last| *last > 0)
+        else {
+            continue;
+        };
+        let dormant_blocks = blk.number.saturating_sub(last_active);
+        if dormant_blocks >= params.dormant_address_blocks {
+            events.push(dormant::candidate(
+                &m,
+                blk.number,
+                dormant_blocks,
+                &params.annotations(),
+            ));
+            flagged.push(m.from.clone());
+        }
+    }
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_contract_pairs(params: String, blk: eth::Block, store: StoreSetIfNotExistsInt64) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    for tx in blk.transaction_traces.iter() {
+        for interaction in novelty::interactions(tx, &params.watch) {
+            store.set_if_not_exists(
+                interaction.ordinal,
+                novelty::pair_key(&interaction.caller, &interaction.callee),
+                &(blk.number as i64),
+            );
+        }
+    }
+}
 
+#[substreams::handlers::map]
+fn map_novel_interactions(
+    params: String,
+    blk: eth::Block,
+    pairs: Deltas<DeltaInt64>,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let mut events = Vec::new();
+
+    // The store only changes when a pair is seen for the first time.
+    if !pairs.deltas.is_empty() {
+        for tx in blk.transaction_traces.iter() {
+            for interaction in novelty::interactions(tx, &params.watch) {
+                let key = novelty::pair_key(&interaction.caller, &interaction.callee);
+                if pairs.deltas.iter().any(|d| d.key == key)
+                    && novelty::is_large(
+                        &interaction,
+                        params.whale_min_units,
+                        &params.annotations(),
+                    )
+                {
+                    events.push(novelty::candidate(
+                        &interaction,
+                        blk.number,
+                        &params.annotations(),
+                    ));
                 }
             }
+        }
+    }
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::map]
+fn map_failed_probes(params: String, blk: eth::Block) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let events = failed_probe::failed_probes(&blk, &params.watch, &params.topics);
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_liquidity_removals(
+    params: String,
+    blk: eth::Block,
+    deployers: StoreGetString,
+    store: StoreSetString,
+) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    if let Some(window) = retention::expired(blk.number, params.rug_pull_window_blocks) {
+        store.delete_prefix(0, &rug_pull::window_prefix(window));
+    }
+    let window = retention::window(blk.number, params.rug_pull_window_blocks);
+    for removal in rug_pull::removals(&blk) {
+        if let Some(deployer) = deployers.get_last(deployer::deployer_key(&removal.pool)) {
+            let key = rug_pull::removal_key(window, &deployer);
+            store.set(removal.ordinal, key, &rug_pull::encode(&removal));
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_rug_pulls(
+    params: String,
+    blk: eth::Block,
+    removals: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let events = rug_pull::candidates(
+        &blk,
+        |sender| {
+            // The current window's removal is the latest when there is one.
+            let removal = retention::live(blk.number, params.rug_pull_window_blocks)
+                .rev()
+                .find_map(|window| removals.get_last(rug_pull::removal_key(window, sender)))?;
+            rug_pull::decode(&removal)
+        },
+        params.rug_pull_window_blocks,
+        params.rug_pull_min_share,
+        &params.annotations(),
+    );
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_approvals(blk: eth::Block, store: StoreAppend<String>) {
+    for grant in approvals::grants(&blk)
+        .into_iter()
+        .chain(nft::operator_grants(&blk))
+    {
+        store.append(
+            grant.ordinal,
+            approvals::approval_key(&grant.owner, &grant.token, &grant.spender),
+            approvals::encode_grant(blk.number, grant.ordinal),
+        );
+    }
+}
+
+#[substreams::handlers::store]
+fn store_contract_creations(blk: eth::Block, store: StoreSetIfNotExistsInt64) {
+    for tx in blk.transaction_traces.iter() {
+        for (ordinal, contract) in deployer::created(tx) {
+            store.set_if_not_exists(
+                ordinal,
+                risky_approval::creation_key(&contract),
+                &(blk.number as i64),
+            );
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_risky_approvals(
+    params: String,
+    blk: eth::Block,
+    activity: StoreGetInt64,
+    creations: StoreGetInt64,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let mut events = Vec::new();
+
+    for tx in blk.transaction_traces.iter() {
+        let (logs, _) = calls::tx_logs(tx);
+        events.extend(risky_approval::risky_approvals(
+            tx, &logs, blk.number, &params, &activity, &creations,
+        ));
+    }
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::map]
+fn map_sandwiches(params: String, blk: eth::Block) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let events = sandwich::sandwiches(
+        &blk,
+        params.sandwich_min_victim_units,
+        &params.annotations(),
+    );
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::map]
+fn map_approval_drains(
+    params: String,
+    blk: eth::Block,
+    approvals: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let events = approvals::drains(
+        &blk,
+        &approvals,
+        params.approval_drain_window_blocks,
+        &params.annotations(),
+    );
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::map]
+fn map_nft_drains(
+    params: String,
+    blk: eth::Block,
+    approvals: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let events = nft::drains(&blk, &approvals, params.nft_drain_window_blocks);
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_token_bound_accounts(blk: eth::Block, store: StoreSetString) {
+    for (_, log) in calls::block_logs(&blk) {
+        if let Some(account) = token_bound::created(&log) {
+            store.set(
+                log.ordinal,
+                token_bound::account_key(&account.account),
+                &token_bound::encode(&account),
+            );
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_token_bound_activity(
+    params: String,
+    blk: eth::Block,
+    events: CandidateEvents,
+    accounts: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, &events.config_epoch);
+    let params = params::Params::parse(&params)?;
+
+    // Events from map_candidate_events have been through emit already.
+    let mut out = emit(token_bound::creations(&blk), &blk, &params).events;
+    for mut event in events.events {
+        if let Some(account) = token_bound::involved(&event, &accounts) {
+            event.token_bound = Some(account);
+            out.push(event);
+        }
+    }
+    ordering::order(&mut out, &blk);
+    Ok(CandidateEvents {
+        events: out,
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_nft_balances(params: String, blk: eth::Block, store: StoreAddBigInt) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    for (ordinal, key, change) in holders::balance_changes(&blk, &params.watch) {
+        store.add(ordinal, key, change);
+    }
+}
+
+#[substreams::handlers::store]
+fn store_nft_holders(balances: Deltas<DeltaBigInt>, store: StoreAddBigInt) {
+    for delta in balances.deltas.iter() {
+        for (key, change) in holders::total_changes(delta) {
+            store.add(delta.ordinal, key, change);
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_nft_holder_snapshots(
+    params: String,
+    clock: Clock,
+    totals: StoreGetBigInt,
+) -> Result<NftHolderSnapshots, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let collections = if clock.number.is_multiple_of(params.nft_snapshot_blocks) {
+        params
+            .watch
+            .iter()
+            .map(|collection| holders::snapshot(collection, &totals))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(NftHolderSnapshots {
+        block_number: clock.number,
+        collections,
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_enum_dictionary(params: String, clock: Clock) -> Result<EnumDictionary, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    if !clock.number.is_multiple_of(params.dictionary_blocks) {
+        return Ok(EnumDictionary {
+            block_number: clock.number,
+            config_epoch,
+            ..Default::default()
+        });
+    }
+
+    Ok(EnumDictionary {
+        block_number: clock.number,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        sets: dictionary::sets(),
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_config_descriptor(params: String, clock: Clock) -> Result<ConfigDescriptor, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    if !clock.number.is_multiple_of(params.dictionary_blocks) {
+        return Ok(ConfigDescriptor {
+            block_number: clock.number,
+            config_epoch,
+            ..Default::default()
+        });
+    }
+
+    Ok(ConfigDescriptor {
+        block_number: clock.number,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        params: params::descriptors(),
+        rules: dictionary::rules(severity, retention_class),
+        enums: dictionary::sets(),
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_approval_velocity(params: String, blk: eth::Block, store: StoreAddInt64) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    // Once a window starts, the one before the previous is no longer read.
+    if let Some(expired) = retention::expired(blk.number, params.approval_baseline_blocks) {
+        store.delete_prefix(0, &velocity::window_prefix(expired));
+    }
+    let window = retention::window(blk.number, params.approval_baseline_blocks);
+    let grants = approvals::grants(&blk);
+    for (token, count) in velocity::counts(&grants) {
+        store.add(0, velocity::count_key(window, token), count);
+        store.add(0, velocity::square_key(window, token), count * count);
+    }
+}
+
+#[substreams::handlers::map]
+fn map_approval_spikes(
+    params: String,
+    blk: eth::Block,
+    stats: StoreGetInt64,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+    let grants = approvals::grants(&blk);
+    let events = velocity::spikes(
+        &velocity::counts(&grants),
+        &stats,
+        blk.number,
+        params.approval_baseline_blocks,
+        params.approval_spike_z,
+        params.approval_spike_min,
+    );
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(events, &blk, &params)
+    })
+}
+
+#[substreams::handlers::store]
+fn store_contract_activity(
+    params: String,
+    clock: Clock,
+    events: CandidateEvents,
+    store: StoreAddInt64,
+) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    for prefix in activity::expired_prefixes(clock.number, params.activity_window_blocks) {
+        store.delete_prefix(0, &prefix);
+    }
+    for ((kind, contract), count) in activity::counts(&events.events, &params.watch) {
+        for key in activity::keys(clock.number, params.activity_window_blocks, kind, contract) {
+            store.add(0, key, count);
+        }
+    }
+}
+
+#[substreams::handlers::map]
+fn map_activity_spikes(
+    params: String,
+    clock: Clock,
+    activity: Deltas<DeltaInt64>,
+) -> Result<ActivitySpikes, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+
+    Ok(ActivitySpikes {
+        block_number: clock.number,
+        spikes: activity::spikes(
+            &activity.deltas,
+            clock.number,
+            params.activity_window_blocks,
+            params.activity_spike_multiple,
+            params.activity_spike_min,
+        ),
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_contract_gas(blk: eth::Block, store: StoreAddInt64) {
+    for (contract, (gas, count)) in gas_anomaly::totals(&blk) {
+        store.add(0, gas_anomaly::gas_key(&contract), gas);
+        store.add(0, gas_anomaly::count_key(&contract), count);
+    }
+}
+
+#[substreams::handlers::map]
+fn map_gas_anomalies(
+    params: String,
+    blk: eth::Block,
+    gas: Deltas<DeltaInt64>,
+) -> Result<GasAnomalies, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+
+    Ok(GasAnomalies {
+        block_number: blk.number,
+        anomalies: gas_anomaly::anomalies(
+            &blk,
+            &gas.deltas,
+            params.gas_anomaly_multiple,
+            params.gas_anomaly_min_samples,
+        ),
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_fresh_contract_interactions(
+    params: String,
+    blk: eth::Block,
+) -> Result<FreshContractInteractions, Error> {
+    let config_epoch = params::config_epoch(&params, "");
+    let params = params::Params::parse(&params)?;
+
+    Ok(FreshContractInteractions {
+        block_number: blk.number,
+        interactions: fresh_contract::interactions(&blk, &params.watch),
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_actor_setup_lengths(params: String, blk: eth::Block, store: StoreAddInt64) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    if let Some(window) = retention::expired(blk.number, params.setup_window_blocks) {
+        store.delete_prefix(0, &setup::window_prefix(window));
+    }
+    let window = retention::window(blk.number, params.setup_window_blocks);
+    for (ordinal, key, _) in setup::entries(&blk, window, &params.annotations()) {
+        store.add(ordinal, key, 1);
+    }
+}
+
+#[substreams::handlers::store]
+fn store_actor_setups(
+    params: String,
+    blk: eth::Block,
+    lengths: StoreGetInt64,
+    store: StoreSetString,
+) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    if let Some(window) = retention::expired(blk.number, params.setup_window_blocks) {
+        store.delete_prefix(0, &setup::window_prefix(window));
+    }
+    let window = retention::window(blk.number, params.setup_window_blocks);
+    let entries = setup::entries(&blk, window, &params.annotations());
+    for (ordinal, key, entry) in
+        retention::entry_keys(entries, |list| retention::length(&lengths, list))
+    {
+        store.set(ordinal, key, &entry);
+    }
+}
+
+#[substreams::handlers::map]
+fn map_attack_chains(
+    params: String,
+    blk: eth::Block,
+    events: CandidateEvents,
+    setup_lengths: StoreGetInt64,
+    setups: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, &events.config_epoch);
+    let params = params::Params::parse(&params)?;
+    let setups = retention::Lists {
+        lengths: setup_lengths,
+        entries: setups,
+    };
+    let senders: BTreeMap<String, String> = blk
+        .transaction_traces
+        .iter()
+        .filter_map(|tx| {
+            let sender = sender::sender(tx)?;
+            Some((
+                format!("0x{}", hex::encode(&tx.hash)),
+                format!("0x{}", hex::encode(sender)),
+            ))
+        })
+        .collect();
+
+    let incidents = events
+        .events
+        .into_iter()
+        .filter(|e| e.severity == "high" || e.severity == "critical")
+        .filter_map(|mut event| {
+            let actor = senders.get(&event.transaction_hash)?;
+            event.setup_fingerprints = setup::recent_setups(
+                &setups,
+                actor,
+                &event.transaction_hash,
+                blk.number,
+                params.setup_window_blocks,
+                &params.setup_ages(),
+            );
+            (!event.setup_fingerprints.is_empty()).then_some(event)
+        })
+        .collect();
+
+    Ok(CandidateEvents {
+        events: incidents,
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::store]
+fn store_actor_probe_lengths(params: String, blk: eth::Block, store: StoreAddInt64) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    if let Some(window) = retention::expired(blk.number, params.setup_window_blocks) {
+        store.delete_prefix(0, &rehearsal::window_prefix(window));
+    }
+    let window = retention::window(blk.number, params.setup_window_blocks);
+    for (ordinal, key, _) in rehearsal::entries(&blk, window, &params.annotations()) {
+        store.add(ordinal, key, 1);
+    }
+}
+
+#[substreams::handlers::store]
+fn store_actor_probes(
+    params: String,
+    blk: eth::Block,
+    lengths: StoreGetInt64,
+    store: StoreSetString,
+) {
+    let Ok(params) = params::Params::parse(&params) else {
+        return;
+    };
+    if let Some(window) = retention::expired(blk.number, params.setup_window_blocks) {
+        store.delete_prefix(0, &rehearsal::window_prefix(window));
+    }
+    let window = retention::window(blk.number, params.setup_window_blocks);
+    let entries = rehearsal::entries(&blk, window, &params.annotations());
+    for (ordinal, key, entry) in
+        retention::entry_keys(entries, |list| retention::length(&lengths, list))
+    {
+        store.set(ordinal, key, &entry);
     }
 }
-    
-    Ok(CandidateEvents { events: events_vec })
+
+#[substreams::handlers::map]
+fn map_exploit_rehearsals(
+    params: String,
+    blk: eth::Block,
+    events: CandidateEvents,
+    probe_lengths: StoreGetInt64,
+    probes: StoreGetString,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, &events.config_epoch);
+    let params = params::Params::parse(&params)?;
+    let probes = retention::Lists {
+        lengths: probe_lengths,
+        entries: probes,
+    };
+    let mut out = Vec::new();
+
+    for tx in blk.transaction_traces.iter() {
+        let tx_hash = format!("0x{}", hex::encode(&tx.hash));
+        // One candidate per flagged transaction, on its first flagged event.
+        let Some(flagged) = events.events.iter().find(|e| {
+            e.transaction_hash == tx_hash && (e.severity == "high" || e.severity == "critical")
+        }) else {
+            continue;
+        };
+        let Some(sender) = sender::sender(tx) else {
+            continue;
+        };
+        let actor = format!("0x{}", hex::encode(sender));
+
+        let rehearsals = rehearsal::rehearsals(
+            &probes,
+            &actor,
+            &tx_hash,
+            &rehearsal::call_targets(tx),
+            blk.number,
+            params.setup_window_blocks,
+            &params.setup_ages(),
+        );
+        if !rehearsals.is_empty() {
+            out.push(rehearsal::candidate(flagged, &actor, &rehearsals));
+        }
+    }
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(out, &blk, &params)
+    })
+}
+
+#[substreams::handlers::map]
+fn map_action_recommendations(
+    params: String,
+    blk: eth::Block,
+    events: CandidateEvents,
+    deployer_events: CandidateEvents,
+) -> Result<ActionRecommendations, Error> {
+    let upstream = format!("{}{}", events.config_epoch, deployer_events.config_epoch);
+    let config_epoch = params::config_epoch(&params, &upstream);
+    let params = params::Params::parse(&params)?;
+    let all: Vec<&CandidateEvent> = events
+        .events
+        .iter()
+        .chain(deployer_events.events.iter())
+        .collect();
+    let recommendations = all
+        .iter()
+        .filter(|e| e.severity == "critical")
+        .flat_map(|event| {
+            let contributing: Vec<&CandidateEvent> = all
+                .iter()
+                .filter(|e| e.transaction_hash == event.transaction_hash)
+                .copied()
+                .collect();
+            pause::recommendations(event, &contributing, &params.pause_registry)
+        })
+        .collect();
+
+    Ok(ActionRecommendations {
+        block_number: blk.number,
+        recommendations,
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_evidence_bundles(
+    blk: eth::Block,
+    events: CandidateEvents,
+) -> Result<EvidenceBundles, Error> {
+    let bundles = events
+        .events
+        .iter()
+        .filter(|e| e.severity == "high" || e.severity == "critical")
+        .filter_map(|incident| {
+            let tx = blk
+                .transaction_traces
+                .iter()
+                .find(|tx| format!("0x{}", hex::encode(&tx.hash)) == incident.transaction_hash)?;
+            Some(evidence::bundle(incident, tx, &events.events))
+        })
+        .collect();
+
+    Ok(EvidenceBundles {
+        block_number: blk.number,
+        bundles,
+        config_epoch: events.config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_suspicious_transactions(
+    params: String,
+    blk: eth::Block,
+    events: CandidateEvents,
+) -> Result<SuspiciousTransactions, Error> {
+    let config_epoch = params::config_epoch(&params, &events.config_epoch);
+    let params = params::Params::parse(&params)?;
+    Ok(SuspiciousTransactions {
+        block_number: blk.number,
+        transactions: suspicion::suspicious_transactions(&events.events, &params),
+        config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_verifiable_alerts(
+    blk: eth::Block,
+    events: CandidateEvents,
+) -> Result<VerifiableAlerts, Error> {
+    let header = verifiable::header(&blk);
+    let alerts = events
+        .events
+        .iter()
+        .filter(|e| e.severity == "high" || e.severity == "critical")
+        .map(|alert| verifiable::package(alert, &header, &blk))
+        .collect();
+
+    Ok(VerifiableAlerts {
+        block_number: blk.number,
+        alerts,
+        config_epoch: events.config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_call_tree_summaries(
+    blk: eth::Block,
+    events: CandidateEvents,
+) -> Result<CallTreeSummaries, Error> {
+    // Event types flagging each transaction, in the order it was first flagged.
+    let mut flagged: Vec<(&str, Vec<String>)> = Vec::new();
+    for event in events
+        .events
+        .iter()
+        .filter(|e| e.severity == "high" || e.severity == "critical")
+        .filter(|e| !e.transaction_hash.is_empty())
+    {
+        let hash = event.transaction_hash.as_str();
+        if !flagged.iter().any(|(h, _)| *h == hash) {
+            flagged.push((hash, Vec::new()));
+        }
+        let Some((_, types)) = flagged.iter_mut().find(|(h, _)| *h == hash) else {
+            continue;
+        };
+        if !types.contains(&event.event_type) {
+            types.push(event.event_type.clone());
+        }
+    }
+
+    let summaries = flagged
+        .into_iter()
+        .filter_map(|(hash, flagged_by)| {
+            let tx = blk
+                .transaction_traces
+                .iter()
+                .find(|tx| format!("0x{}", hex::encode(&tx.hash)) == hash)?;
+            Some(call_tree::summary(tx, flagged_by))
+        })
+        .collect();
+
+    Ok(CallTreeSummaries {
+        block_number: blk.number,
+        summaries,
+        config_epoch: events.config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn graph_out(events: CandidateEvents) -> Result<EntityChanges, Error> {
+    Ok(graph::entity_changes(&events))
+}
+
+#[substreams::handlers::map]
+fn db_out(events: CandidateEvents) -> Result<DatabaseChanges, Error> {
+    Ok(database::database_changes(&events))
+}
+
+#[substreams::handlers::map]
+fn kv_out(events: CandidateEvents) -> Result<KvOperations, Error> {
+    Ok(kv::kv_operations(&events))
+}
+
+#[substreams::handlers::map]
+fn map_output_metrics(
+    events: CandidateEvents,
+    enriched: CandidateEvents,
+    summary: BlockSummary,
+    entities: EntityChanges,
+    database: DatabaseChanges,
+    kv: KvOperations,
+) -> Result<OutputMetrics, Error> {
+    let outputs = vec![
+        metrics::output(
+            "map_candidate_events",
+            "pulseproof.CandidateEvents",
+            &events,
+            events.events.len(),
+        ),
+        metrics::output(
+            "map_enriched_events",
+            "pulseproof.CandidateEvents",
+            &enriched,
+            enriched.events.len(),
+        ),
+        metrics::output("map_block_summary", "pulseproof.BlockSummary", &summary, 1),
+        metrics::output(
+            "graph_out",
+            "sf.substreams.sink.entity.v1.EntityChanges",
+            &entities,
+            entities.entity_changes.len(),
+        ),
+        metrics::output(
+            "db_out",
+            "sf.substreams.sink.database.v1.DatabaseChanges",
+            &database,
+            database.table_changes.len(),
+        ),
+        metrics::output(
+            "kv_out",
+            "sf.substreams.sink.kv.v1.KVOperations",
+            &kv,
+            kv.operations.len(),
+        ),
+    ];
+    Ok(OutputMetrics {
+        block_number: summary.block_number,
+        total_bytes: outputs.iter().map(|output| output.bytes).sum(),
+        outputs,
+        config_epoch: events.config_epoch,
+    })
+}
+
+#[substreams::handlers::map]
+fn map_event_commitment(
+    blk: eth::Block,
+    events: CandidateEvents,
+) -> Result<BlockCommitment, Error> {
+    Ok(BlockCommitment {
+        config_epoch: events.config_epoch.clone(),
+        ..commitment::block_commitment(&events.events, blk.number, &blk.hash)
+    })
+}
+
+#[substreams::handlers::map]
+fn map_operator_divergence(
+    params: String,
+    blk: eth::Block,
+    events: CandidateEvents,
+) -> Result<CandidateEvents, Error> {
+    let config_epoch = params::config_epoch(&params, &events.config_epoch);
+    let params = params::Params::parse(&params)?;
+    let local_root = commitment::block_root(&events.events);
+
+    let divergences: Vec<CandidateEvent> = params
+        .peer_roots
+        .iter()
+        .filter(|(block, root)| *block == blk.number && *root != local_root)
+        .map(|(_, peer_root)| CandidateEvent {
+            block_number: blk.number,
+            event_type: "DivergenceDetected".to_string(),
+            metadata: format!(
+                "{{\"local_root\":\"{}\",\"peer_root\":\"{}\",\"local_events\":{}}}",
+                local_root,
+                peer_root,
+                events.events.len()
+            ),
+            ..Default::default()
+        })
+        .collect();
+
+    Ok(CandidateEvents {
+        config_epoch,
+        ..emit(divergences, &blk, &params)
+    })
 }
@@ -0,0 +1,165 @@
+//! Per-decoder cost accounting for `map_decoder_metrics`. Only the `metrics`
+//! feature installs the counting allocator and records anything; without it
+//! `Meter::measure` just runs the decoder. Wall-clock time is unavailable
+//! inside the WASM runtime, so `nanos` is only filled in native builds.
+//!
+//! Also the keys and readout of the per-rule trigger cardinality behind
+//! `map_rule_cardinality`, and the output sizes `map_output_metrics` reports.
+
+use prost::Message;
+use substreams::store::{StoreGet, StoreGetInt64};
+
+use crate::dictionary;
+use crate::pb::pulseproof::{CandidateEvent, DecoderMetric, OutputMetric, RuleCardinality};
+
+#[cfg(feature = "metrics")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    pub static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+    pub struct Counting;
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Counting = Counting;
+
+    pub fn snapshot() -> (u64, u64) {
+        (
+            ALLOCATIONS.load(Ordering::Relaxed),
+            ALLOCATED_BYTES.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Accumulates cost per decoder name across every call in a block.
+#[derive(Default)]
+pub struct Meter {
+    metrics: Vec<DecoderMetric>,
+}
+
+impl Meter {
+    #[cfg(feature = "metrics")]
+    pub fn measure<T>(&mut self, decoder: &str, f: impl FnOnce() -> T) -> T {
+        #[cfg(not(target_arch = "wasm32"))]
+        let started = std::time::Instant::now();
+        let (allocations, bytes) = counting::snapshot();
+
+        let out = f();
+
+        let (allocations_after, bytes_after) = counting::snapshot();
+        #[cfg(not(target_arch = "wasm32"))]
+        let nanos = started.elapsed().as_nanos() as u64;
+        #[cfg(target_arch = "wasm32")]
+        let nanos = 0;
+
+        if !self.metrics.iter().any(|m| m.decoder == decoder) {
+            self.metrics.push(DecoderMetric {
+                decoder: decoder.to_string(),
+                ..Default::default()
+            });
+        }
+        if let Some(metric) = self.metrics.iter_mut().find(|m| m.decoder == decoder) {
+            metric.calls += 1;
+            metric.nanos += nanos;
+            metric.allocations += allocations_after - allocations;
+            metric.allocated_bytes += bytes_after - bytes;
+        }
+        out
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    pub fn measure<T>(&mut self, _decoder: &str, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+
+    pub fn into_metrics(self) -> Vec<DecoderMetric> {
+        self.metrics
+    }
+}
+
+/// Prefix of every key of one cardinality window, so a whole window can be
+/// dropped once it has been reported.
+pub fn window_prefix(window: u64) -> String {
+    format!("rules:{}:", window)
+}
+
+/// Key marking that `address` triggered `rule` in `window`, `side` being
+/// `contract` or `actor`.
+pub fn trigger_key(window: u64, rule: &str, side: &str, address: &str) -> String {
+    format!("{}{}:{}:{}", window_prefix(window), rule, side, address)
+}
+
+/// Key counting the distinct `side` addresses that triggered `rule` in
+/// `window`.
+pub fn count_key(window: u64, rule: &str, side: &str) -> String {
+    format!("{}{}:{}", window_prefix(window), rule, side)
+}
+
+/// Trigger keys of an event: its contract and the sender of its transaction,
+/// when set.
+pub fn triggers(window: u64, event: &CandidateEvent) -> Vec<String> {
+    [
+        ("contract", &event.contract_address),
+        ("actor", &event.tx_from),
+    ]
+    .into_iter()
+    .filter(|(_, address)| !address.is_empty())
+    .map(|(side, address)| trigger_key(window, &event.event_type, side, address))
+    .collect()
+}
+
+/// The count key a trigger key adds to.
+pub fn trigger_count_key(trigger: &str) -> Option<&str> {
+    trigger.rsplit_once(':').map(|(count, _)| count)
+}
+
+/// Distinct contracts and actors per rule over `window`, for every rule that
+/// fired in it.
+pub fn cardinality(window: u64, counts: &StoreGetInt64) -> Vec<RuleCardinality> {
+    dictionary::event_types()
+        .filter_map(|rule| {
+            let count = |side| {
+                counts
+                    .get_last(count_key(window, rule, side))
+                    .unwrap_or_default()
+                    .max(0) as u64
+            };
+            let (contracts, actors) = (count("contract"), count("actor"));
+            (contracts > 0 || actors > 0).then(|| RuleCardinality {
+                rule: rule.to_string(),
+                contracts,
+                actors,
+            })
+        })
+        .collect()
+}
+
+/// Size of one module's output for a block: its encoded length, which is
+/// what a sink stores and a provider bills egress on, and the records in it.
+pub fn output(
+    module: &str,
+    output_type: &str,
+    message: &impl Message,
+    items: usize,
+) -> OutputMetric {
+    OutputMetric {
+        module: module.to_string(),
+        output_type: output_type.to_string(),
+        bytes: message.encoded_len() as u64,
+        items: items as u64,
+    }
+}
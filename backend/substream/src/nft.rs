@@ -0,0 +1,235 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use substreams::store::StoreGetString;
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::approvals::{self, Grant};
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::{ERC1155, ERC721};
+use crate::u256::U256;
+use crate::{abi, calls};
+
+// Transfer(address,address,uint256), ERC-721 when the token id is indexed.
+const TRANSFER_TOPIC: [u8; 32] =
+    hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+// TransferSingle(address,address,address,uint256,uint256)
+const TRANSFER_SINGLE_TOPIC: [u8; 32] =
+    hex_literal::hex!("c3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62");
+// TransferBatch(address,address,address,uint256[],uint256[])
+const TRANSFER_BATCH_TOPIC: [u8; 32] =
+    hex_literal::hex!("4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb");
+// ApprovalForAll(address,address,bool)
+const APPROVAL_FOR_ALL_TOPIC: [u8; 32] =
+    hex_literal::hex!("17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31");
+
+/// Least distinct collections one owner must lose to a recipient for the
+/// transfers to look like a sweep rather than a sale.
+const MIN_COLLECTIONS: usize = 2;
+
+/// One token moved by an ERC-721 or ERC-1155 transfer log. A batch log yields
+/// one item per id.
+pub struct Item {
+    pub log_index: u32,
+    pub ordinal: u64,
+    pub collection: String,
+    /// Caller named by ERC-1155 logs; ERC-721 logs don't carry it.
+    pub operator: Option<String>,
+    pub from: String,
+    pub to: String,
+    pub id: U256,
+    pub amount: U256,
+}
+
+/// Decodes the tokens an ERC-721 `Transfer` or ERC-1155 `TransferSingle` /
+/// `TransferBatch` log moved. ERC20 transfers (three topics) and malformed
+/// logs yield nothing.
+pub fn items(log: &eth::Log) -> Vec<Item> {
+    let Some(topic0) = log.topics.first().map(Vec::as_slice) else {
+        return Vec::new();
+    };
+    let collection = format!("0x{}", hex::encode(&log.address));
+    let item = |operator, from, to, id, amount| Item {
+        log_index: log.index,
+        ordinal: log.ordinal,
+        collection: collection.clone(),
+        operator,
+        from,
+        to,
+        id,
+        amount,
+    };
+
+    if topic0 == TRANSFER_TOPIC && log.topics.len() == 4 {
+        let (Some(from), Some(to), Some(id)) = (
+            abi::topic_address(log, 1),
+            abi::topic_address(log, 2),
+            abi::topic_u256(log, 3),
+        ) else {
+            return Vec::new();
+        };
+        return vec![item(None, from, to, id, U256::one())];
+    }
+    if log.topics.len() != 4 || (topic0 != TRANSFER_SINGLE_TOPIC && topic0 != TRANSFER_BATCH_TOPIC)
+    {
+        return Vec::new();
+    }
+    let (Some(operator), Some(from), Some(to)) = (
+        abi::topic_address(log, 1),
+        abi::topic_address(log, 2),
+        abi::topic_address(log, 3),
+    ) else {
+        return Vec::new();
+    };
+    let moved: Vec<(U256, U256)> = if topic0 == TRANSFER_SINGLE_TOPIC {
+        match abi::word_u256(&log.data, 0).zip(abi::word_u256(&log.data, 1)) {
+            Some(moved) => vec![moved],
+            None => return Vec::new(),
+        }
+    } else {
+        match abi::word_array(&log.data, 0).zip(abi::word_array(&log.data, 1)) {
+            Some((ids, amounts)) if ids.len() == amounts.len() => ids
+                .into_iter()
+                .zip(amounts)
+                .map(|(id, amount)| (U256::from_big_endian(id), U256::from_big_endian(amount)))
+                .collect(),
+            _ => return Vec::new(),
+        }
+    };
+    moved
+        .into_iter()
+        .map(|(id, amount)| item(Some(operator.clone()), from.clone(), to.clone(), id, amount))
+        .collect()
+}
+
+/// Token standard of a log `items` decodes.
+pub fn standard(log: &eth::Log) -> &'static str {
+    if log.topics.first().map(Vec::as_slice) == Some(&TRANSFER_TOPIC[..]) {
+        ERC721
+    } else {
+        ERC1155
+    }
+}
+
+/// Decodes `ApprovalForAll(owner, operator, approved)`, shared by ERC-721
+/// and ERC-1155.
+pub fn operator_approval(log: &eth::Log) -> Option<(String, String, bool)> {
+    if log.topics.len() != 3 || log.topics.first()? != &APPROVAL_FOR_ALL_TOPIC {
+        return None;
+    }
+    Some((
+        abi::topic_address(log, 1)?,
+        abi::topic_address(log, 2)?,
+        !abi::word_u256(&log.data, 0)?.is_zero(),
+    ))
+}
+
+/// `ApprovalForAll(owner, operator, true)` grants in the block, keyed like
+/// ERC20 approvals with the collection as the token. Revocations are skipped.
+pub fn operator_grants(blk: &eth::Block) -> Vec<Grant> {
+    calls::block_logs(blk)
+        .filter_map(|(_, log)| {
+            let (owner, operator, approved) = operator_approval(&log)?;
+            approved.then(|| Grant {
+                ordinal: log.ordinal,
+                owner,
+                token: format!("0x{}", hex::encode(&log.address)),
+                spender: operator,
+            })
+        })
+        .collect()
+}
+
+/// Qualifying transfers by (victim, recipient, operator), each with its
+/// transaction hash and the block of the approval that enabled it.
+type Sweeps<'a> = BTreeMap<(String, String, String), Vec<(&'a [u8], Item, u64)>>;
+
+/// `NftDrainCandidate` events for an operator moving tokens of at least two
+/// collections from one owner to one recipient within `window_blocks` of the
+/// owner's first-ever `ApprovalForAll` of that operator on each collection:
+/// the sweep that follows a phished `setApprovalForAll` signature. One event
+/// per owner, recipient and operator in the block, at its first transfer.
+pub fn drains(
+    blk: &eth::Block,
+    approvals: &StoreGetString,
+    window_blocks: u64,
+) -> Vec<CandidateEvent> {
+    let executors: BTreeMap<u64, Cow<[u8]>> = blk
+        .transaction_traces
+        .iter()
+        .flat_map(approvals::executors)
+        .collect();
+
+    let mut sweeps: Sweeps = BTreeMap::new();
+    for tx in blk.transaction_traces.iter() {
+        for item in calls::tx_logs(tx).0.iter().flat_map(items) {
+            let operator = match &item.operator {
+                Some(operator) => operator.clone(),
+                None => match executors.get(&item.ordinal) {
+                    Some(executor) => format!("0x{}", hex::encode(executor)),
+                    None => continue,
+                },
+            };
+            if operator == item.from || item.to == item.from {
+                continue;
+            }
+            let Some(granted) = approvals::first_grant(
+                approvals,
+                &approvals::approval_key(&item.from, &item.collection, &operator),
+                blk.number,
+                item.ordinal,
+                window_blocks,
+            ) else {
+                continue;
+            };
+            sweeps
+                .entry((item.from.clone(), item.to.clone(), operator))
+                .or_default()
+                .push((&tx.hash, item, granted));
+        }
+    }
+
+    let mut events = Vec::new();
+    for ((victim, recipient, operator), swept) in sweeps {
+        let mut collections: Vec<&str> = swept
+            .iter()
+            .map(|(_, i, _)| i.collection.as_str())
+            .collect();
+        collections.sort_unstable();
+        collections.dedup();
+        if collections.len() < MIN_COLLECTIONS {
+            continue;
+        }
+        let collections: Vec<String> = collections.iter().map(|c| format!("\"{}\"", c)).collect();
+        let listed: Vec<String> = swept
+            .iter()
+            .map(|(_, item, granted)| {
+                format!(
+                    "{{\"collection\":\"{}\",\"token_id\":\"{}\",\"amount\":\"{}\",\"approved_block\":{}}}",
+                    item.collection, item.id, item.amount, granted
+                )
+            })
+            .collect();
+        let Some((tx_hash, first, _)) = swept.first() else {
+            continue;
+        };
+        events.push(CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(tx_hash)),
+            block_number: blk.number,
+            log_index: first.log_index as u64,
+            contract_address: operator.clone(),
+            event_type: "NftDrainCandidate".to_string(),
+            owner: victim.clone(),
+            spender: operator,
+            from: victim,
+            to: recipient,
+            metadata: format!(
+                "{{\"collections\":[{}],\"items\":[{}]}}",
+                collections.join(","),
+                listed.join(",")
+            ),
+            ..Default::default()
+        });
+    }
+    events
+}
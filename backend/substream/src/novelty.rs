@@ -0,0 +1,106 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::u256::{self, U256};
+use crate::{abi, amount};
+
+/// Key holding the first block a contract called another.
+pub fn pair_key(caller: &str, callee: &str) -> String {
+    format!("pair:{}:{}", caller, callee)
+}
+
+/// One contract calling another, with the value it moved between them: the
+/// call's native value and the ERC20 transfers it emitted between the two.
+pub struct Interaction<'a> {
+    pub tx_hash: &'a [u8],
+    pub call_index: u32,
+    pub ordinal: u64,
+    pub caller: String,
+    pub callee: String,
+    /// `(token, amount)`, `"native"` for the call value.
+    pub moves: Vec<(String, U256)>,
+}
+
+/// Non-reverted contract-to-contract calls involving a watched contract.
+/// Nested calls are the only ones with a contract as caller, so this needs
+/// call traces.
+pub fn interactions<'a>(tx: &'a eth::TransactionTrace, watch: &[String]) -> Vec<Interaction<'a>> {
+    tx.calls
+        .iter()
+        .filter(|call| {
+            call.depth > 0 && !call.state_reverted && call.call_type != eth::CallType::Create as i32
+        })
+        .filter_map(|call| {
+            let caller = format!("0x{}", hex::encode(&call.caller));
+            let callee = format!("0x{}", hex::encode(&call.address));
+            if !watch.contains(&caller) && !watch.contains(&callee) {
+                return None;
+            }
+
+            let mut moves = Vec::new();
+            let value = u256::from_pb(call.value.as_ref());
+            if !value.is_zero() {
+                moves.push(("native".to_string(), value));
+            }
+            let between = |a: &[u8], b: &[u8]| {
+                (a == call.caller && b == call.address) || (a == call.address && b == call.caller)
+            };
+            for log in call.logs.iter() {
+                if let Some(transfer) = abi::transfer(log).filter(|t| between(t.from, t.to)) {
+                    moves.push((format!("0x{}", hex::encode(&log.address)), transfer.amount));
+                }
+            }
+
+            Some(Interaction {
+                tx_hash: &tx.hash,
+                call_index: call.index,
+                ordinal: call.begin_ordinal,
+                caller,
+                callee,
+                moves,
+            })
+        })
+        .collect()
+}
+
+/// Whether an interaction moved at least `min_units` whole tokens of any
+/// asset.
+pub fn is_large(interaction: &Interaction, min_units: u64, annotations: &Annotations) -> bool {
+    interaction
+        .moves
+        .iter()
+        .any(|(token, raw)| amount::at_least_units(annotations, *raw, token, min_units))
+}
+
+/// Builds a `NovelInteraction` for a large interaction between two contracts
+/// that had never called each other before this block. `log_index` carries
+/// the call index.
+pub fn candidate(
+    interaction: &Interaction,
+    block_number: u64,
+    annotations: &Annotations,
+) -> CandidateEvent {
+    let moves: Vec<String> = interaction
+        .moves
+        .iter()
+        .map(|(token, raw)| {
+            format!(
+                "{{\"token\":\"{}\",{}}}",
+                token,
+                amount::json_fields(annotations, "amount", *raw, token)
+            )
+        })
+        .collect();
+    CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(interaction.tx_hash)),
+        block_number,
+        log_index: interaction.call_index as u64,
+        contract_address: interaction.callee.clone(),
+        event_type: "NovelInteraction".to_string(),
+        from: interaction.caller.clone(),
+        to: interaction.callee.clone(),
+        metadata: format!("{{\"moves\":[{}]}}", moves.join(",")),
+        ..Default::default()
+    }
+}
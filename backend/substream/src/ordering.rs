@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CandidateEvent;
+
+/// Gives every event its position `(block_number, tx_index, log_index, part)`
+/// and sorts the events by it. `part` numbers events sharing a transaction and
+/// log index (a log and the heuristic it completed) in emission order, so the
+/// key is unique within the block. `ordering_key` renders the tuple
+/// zero-padded so it also sorts correctly as a string across blocks.
+pub fn order(events: &mut [CandidateEvent], blk: &eth::Block) {
+    let tx_indexes: BTreeMap<String, u32> = blk
+        .transaction_traces
+        .iter()
+        .map(|tx| (format!("0x{}", hex::encode(&tx.hash)), tx.index))
+        .collect();
+    for event in events.iter_mut() {
+        event.tx_index = tx_indexes
+            .get(&event.transaction_hash)
+            .copied()
+            .unwrap_or_default();
+    }
+
+    // Stable, so equal positions keep their emission order.
+    events.sort_by_key(|e| (e.tx_index, e.log_index));
+
+    let mut previous = None;
+    let mut part = 0;
+    for event in events.iter_mut() {
+        let position = (event.tx_index, event.log_index);
+        part = if previous == Some(position) {
+            part + 1
+        } else {
+            0
+        };
+        previous = Some(position);
+
+        event.part = part;
+        event.ordering_key = format!(
+            "{:020}-{:010}-{:020}-{:010}",
+            event.block_number, event.tx_index, event.log_index, part
+        );
+    }
+}
@@ -0,0 +1,1201 @@
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use substreams::errors::Error;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::ParamDescriptor;
+use crate::u256::U256;
+use crate::{chain, params_blob, routing};
+
+/// Runtime configuration for `map_candidate_events` and the modules that share
+/// its thresholds, passed as `key=value` pairs joined with `&`, e.g.
+/// `substreams run -p map_candidate_events="mass_distribution_detail=true"`,
+/// or as a base64 `ParamsBlob` in the `blob` param.
+pub struct Params {
+    /// Attach every recipient/amount pair to `MassDistribution` events instead
+    /// of just the count and total.
+    pub mass_distribution_detail: bool,
+    /// Largest raw transfer amount counted as dust.
+    pub dust_max_amount: u64,
+    /// Distinct recipients one sender must dust in a block to be flagged.
+    pub dust_min_recipients: usize,
+    /// Blocks a non-dust transfer keeps its parties counterparties of each
+    /// other for address-poisoning matching.
+    pub counterparty_window_blocks: u64,
+    /// Blocks after a vault's first deposit during which a diluted deposit
+    /// counts as a first-depositor exploit.
+    pub first_depositor_window_blocks: u64,
+    /// Lowercase 0x-prefixed addresses singled out for monitoring,
+    /// given as a comma-separated list.
+    pub watch: Vec<String>,
+    /// Seconds between consecutive block timestamps above which a
+    /// `ChainAnomaly` timestamp gap is reported.
+    pub halt_gap_seconds: u64,
+    /// Consecutive blocks without transactions that make a `ChainAnomaly`.
+    pub empty_block_run: u64,
+    /// Blocks a watched contract may go without emitting a log before a
+    /// `ChainAnomaly` reports it silent.
+    pub silent_blocks: u64,
+    /// Blocks without a transaction after which a deployer acting on its own
+    /// contracts' admin rights is flagged.
+    pub deployer_dormancy_blocks: u64,
+    /// Blocks an address must go without moving value out before a large
+    /// move from it is reported as `DormantAddressActive`.
+    pub dormant_address_blocks: u64,
+    /// Smallest move, in whole tokens, that counts as large for
+    /// `DormantAddressActive`.
+    pub whale_min_units: u64,
+    /// Whole tokens an ERC20 transfer must exceed to be emitted as
+    /// `LargeTransfer` instead of `Transfer`; 0 disables the split.
+    pub large_transfer_units: u64,
+    /// Whole tokens an approval to an unknown spender must exceed to be a
+    /// `RiskyApproval`; 0 flags only `MAX_UINT256` approvals.
+    pub risky_approval_units: u64,
+    /// Blocks since its creation within which a permit's spender counts as
+    /// freshly deployed for `RiskyApproval`.
+    pub fresh_spender_blocks: u64,
+    /// Whole tokens the sandwiched swap must sell for a `SandwichCandidate`;
+    /// 0 accepts any victim swap.
+    pub sandwich_min_victim_units: u64,
+    /// Profile of the chain the substream runs on: its extra swap topics and
+    /// curated token list, protocol registry and labels.
+    pub chain: &'static chain::Profile,
+    /// Chain the substream runs on, as its EVM chain id, for the receiving
+    /// side of Hyperlane messages. Defaults to the profile's.
+    pub chain_id: u64,
+    /// Chain the substream runs on, as its LayerZero endpoint id, for the
+    /// receiving side of LayerZero messages. Defaults to the profile's.
+    pub layerzero_eid: u64,
+    /// Blocks after an owner's first approval of a spender in which a
+    /// transfer the spender executes from the owner counts as a drain.
+    pub approval_drain_window_blocks: u64,
+    /// Blocks after an owner's first `ApprovalForAll` of an operator in which
+    /// the operator sweeping the owner's NFTs counts as a drain.
+    pub nft_drain_window_blocks: u64,
+    /// Length of the windows whose per-block approval rate is the baseline
+    /// for `ApprovalSpike`; each window is compared against the previous one.
+    pub approval_baseline_blocks: u64,
+    /// Standard deviations above its baseline rate a token's approvals in
+    /// one block must reach for an `ApprovalSpike`.
+    pub approval_spike_z: u64,
+    /// Fewest approvals on a token in one block that can make a spike.
+    pub approval_spike_min: u64,
+    /// Length of the windows `map_activity_spikes` takes a contract's
+    /// baseline activity over.
+    pub activity_window_blocks: u64,
+    /// Multiple of its mean activity per block a contract's activity in one
+    /// block must reach to spike.
+    pub activity_spike_multiple: u64,
+    /// Fewest events of one kind on a contract in one block that can spike.
+    pub activity_spike_min: u64,
+    /// Multiple of the average gas its target's earlier transactions used
+    /// that a transaction must reach to be a gas anomaly.
+    pub gas_anomaly_multiple: u64,
+    /// Earlier transactions a contract needs before its average counts.
+    pub gas_anomaly_min_samples: u64,
+    /// Calls to one precompile in one transaction that make a
+    /// `PrecompileAnomaly`.
+    pub precompile_min_calls: u64,
+    /// Percent of a flagged transaction's gas used that its end-of-execution
+    /// refund must reach to count as gas token usage.
+    pub gas_refund_min_percent: u64,
+    /// Blocks after a liquidity removal in which the pool deployer moving
+    /// out what was removed is a rug pull.
+    pub rug_pull_window_blocks: u64,
+    /// Percent of what a removal paid out of a token that one transfer by
+    /// the deployer must move.
+    pub rug_pull_min_share: u64,
+    /// Blocks between `map_nft_holder_snapshots` outputs.
+    pub nft_snapshot_blocks: u64,
+    /// Blocks between `map_enum_dictionary` outputs.
+    pub dictionary_blocks: u64,
+    /// Length of the windows `map_rule_cardinality` counts distinct triggers
+    /// over.
+    pub rule_metrics_blocks: u64,
+    /// Seconds past the block a permit's deadline may lie before the permit
+    /// is reported as `LongLivedPermit`.
+    pub permit_max_lifetime_seconds: u64,
+    /// Blocks before a flagged transaction in which its sender's setup
+    /// actions are linked to it.
+    pub setup_window_blocks: u64,
+    /// Blocks behind the current one before stored setup and probe actions
+    /// count as final and can escalate an incident. Actions from more recent
+    /// blocks could still be reorged out.
+    pub finality_depth: u64,
+    /// Guardian contracts and pause selectors to recommend when a critical
+    /// incident touches a protocol contract, given as comma-separated
+    /// `contract:guardian:selector` entries.
+    pub pause_registry: Vec<PauseEntry>,
+    /// Flagged or stolen NFTs whose every transfer is reported as
+    /// `StolenAssetMovement`, given as comma-separated `collection:id` or
+    /// `collection:first-last` entries with decimal token ids.
+    pub stolen_tokens: Vec<TokenRange>,
+    /// Tokens looked up before the curated token list, for symbols, decimals
+    /// and pegs on chains or tokens the list doesn't cover.
+    pub tokens: Vec<CanonicalToken>,
+    /// Only logs from these contracts become candidate events; empty allows
+    /// every contract.
+    pub contracts: Vec<String>,
+    /// Logs from these contracts never become candidate events.
+    pub exclude_contracts: Vec<String>,
+    /// Extra `topic0` hashes emitted as `CustomTopic` events.
+    pub topics: Vec<String>,
+    /// `topics` as raw bytes, to match logs against without hex-encoding
+    /// them.
+    pub topic_hashes: Vec<[u8; 32]>,
+    /// Smallest score, out of 100, at which `map_suspicious_transactions`
+    /// reports a transaction.
+    pub suspicion_min_score: u32,
+    /// Transfers out of one address in one transaction that count as a
+    /// victim being drained.
+    pub victim_min_transfers: usize,
+    /// Whole tokens an address must lose, net of what it receives back, in a
+    /// suspicious transaction to be listed among its `victims`; 0 lists every
+    /// net loser.
+    pub victim_min_units: u64,
+    /// Another operator's published event roots, as comma-separated
+    /// `block:root` entries, to compare the local roots against.
+    pub peer_roots: Vec<(u64, String)>,
+    /// How much of the raw log payload decoded log events carry in their
+    /// `topics`, `data` and `tx_input` fields.
+    pub raw_payload: RawPayload,
+    /// Attach the full call trace to critical events.
+    pub critical_traces: bool,
+    /// Mode of each rule (event type) listed in `rule_modes`; rules not
+    /// listed are enforced.
+    pub rule_modes: Vec<(String, RuleMode)>,
+    /// Opaque routing keys (team or channel ids) of watched contracts,
+    /// attached to the events involving them, given as comma-separated
+    /// `contract:key` entries. A contract may have several.
+    pub routes: Vec<(String, String)>,
+    /// Named watch lists events are tagged with in the same pass, given as
+    /// comma-separated `id:min_severity:route:address|address...` entries.
+    /// Tenants only filter by severity; detector thresholds are the global
+    /// params, shared by every tenant.
+    pub tenants: Vec<Tenant>,
+}
+
+/// A named watch list of the `tenants` param. Events are detected once for
+/// all tenants, so a tenant chooses which events reach it by severity only.
+pub struct Tenant {
+    pub id: String,
+    /// Least severity an event involving the tenant's addresses must have to
+    /// be tagged for it.
+    pub min_severity: String,
+    /// Routing key attached to the tenant's events; empty for none.
+    pub route: String,
+    pub addresses: Vec<String>,
+}
+
+/// One way to pause a protocol contract: call `selector` on `guardian`.
+pub struct PauseEntry {
+    pub contract: String,
+    pub guardian: String,
+    pub selector: String,
+}
+
+/// A token of the `tokens` param, listed like the curated token list.
+#[derive(Clone)]
+pub struct CanonicalToken {
+    pub address: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// What the token tracks (`usd`, `eth`, ...); empty when unpegged.
+    pub peg: String,
+}
+
+/// Token ids `first..=last` of an NFT collection.
+pub struct TokenRange {
+    pub collection: String,
+    pub first: U256,
+    pub last: U256,
+}
+
+impl TokenRange {
+    pub fn contains(&self, collection: &str, id: U256) -> bool {
+        self.collection == collection && self.first <= id && id <= self.last
+    }
+}
+
+/// Raw payload attached to events decoded from logs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RawPayload {
+    /// Decoded fields only; `topics`, `data` and `tx_input` are left empty.
+    Omit,
+    /// The log's topics and data, in `topics` and `data`.
+    Log,
+    /// The log's topics and data plus the calldata of the transaction that
+    /// emitted it, in `tx_input`.
+    Full,
+}
+
+/// Whether a rule's events may page.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RuleMode {
+    /// Events keep the severity their detector gave them.
+    Enforce,
+    /// Events are emitted at `info`, their severity moved to
+    /// `shadow_severity`, so nothing downstream alerts on them.
+    Shadow,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            mass_distribution_detail: false,
+            dust_max_amount: 1_000,
+            dust_min_recipients: 1_000,
+            // About a week of Polygon blocks.
+            counterparty_window_blocks: 302_400,
+            // About a day of Polygon blocks.
+            first_depositor_window_blocks: 43_200,
+            watch: Vec::new(),
+            raw_payload: RawPayload::Log,
+            critical_traces: false,
+            rule_modes: Vec::new(),
+            routes: Vec::new(),
+            tenants: Vec::new(),
+            // Polygon targets ~2s blocks.
+            halt_gap_seconds: 60,
+            empty_block_run: 30,
+            // About an hour of Polygon blocks.
+            silent_blocks: 1_800,
+            // About 30 days of Polygon blocks.
+            deployer_dormancy_blocks: 1_296_000,
+            // About 180 days of Polygon blocks.
+            dormant_address_blocks: 7_776_000,
+            whale_min_units: 100_000,
+            large_transfer_units: 0,
+            risky_approval_units: 0,
+            // About a day of Polygon blocks.
+            fresh_spender_blocks: 43_200,
+            sandwich_min_victim_units: 0,
+            chain: &chain::POLYGON,
+            chain_id: chain::POLYGON.chain_id,
+            layerzero_eid: chain::POLYGON.layerzero_eid,
+            // About an hour of Polygon blocks.
+            approval_drain_window_blocks: 1_800,
+            // About five minutes of Polygon blocks.
+            nft_drain_window_blocks: 150,
+            // About an hour of Polygon blocks.
+            approval_baseline_blocks: 1_800,
+            approval_spike_z: 4,
+            approval_spike_min: 20,
+            // About an hour of Polygon blocks.
+            activity_window_blocks: 1_800,
+            activity_spike_multiple: 100,
+            activity_spike_min: 20,
+            gas_anomaly_multiple: 10,
+            gas_anomaly_min_samples: 100,
+            gas_refund_min_percent: 10,
+            precompile_min_calls: 1_000,
+            // About five minutes of Polygon blocks.
+            rug_pull_window_blocks: 150,
+            rug_pull_min_share: 50,
+            // About a day of Polygon blocks.
+            nft_snapshot_blocks: 43_200,
+            // About a day of Polygon blocks.
+            dictionary_blocks: 43_200,
+            // About an hour of Polygon blocks.
+            rule_metrics_blocks: 1_800,
+            // 30 days.
+            permit_max_lifetime_seconds: 2_592_000,
+            // About a day of Polygon blocks.
+            setup_window_blocks: 43_200,
+            // Link actions as soon as they are stored; substreams undoes
+            // reorged blocks itself.
+            finality_depth: 0,
+            pause_registry: Vec::new(),
+            stolen_tokens: Vec::new(),
+            tokens: Vec::new(),
+            contracts: Vec::new(),
+            exclude_contracts: Vec::new(),
+            topics: Vec::new(),
+            topic_hashes: Vec::new(),
+            peer_roots: Vec::new(),
+            suspicion_min_score: 50,
+            victim_min_transfers: 5,
+            victim_min_units: 0,
+        }
+    }
+}
+
+impl Params {
+    pub fn parse(raw: &str) -> Result<Self, Error> {
+        let mut params = Params::default();
+        // Set after the loop, so the profile's defaults don't depend on the
+        // order of the params.
+        let (mut chain_id, mut layerzero_eid) = (None, None);
+
+        // A blob stands for the pairs it carries, in its place.
+        let mut pairs: Vec<(Cow<str>, Cow<str>)> = Vec::new();
+        for pair in raw.split('&').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("param {:?} is not of the form key=value", pair))?;
+            let key = key.trim();
+            if key == "blob" {
+                let blob = params_blob::pairs(key, value.trim())?;
+                pairs.extend(
+                    blob.into_iter()
+                        .map(|(k, v)| (Cow::Owned(k), Cow::Owned(v))),
+                );
+            } else {
+                pairs.push((Cow::Borrowed(key), Cow::Borrowed(value)));
+            }
+        }
+
+        for (key, value) in pairs.iter() {
+            let (key, value) = (key.as_ref(), value.as_ref());
+            match key {
+                "mass_distribution_detail" => {
+                    params.mass_distribution_detail = parse_bool(key, value)?
+                }
+                "dust_max_amount" => params.dust_max_amount = parse_number(key, value)?,
+                "dust_min_recipients" => params.dust_min_recipients = parse_number(key, value)?,
+                "counterparty_window_blocks" => {
+                    params.counterparty_window_blocks = parse_number(key, value)?
+                }
+                "first_depositor_window_blocks" => {
+                    params.first_depositor_window_blocks = parse_number(key, value)?
+                }
+                "watch" => params.watch = parse_addresses(key, value)?,
+                "halt_gap_seconds" => params.halt_gap_seconds = parse_number(key, value)?,
+                "empty_block_run" => params.empty_block_run = parse_number(key, value)?,
+                "silent_blocks" => params.silent_blocks = parse_number(key, value)?,
+                "deployer_dormancy_blocks" => {
+                    params.deployer_dormancy_blocks = parse_number(key, value)?
+                }
+                "dormant_address_blocks" => {
+                    params.dormant_address_blocks = parse_number(key, value)?
+                }
+                "whale_min_units" => params.whale_min_units = parse_number(key, value)?,
+                "large_transfer_units" => params.large_transfer_units = parse_number(key, value)?,
+                "risky_approval_units" => params.risky_approval_units = parse_number(key, value)?,
+                "fresh_spender_blocks" => params.fresh_spender_blocks = parse_number(key, value)?,
+                "chain" => {
+                    params.chain = chain::profile(value.trim()).ok_or_else(|| {
+                        anyhow!(
+                            "param {} expects one of {}, got {:?}",
+                            key,
+                            chain::PROFILES.map(|p| p.name).join(", "),
+                            value
+                        )
+                    })?
+                }
+                "chain_id" => chain_id = Some(parse_number(key, value)?),
+                "layerzero_eid" => layerzero_eid = Some(parse_number(key, value)?),
+                "sandwich_min_victim_units" => {
+                    params.sandwich_min_victim_units = parse_number(key, value)?
+                }
+                "approval_drain_window_blocks" => {
+                    params.approval_drain_window_blocks = parse_number(key, value)?
+                }
+                "nft_drain_window_blocks" => {
+                    params.nft_drain_window_blocks = parse_number(key, value)?
+                }
+                "approval_baseline_blocks" => {
+                    params.approval_baseline_blocks = parse_number(key, value)?
+                }
+                "approval_spike_z" => params.approval_spike_z = parse_number(key, value)?,
+                "approval_spike_min" => params.approval_spike_min = parse_number(key, value)?,
+                "activity_window_blocks" => {
+                    params.activity_window_blocks = parse_number(key, value)?
+                }
+                "activity_spike_multiple" => {
+                    params.activity_spike_multiple = parse_number(key, value)?
+                }
+                "activity_spike_min" => params.activity_spike_min = parse_number(key, value)?,
+                "gas_anomaly_multiple" => params.gas_anomaly_multiple = parse_number(key, value)?,
+                "gas_anomaly_min_samples" => {
+                    params.gas_anomaly_min_samples = parse_number(key, value)?
+                }
+                "precompile_min_calls" => params.precompile_min_calls = parse_number(key, value)?,
+                "gas_refund_min_percent" => {
+                    params.gas_refund_min_percent = parse_number(key, value)?
+                }
+                "rug_pull_window_blocks" => {
+                    params.rug_pull_window_blocks = parse_number(key, value)?
+                }
+                "rug_pull_min_share" => params.rug_pull_min_share = parse_number(key, value)?,
+                "nft_snapshot_blocks" => params.nft_snapshot_blocks = parse_number(key, value)?,
+                "dictionary_blocks" => params.dictionary_blocks = parse_number(key, value)?,
+                "rule_metrics_blocks" => params.rule_metrics_blocks = parse_number(key, value)?,
+                "permit_max_lifetime_seconds" => {
+                    params.permit_max_lifetime_seconds = parse_number(key, value)?
+                }
+                "setup_window_blocks" => params.setup_window_blocks = parse_number(key, value)?,
+                "finality_depth" => params.finality_depth = parse_number(key, value)?,
+                "pause_registry" => params.pause_registry = parse_pause_registry(key, value)?,
+                "stolen_tokens" => params.stolen_tokens = parse_token_ranges(key, value)?,
+                "tokens" => params.tokens = parse_canonical_tokens(key, value)?,
+                "contracts" => params.contracts = parse_addresses(key, value)?,
+                "exclude_contracts" => params.exclude_contracts = parse_addresses(key, value)?,
+                "topics" => params.topics = parse_topics(key, value)?,
+                "suspicion_min_score" => params.suspicion_min_score = parse_number(key, value)?,
+                "victim_min_transfers" => params.victim_min_transfers = parse_number(key, value)?,
+                "victim_min_units" => params.victim_min_units = parse_number(key, value)?,
+                "peer_roots" => params.peer_roots = parse_peer_roots(key, value)?,
+                "raw_payload" => params.raw_payload = parse_raw_payload(key, value)?,
+                "critical_traces" => params.critical_traces = parse_bool(key, value)?,
+                "rule_modes" => params.rule_modes = parse_rule_modes(key, value)?,
+                "routes" => params.routes = parse_routes(key, value)?,
+                "tenants" => params.tenants = parse_tenants(key, value)?,
+                other => bail!("unknown param {:?}", other),
+            }
+        }
+
+        if params.approval_baseline_blocks == 0 {
+            bail!("param approval_baseline_blocks must be positive");
+        }
+        if params.activity_window_blocks < 2 {
+            bail!("param activity_window_blocks must be at least 2");
+        }
+        if params.counterparty_window_blocks == 0 {
+            bail!("param counterparty_window_blocks must be positive");
+        }
+        if params.rug_pull_window_blocks == 0 {
+            bail!("param rug_pull_window_blocks must be positive");
+        }
+        if params.setup_window_blocks == 0 {
+            bail!("param setup_window_blocks must be positive");
+        }
+        if params.rug_pull_min_share == 0 || params.rug_pull_min_share > 100 {
+            bail!("param rug_pull_min_share must be between 1 and 100");
+        }
+        if params.nft_snapshot_blocks == 0 {
+            bail!("param nft_snapshot_blocks must be positive");
+        }
+        if params.dictionary_blocks == 0 {
+            bail!("param dictionary_blocks must be positive");
+        }
+        if params.rule_metrics_blocks == 0 {
+            bail!("param rule_metrics_blocks must be positive");
+        }
+        params.topic_hashes = params
+            .topics
+            .iter()
+            .filter_map(|topic| hex::decode(&topic[2..]).ok()?.try_into().ok())
+            .collect();
+        params.chain_id = chain_id.unwrap_or(params.chain.chain_id);
+        params.layerzero_eid = layerzero_eid.unwrap_or(params.chain.layerzero_eid);
+        Ok(params)
+    }
+
+    /// Token, protocol and label lookups of the chain profile, with the
+    /// `tokens` param first.
+    pub fn annotations(&self) -> Annotations<'_> {
+        Annotations::new(self.chain, &self.tokens)
+    }
+
+    /// Ages, in blocks, at which a stored setup or probe action can be linked
+    /// to the current transaction: final, and inside the setup window.
+    pub fn setup_ages(&self) -> RangeInclusive<u64> {
+        self.finality_depth..=self.setup_window_blocks
+    }
+
+    /// Mode of `rule`; the last `rule_modes` entry naming it wins.
+    pub fn rule_mode(&self, rule: &str) -> RuleMode {
+        self.rule_modes
+            .iter()
+            .rev()
+            .find(|(r, _)| r == rule)
+            .map(|(_, mode)| *mode)
+            .unwrap_or(RuleMode::Enforce)
+    }
+
+    /// Whether a raw topic0 is one of the `topics` param's.
+    pub fn custom_topic(&self, topic0: &[u8]) -> bool {
+        self.topic_hashes.iter().any(|topic| topic[..] == *topic0)
+    }
+
+    /// Whether logs from `contract` pass the allow and deny lists.
+    pub fn allows(&self, contract: &str) -> bool {
+        (self.contracts.is_empty() || self.contracts.iter().any(|c| c == contract))
+            && !self.exclude_contracts.iter().any(|c| c == contract)
+    }
+}
+
+/// Short hash identifying a module's configuration: its own `raw` params,
+/// with pairs trimmed and sorted so their order doesn't matter, chained onto
+/// the epoch of the `upstream` output it consumes (empty when none). Sinks
+/// see it change exactly when a param behind an output does.
+pub fn config_epoch(raw: &str, upstream: &str) -> String {
+    let mut pairs: Vec<&str> = raw
+        .split('&')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    pairs.sort_unstable();
+
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(upstream.as_bytes());
+    keccak.update(&[0]);
+    keccak.update(pairs.join("&").as_bytes());
+    keccak.finalize(&mut hash);
+    format!("0x{}", hex::encode(&hash[..8]))
+}
+
+fn param(
+    name: &str,
+    kind: &str,
+    default_value: impl ToString,
+    description: &str,
+) -> ParamDescriptor {
+    ParamDescriptor {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        default_value: default_value.to_string(),
+        description: description.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Every param `Params::parse` accepts, with its default as it would be
+/// passed, taken from `Params::default()`, and the bounds `parse` enforces.
+/// `chain_id` and `layerzero_eid` show the default profile's values.
+pub fn descriptors() -> Vec<ParamDescriptor> {
+    let d = Params::default();
+    let list = |items: &[String]| items.join(",");
+    let raw_payload = match d.raw_payload {
+        RawPayload::Omit => "omit",
+        RawPayload::Log => "log",
+        RawPayload::Full => "full",
+    };
+    vec![
+        param(
+            "mass_distribution_detail",
+            "bool",
+            d.mass_distribution_detail,
+            "List every recipient and amount on MassDistribution events",
+        ),
+        param(
+            "dust_max_amount",
+            "uint",
+            d.dust_max_amount,
+            "Largest raw transfer amount counted as dust",
+        ),
+        param(
+            "dust_min_recipients",
+            "uint",
+            d.dust_min_recipients,
+            "Distinct recipients one sender must dust in a block to emit DustingCampaign",
+        ),
+        ParamDescriptor {
+            minimum: 1,
+            ..param(
+                "counterparty_window_blocks",
+                "uint",
+                d.counterparty_window_blocks,
+                "Blocks a non-dust transfer's parties stay counterparties for poisoning matches",
+            )
+        },
+        param("watch", "addresses", list(&d.watch), "Addresses to monitor"),
+        param(
+            "halt_gap_seconds",
+            "uint",
+            d.halt_gap_seconds,
+            "Timestamp gap between consecutive blocks that map_chain_anomalies reports",
+        ),
+        param(
+            "empty_block_run",
+            "uint",
+            d.empty_block_run,
+            "Consecutive blocks without transactions that map_chain_anomalies reports",
+        ),
+        param(
+            "silent_blocks",
+            "uint",
+            d.silent_blocks,
+            "Blocks a watched contract may go without logs before it is reported silent",
+        ),
+        param(
+            "deployer_dormancy_blocks",
+            "uint",
+            d.deployer_dormancy_blocks,
+            "Blocks a deployer must go without a transaction before its admin actions alert",
+        ),
+        param(
+            "dormant_address_blocks",
+            "uint",
+            d.dormant_address_blocks,
+            "Blocks an address must go without moving value to be reported dormant",
+        ),
+        param(
+            "whale_min_units",
+            "uint",
+            d.whale_min_units,
+            "Smallest move, in whole tokens, that dormancy and novelty checks treat as large",
+        ),
+        param(
+            "large_transfer_units",
+            "uint",
+            d.large_transfer_units,
+            "Whole tokens a transfer must exceed to be a LargeTransfer; 0 disables",
+        ),
+        param(
+            "risky_approval_units",
+            "uint",
+            d.risky_approval_units,
+            "Whole tokens an approval to an unknown spender must exceed; 0 flags MAX_UINT256 only",
+        ),
+        param(
+            "sandwich_min_victim_units",
+            "uint",
+            d.sandwich_min_victim_units,
+            "Whole tokens the sandwiched swap must sell; 0 accepts any swap",
+        ),
+        param(
+            "fresh_spender_blocks",
+            "uint",
+            d.fresh_spender_blocks,
+            "Blocks since its creation within which a permit's spender counts as freshly deployed",
+        ),
+        param(
+            "approval_drain_window_blocks",
+            "uint",
+            d.approval_drain_window_blocks,
+            "Blocks after an owner's first approval of a spender that its transfers are flagged",
+        ),
+        param(
+            "nft_drain_window_blocks",
+            "uint",
+            d.nft_drain_window_blocks,
+            "Blocks after an owner's first ApprovalForAll that operator sweeps are flagged",
+        ),
+        ParamDescriptor {
+            minimum: 1,
+            ..param(
+                "approval_baseline_blocks",
+                "uint",
+                d.approval_baseline_blocks,
+                "Windows whose per-block approval rate is the ApprovalSpike baseline",
+            )
+        },
+        param(
+            "approval_spike_z",
+            "uint",
+            d.approval_spike_z,
+            "Standard deviations above the baseline a token's approvals in a block must reach",
+        ),
+        param(
+            "approval_spike_min",
+            "uint",
+            d.approval_spike_min,
+            "Fewest approvals on a token in one block that can be an ApprovalSpike",
+        ),
+        ParamDescriptor {
+            minimum: 2,
+            ..param(
+                "activity_window_blocks",
+                "uint",
+                d.activity_window_blocks,
+                "Windows each contract's baseline activity is taken over",
+            )
+        },
+        param(
+            "activity_spike_multiple",
+            "uint",
+            d.activity_spike_multiple,
+            "Multiple of its mean activity per block a contract must reach to spike",
+        ),
+        param(
+            "activity_spike_min",
+            "uint",
+            d.activity_spike_min,
+            "Fewest events of one kind on a contract in one block that can spike",
+        ),
+        param(
+            "gas_anomaly_multiple",
+            "uint",
+            d.gas_anomaly_multiple,
+            "Multiple of its target's average gas a transaction must use for map_gas_anomalies",
+        ),
+        param(
+            "gas_anomaly_min_samples",
+            "uint",
+            d.gas_anomaly_min_samples,
+            "Earlier transactions a contract needs before its average gas is compared against",
+        ),
+        param(
+            "precompile_min_calls",
+            "uint",
+            d.precompile_min_calls,
+            "Calls to one precompile in one transaction that make a PrecompileAnomaly",
+        ),
+        param(
+            "gas_refund_min_percent",
+            "uint",
+            d.gas_refund_min_percent,
+            "Percent of its gas used a flagged transaction's refund must reach",
+        ),
+        ParamDescriptor {
+            minimum: 1,
+            ..param(
+                "rug_pull_window_blocks",
+                "uint",
+                d.rug_pull_window_blocks,
+                "Blocks after a liquidity removal that the pool deployer's transfers are flagged",
+            )
+        },
+        ParamDescriptor {
+            minimum: 1,
+            maximum: 100,
+            ..param(
+                "rug_pull_min_share",
+                "uint",
+                d.rug_pull_min_share,
+                "Percent of a removal's payout one deployer transfer must move",
+            )
+        },
+        ParamDescriptor {
+            minimum: 1,
+            ..param(
+                "nft_snapshot_blocks",
+                "uint",
+                d.nft_snapshot_blocks,
+                "Blocks between map_nft_holder_snapshots outputs",
+            )
+        },
+        ParamDescriptor {
+            minimum: 1,
+            ..param(
+                "dictionary_blocks",
+                "uint",
+                d.dictionary_blocks,
+                "Blocks between map_enum_dictionary and map_config_descriptor outputs",
+            )
+        },
+        ParamDescriptor {
+            minimum: 1,
+            ..param(
+                "rule_metrics_blocks",
+                "uint",
+                d.rule_metrics_blocks,
+                "Windows map_rule_cardinality counts contracts and actors over",
+            )
+        },
+        ParamDescriptor {
+            choices: chain::PROFILES
+                .iter()
+                .map(|profile| profile.name.to_string())
+                .collect(),
+            ..param(
+                "chain",
+                "enum",
+                d.chain.name,
+                "Chain profile: curated annotations and extra swap topics",
+            )
+        },
+        param(
+            "chain_id",
+            "uint",
+            d.chain_id,
+            "EVM chain id of the chain the substream runs on; defaults to the profile's",
+        ),
+        param(
+            "layerzero_eid",
+            "uint",
+            d.layerzero_eid,
+            "LayerZero endpoint id of the chain the substream runs on; defaults to the profile's",
+        ),
+        param(
+            "permit_max_lifetime_seconds",
+            "uint",
+            d.permit_max_lifetime_seconds,
+            "Seconds past the block a permit's deadline may lie before it is a LongLivedPermit",
+        ),
+        ParamDescriptor {
+            minimum: 1,
+            ..param(
+                "setup_window_blocks",
+                "uint",
+                d.setup_window_blocks,
+                "Blocks before a flagged transaction its sender's setups and rehearsals link from",
+            )
+        },
+        param(
+            "finality_depth",
+            "uint",
+            d.finality_depth,
+            "Blocks a stored setup or probe action must age before it can escalate an incident",
+        ),
+        param(
+            "pause_registry",
+            "entries",
+            "",
+            "contract:guardian:selector entries: call selector on guardian to pause contract",
+        ),
+        param(
+            "tokens",
+            "entries",
+            "",
+            "address:symbol:decimals[:peg] entries looked up before the curated token list",
+        ),
+        param(
+            "stolen_tokens",
+            "entries",
+            "",
+            "collection:id or collection:first-last entries of stolen NFTs",
+        ),
+        param(
+            "contracts",
+            "addresses",
+            list(&d.contracts),
+            "Allowlist: only logs from these contracts become decoded log events",
+        ),
+        param(
+            "exclude_contracts",
+            "addresses",
+            list(&d.exclude_contracts),
+            "Denylist of contracts whose logs are never decoded",
+        ),
+        param(
+            "topics",
+            "topics",
+            list(&d.topics),
+            "Extra topic0 hashes emitted as CustomTopic events",
+        ),
+        param(
+            "suspicion_min_score",
+            "uint",
+            d.suspicion_min_score,
+            "Smallest score, out of 100, at which a transaction is reported suspicious",
+        ),
+        param(
+            "victim_min_transfers",
+            "uint",
+            d.victim_min_transfers,
+            "Transfers out of one address in one transaction scored as a victim being drained",
+        ),
+        param(
+            "victim_min_units",
+            "uint",
+            d.victim_min_units,
+            "Whole tokens an address must lose, net, in a suspicious transaction to be listed as a victim",
+        ),
+        param(
+            "peer_roots",
+            "entries",
+            "",
+            "block:root entries published by another operator, checked by map_operator_divergence",
+        ),
+        param(
+            "rule_modes",
+            "entries",
+            "",
+            "event_type:mode entries, mode being enforce or shadow; rules not listed are enforced",
+        ),
+        param(
+            "routes",
+            "entries",
+            "",
+            "contract:key entries: routing key attached to events involving contract",
+        ),
+        param(
+            "tenants",
+            "entries",
+            "",
+            "id:min_severity:route:address|address... watch lists events are tagged with",
+        ),
+        param(
+            "critical_traces",
+            "bool",
+            d.critical_traces,
+            "Attach every call of the transaction to critical events as trace",
+        ),
+        ParamDescriptor {
+            choices: vec!["omit".to_string(), "log".to_string(), "full".to_string()],
+            ..param(
+                "raw_payload",
+                "enum",
+                raw_payload,
+                "Raw payload on decoded log events: omit, log or full",
+            )
+        },
+        param(
+            "blob",
+            "blob",
+            "",
+            "Base64 ParamsBlob whose params apply in its place",
+        ),
+        param(
+            "first_depositor_window_blocks",
+            "uint",
+            d.first_depositor_window_blocks,
+            "Blocks after a vault's first deposit in which diluted deposits are flagged",
+        ),
+    ]
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, Error> {
+    match value.trim() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => bail!("param {} expects true or false, got {:?}", key, other),
+    }
+}
+
+fn parse_raw_payload(key: &str, value: &str) -> Result<RawPayload, Error> {
+    match value.trim() {
+        "omit" => Ok(RawPayload::Omit),
+        "log" => Ok(RawPayload::Log),
+        "full" => Ok(RawPayload::Full),
+        other => bail!("param {} expects omit, log or full, got {:?}", key, other),
+    }
+}
+
+fn parse_rule_modes(key: &str, value: &str) -> Result<Vec<(String, RuleMode)>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let Some((rule, mode)) = entry.split_once(':') else {
+                bail!("param {} expects rule:mode entries, got {:?}", key, entry);
+            };
+            let mode = match mode.trim() {
+                "enforce" => RuleMode::Enforce,
+                "shadow" => RuleMode::Shadow,
+                other => bail!("param {} expects enforce or shadow, got {:?}", key, other),
+            };
+            Ok((rule.trim().to_string(), mode))
+        })
+        .collect()
+}
+
+fn parse_routes(key: &str, value: &str) -> Result<Vec<(String, String)>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| match entry.split_once(':') {
+            Some((contract, route)) if !route.trim().is_empty() => {
+                Ok((parse_address(key, contract)?, route.trim().to_string()))
+            }
+            _ => bail!(
+                "param {} expects contract:key entries, got {:?}",
+                key,
+                entry
+            ),
+        })
+        .collect()
+}
+
+fn parse_tenants(key: &str, value: &str) -> Result<Vec<Tenant>, Error> {
+    let mut tenants: Vec<Tenant> = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut fields = entry.splitn(4, ':').map(str::trim);
+        let (Some(id), Some(min_severity), Some(route), Some(addresses)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            bail!(
+                "param {} expects id:min_severity:route:address|address... entries, got {:?}",
+                key,
+                entry
+            );
+        };
+        if id.is_empty() {
+            bail!("param {} has a tenant without an id in {:?}", key, entry);
+        }
+        if tenants.iter().any(|tenant| tenant.id == id) {
+            bail!("param {} lists tenant {:?} twice", key, id);
+        }
+        if !routing::is_severity(min_severity) {
+            bail!(
+                "param {} expects info, medium, high or critical, got {:?}",
+                key,
+                min_severity
+            );
+        }
+        tenants.push(Tenant {
+            id: id.to_string(),
+            min_severity: min_severity.to_string(),
+            route: route.to_string(),
+            addresses: addresses
+                .split('|')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .map(|address| parse_address(key, address))
+                .collect::<Result<_, _>>()?,
+        });
+    }
+    Ok(tenants)
+}
+
+fn parse_pause_registry(key: &str, value: &str) -> Result<Vec<PauseEntry>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let parts: Vec<&str> = entry.split(':').collect();
+            let [contract, guardian, selector] = parts[..] else {
+                bail!(
+                    "param {} expects contract:guardian:selector entries, got {:?}",
+                    key,
+                    entry
+                );
+            };
+            let hex = selector.strip_prefix("0x").unwrap_or("");
+            if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                bail!(
+                    "param {} expects a 0x-prefixed 4-byte selector, got {:?}",
+                    key,
+                    selector
+                );
+            }
+            Ok(PauseEntry {
+                contract: parse_address(key, contract)?,
+                guardian: parse_address(key, guardian)?,
+                selector: selector.to_ascii_lowercase(),
+            })
+        })
+        .collect()
+}
+
+fn parse_canonical_tokens(key: &str, value: &str) -> Result<Vec<CanonicalToken>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let mut fields = entry.split(':');
+            let (Some(address), Some(symbol), Some(decimals)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                bail!(
+                    "param {} expects address:symbol:decimals[:peg] entries, got {:?}",
+                    key,
+                    entry
+                );
+            };
+            let peg = fields.next().unwrap_or_default();
+            if fields.next().is_some() {
+                bail!(
+                    "param {} expects address:symbol:decimals[:peg] entries, got {:?}",
+                    key,
+                    entry
+                );
+            }
+            let decimals: u8 = parse_number(key, decimals)?;
+            if decimals > 77 {
+                bail!("param {} expects decimals <= 77, got {}", key, decimals);
+            }
+            Ok(CanonicalToken {
+                address: parse_address(key, address)?,
+                symbol: symbol.trim().to_string(),
+                decimals,
+                peg: peg.trim().to_ascii_lowercase(),
+            })
+        })
+        .collect()
+}
+
+fn parse_token_ranges(key: &str, value: &str) -> Result<Vec<TokenRange>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let Some((collection, ids)) = entry.split_once(':') else {
+                bail!(
+                    "param {} expects collection:id or collection:first-last entries, got {:?}",
+                    key,
+                    entry
+                );
+            };
+            let (first, last) = ids.split_once('-').unwrap_or((ids, ids));
+            let id = |id: &str| {
+                U256::from_dec_str(id.trim())
+                    .map_err(|_| anyhow!("param {} expects decimal token ids, got {:?}", key, id))
+            };
+            let (first, last) = (id(first)?, id(last)?);
+            if first > last {
+                bail!("param {} has an empty token id range {:?}", key, ids);
+            }
+            Ok(TokenRange {
+                collection: parse_address(key, collection)?,
+                first,
+                last,
+            })
+        })
+        .collect()
+}
+
+fn parse_addresses(key: &str, value: &str) -> Result<Vec<String>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(|address| parse_address(key, address))
+        .collect()
+}
+
+fn parse_topics(key: &str, value: &str) -> Result<Vec<String>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|topic| {
+            let hex = topic.strip_prefix("0x").unwrap_or("");
+            if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                bail!(
+                    "param {} expects 0x-prefixed 32-byte topic hashes, got {:?}",
+                    key,
+                    topic
+                );
+            }
+            Ok(topic.to_ascii_lowercase())
+        })
+        .collect()
+}
+
+fn parse_peer_roots(key: &str, value: &str) -> Result<Vec<(u64, String)>, Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let Some((block, root)) = entry.split_once(':') else {
+                bail!("param {} expects block:root entries, got {:?}", key, entry);
+            };
+            let hex = root.strip_prefix("0x").unwrap_or("");
+            if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                bail!(
+                    "param {} expects 0x-prefixed 32-byte roots, got {:?}",
+                    key,
+                    root
+                );
+            }
+            Ok((parse_number(key, block)?, root.to_ascii_lowercase()))
+        })
+        .collect()
+}
+
+fn parse_address(key: &str, address: &str) -> Result<String, Error> {
+    let hex = address.trim().strip_prefix("0x").unwrap_or("");
+    if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!(
+            "param {} expects 0x-prefixed addresses, got {:?}",
+            key,
+            address
+        );
+    }
+    Ok(address.trim().to_ascii_lowercase())
+}
+
+fn parse_number<T: FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    value.trim().parse().map_err(|_| {
+        anyhow!(
+            "param {} expects a non-negative integer, got {:?}",
+            key,
+            value
+        )
+    })
+}
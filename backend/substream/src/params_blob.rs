@@ -0,0 +1,62 @@
+use anyhow::{anyhow, bail};
+use prost::Message;
+use substreams::errors::Error;
+
+use crate::pb::pulseproof::ParamsBlob;
+
+/// `ParamsBlob` layout this package reads.
+pub const VERSION: u32 = 1;
+
+fn sextet(c: u8) -> Option<u32> {
+    let value = match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' | b'-' => 62,
+        b'/' | b'_' => 63,
+        _ => return None,
+    };
+    Some(value as u32)
+}
+
+/// Decodes standard or URL-safe base64, padded or not.
+fn base64(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut len) = (0u32, 0);
+    for c in text.trim_end_matches('=').bytes() {
+        bits = (bits << 6) | sextet(c)?;
+        len += 6;
+        if len >= 8 {
+            len -= 8;
+            out.push((bits >> len) as u8);
+            bits &= (1 << len) - 1;
+        }
+    }
+    // A lone trailing character carries less than a byte.
+    (len < 6).then_some(out)
+}
+
+/// The `key=value` pairs a base64 `ParamsBlob` carries, in order, with list
+/// values joined by `,` as the flat format writes them.
+pub fn pairs(key: &str, value: &str) -> Result<Vec<(String, String)>, Error> {
+    let bytes = base64(value).ok_or_else(|| anyhow!("param {} is not base64", key))?;
+    let blob = ParamsBlob::decode(bytes.as_slice())
+        .map_err(|err| anyhow!("param {} is not a ParamsBlob: {}", key, err))?;
+    if blob.version != VERSION {
+        bail!(
+            "param {} has ParamsBlob version {}, this package reads {}",
+            key,
+            blob.version,
+            VERSION
+        );
+    }
+    blob.params
+        .into_iter()
+        .map(|param| {
+            if param.key == key {
+                bail!("param {} cannot hold another {}", key, key);
+            }
+            Ok((param.key, param.values.join(",")))
+        })
+        .collect()
+}
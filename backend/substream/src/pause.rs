@@ -0,0 +1,41 @@
+use crate::fingerprint;
+use crate::params::PauseEntry;
+use crate::pb::pulseproof::{ActionRecommendation, CandidateEvent, PauseAction};
+
+/// Pause actions for every registry contract a critical event touches, one
+/// recommendation per contract. An event touches a contract when it is the
+/// event's `contract_address` or is named in its `metadata` (e.g. the
+/// contracts of a `MassAdminChange`).
+pub fn recommendations(
+    event: &CandidateEvent,
+    contributing: &[&CandidateEvent],
+    registry: &[PauseEntry],
+) -> Vec<ActionRecommendation> {
+    let mut out: Vec<ActionRecommendation> = Vec::new();
+    for entry in registry {
+        let touches = event.contract_address == entry.contract
+            || event.metadata.contains(&format!("\"{}\"", entry.contract));
+        if !touches {
+            continue;
+        }
+
+        let action = PauseAction {
+            guardian: entry.guardian.clone(),
+            selector: entry.selector.clone(),
+        };
+        match out.iter_mut().find(|r| r.contract == entry.contract) {
+            Some(recommendation) => recommendation.actions.push(action),
+            None => out.push(ActionRecommendation {
+                incident_id: fingerprint::incident_id(
+                    contributing.iter().map(|e| e.fingerprint.as_str()),
+                    contributing.iter().map(|e| e.event_type.as_str()),
+                ),
+                incident_fingerprint: event.fingerprint.clone(),
+                event_type: event.event_type.clone(),
+                contract: entry.contract.clone(),
+                actions: vec![action],
+            }),
+        }
+    }
+    out
+}
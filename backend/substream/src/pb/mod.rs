@@ -35,6 +35,27 @@ pub mod sf {
             }
         }
         pub mod sink {
+            pub mod database {
+                // @@protoc_insertion_point(attribute:sf.substreams.sink.database.v1)
+                pub mod v1 {
+                    include!("sf.substreams.sink.database.v1.rs");
+                    // @@protoc_insertion_point(sf.substreams.sink.database.v1)
+                }
+            }
+            pub mod entity {
+                // @@protoc_insertion_point(attribute:sf.substreams.sink.entity.v1)
+                pub mod v1 {
+                    include!("sf.substreams.sink.entity.v1.rs");
+                    // @@protoc_insertion_point(sf.substreams.sink.entity.v1)
+                }
+            }
+            pub mod kv {
+                // @@protoc_insertion_point(attribute:sf.substreams.sink.kv.v1)
+                pub mod v1 {
+                    include!("sf.substreams.sink.kv.v1.rs");
+                    // @@protoc_insertion_point(sf.substreams.sink.kv.v1)
+                }
+            }
             pub mod service {
                 // @@protoc_insertion_point(attribute:sf.substreams.sink.service.v1)
                 pub mod v1 {
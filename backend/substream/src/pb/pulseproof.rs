@@ -13,17 +13,1165 @@ pub struct CandidateEvent {
     pub contract_address: ::prost::alloc::string::String,
     #[prost(string, tag="5")]
     pub event_signature: ::prost::alloc::string::String,
-    /// "Transfer","Approval","Swap","Permit","FlashLoan"
+    /// "Transfer","Approval","Swap","FlashLoan","ArbLoop","EoaDelegationSet","MassDistribution","DustingCampaign","AddressPoisoningCandidate","SlippageAbuseCandidate","DonationAttackCandidate","FirstDepositorExploitCandidate","ReadOnlyReentrancyCandidate","ChainAnomaly","BeaconWithdrawal","MassAdminChange","DeployerKeyCompromiseCandidate","DormantAddressActive","VestingUnlock","LockExpired","FlashMint","ExploitRehearsalCandidate","CustomTopic","DivergenceDetected","NovelInteraction","ApprovalDrainCandidate","ApprovalSpike","ReentrancySuspect","LongLivedPermit","LargeTransfer","NftDrainCandidate","NftTransfer","ApprovalForAll","StolenAssetMovement","ProxyUpgraded","AdminChanged","OwnershipTransferred","RoleGranted","RoleRevoked","ImplementationSlotChanged","TokenBoundAccountCreated","RiskyApproval","SandwichCandidate","CrossChainMessage","FailedProbe","RugPullCandidate","PrecompileAnomaly"
     #[prost(string, tag="6")]
     pub event_type: ::prost::alloc::string::String,
-    /// optional JSON string (topics, data)
+    /// optional JSON string with detector-specific details
     #[prost(string, tag="7")]
     pub metadata: ::prost::alloc::string::String,
+    /// "hot","warm","cold"
+    #[prost(string, tag="8")]
+    pub retention_class: ::prost::alloc::string::String,
+    /// Indexed parameters decoded from topics; empty when the event has none.
+    ///
+    /// Transfer, NftTransfer
+    #[prost(string, tag="9")]
+    pub from: ::prost::alloc::string::String,
+    /// Transfer, NftTransfer
+    #[prost(string, tag="10")]
+    pub to: ::prost::alloc::string::String,
+    /// Approval, ApprovalForAll
+    #[prost(string, tag="11")]
+    pub owner: ::prost::alloc::string::String,
+    /// Approval, ApprovalForAll (the operator)
+    #[prost(string, tag="12")]
+    pub spender: ::prost::alloc::string::String,
+    /// keccak256(tx hash, log index, event type); stable join key across outputs
+    #[prost(string, tag="13")]
+    pub fingerprint: ::prost::alloc::string::String,
+    /// Transfer decoded from data because from/to weren't indexed
+    #[prost(bool, tag="14")]
+    pub nonstandard_abi: bool,
+    /// Taxonomy, empty when unknown.
+    ///
+    /// "uniswap","disperse","safe",...
+    #[prost(string, tag="15")]
+    pub protocol: ::prost::alloc::string::String,
+    /// "v2",...
+    #[prost(string, tag="16")]
+    pub protocol_version: ::prost::alloc::string::String,
+    /// "ERC-20","ERC-721","ERC-1155","ERC-4626","EIP-7702"
+    #[prost(string, tag="17")]
+    pub standard: ::prost::alloc::string::String,
+    /// Annotations from data/annotations.json.
+    ///
+    /// symbol of contract_address when it is a listed token
+    #[prost(string, tag="18")]
+    pub token_symbol: ::prost::alloc::string::String,
+    /// labels of any address on the event
+    #[prost(string, repeated, tag="19")]
+    pub labels: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// tx paid the coinbase directly with zero priority fee
+    #[prost(bool, tag="20")]
+    pub likely_private: bool,
+    /// Swap: fingerprints of the Transfers into and out of the pool
+    #[prost(string, repeated, tag="21")]
+    pub leg_fingerprints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Position (block_number, tx_index, log_index, part); events are sorted by it.
+    #[prost(uint32, tag="22")]
+    pub tx_index: u32,
+    /// numbers events sharing tx_index and log_index
+    #[prost(uint32, tag="23")]
+    pub part: u32,
+    /// zero-padded "block-tx-log-part", sortable as a string
+    #[prost(string, tag="24")]
+    pub ordering_key: ::prost::alloc::string::String,
+    /// Where a log-decoded event's log was read from: "receipt", or "calls" when
+    /// the block has no receipts and logs were taken from the call traces.
+    #[prost(string, tag="25")]
+    pub source: ::prost::alloc::string::String,
+    /// "info","medium","high","critical"
+    #[prost(string, tag="26")]
+    pub severity: ::prost::alloc::string::String,
+    /// tx took a flash loan or flash mint
+    #[prost(bool, tag="27")]
+    pub flash_funded: bool,
+    /// map_attack_chains: fingerprints of the sender's earlier setup actions
+    /// (deployments, approvals, test transfers).
+    #[prost(string, repeated, tag="28")]
+    pub setup_fingerprints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Decoded parameters of the log, set for the matching event_type only.
+    #[prost(message, optional, tag="29")]
+    pub transfer: ::core::option::Option<TransferParams>,
+    /// Approval
+    #[prost(message, optional, tag="30")]
+    pub approval: ::core::option::Option<ApprovalParams>,
+    #[prost(message, optional, tag="31")]
+    pub swap: ::core::option::Option<SwapParams>,
+    #[prost(message, optional, tag="32")]
+    pub nft_transfer: ::core::option::Option<NftTransferParams>,
+    /// ApprovalForAll
+    #[prost(message, optional, tag="33")]
+    pub operator_approval: ::core::option::Option<OperatorApprovalParams>,
+    /// ProxyUpgraded, AdminChanged, OwnershipTransferred, RoleGranted,
+    /// RoleRevoked, ImplementationSlotChanged
+    #[prost(message, optional, tag="34")]
+    pub governance: ::core::option::Option<GovernanceParams>,
+    /// map_token_bound_activity: the ERC-6551 account the event involves and the
+    /// NFT controlling it.
+    #[prost(message, optional, tag="35")]
+    pub token_bound: ::core::option::Option<TokenBoundAccount>,
+    /// Raw log payload of log-decoded events, per the raw_payload param.
+    #[prost(string, repeated, tag="36")]
+    pub topics: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(bytes="vec", tag="37")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    /// raw_payload=full only
+    #[prost(bytes="vec", tag="38")]
+    pub tx_input: ::prost::alloc::vec::Vec<u8>,
+    /// Block and transaction context; tx_* are empty for events not tied to a
+    /// transaction.
+    #[prost(uint64, tag="39")]
+    pub block_timestamp: u64,
+    #[prost(string, tag="40")]
+    pub tx_from: ::prost::alloc::string::String,
+    /// empty for contract creations
+    #[prost(string, tag="41")]
+    pub tx_to: ::prost::alloc::string::String,
+    /// wei, decimal string
+    #[prost(string, tag="42")]
+    pub tx_value: ::prost::alloc::string::String,
+    #[prost(uint64, tag="43")]
+    pub tx_gas_used: u64,
+    /// Severity the detector gave an event of a rule in shadow mode (see the
+    /// rule_modes param), which is emitted at "info"; empty when enforced.
+    #[prost(string, tag="44")]
+    pub shadow_severity: ::prost::alloc::string::String,
+    /// map_sandwiches: the three transactions of the sandwich.
+    #[prost(message, optional, tag="45")]
+    pub sandwich: ::core::option::Option<SandwichCandidate>,
+    /// CrossChainMessage
+    #[prost(message, optional, tag="46")]
+    pub bridge: ::core::option::Option<BridgeMessage>,
+    /// What contract_address tracks ("usd","eth",...) when it is a listed,
+    /// pegged token, so sinks can sum value_decimal per peg.
+    #[prost(string, tag="47")]
+    pub token_peg: ::prost::alloc::string::String,
+    /// Asset contract_address stands for when it is a listed token: itself, the
+    /// token it is equivalent to (USDC.e -> USDC), or "native" for the wrapped
+    /// native token, so sinks can aggregate value across representations.
+    #[prost(string, tag="48")]
+    pub asset: ::prost::alloc::string::String,
+    /// Every call of the transaction, for critical events when the
+    /// critical_traces param is set.
+    #[prost(message, repeated, tag="49")]
+    pub trace: ::prost::alloc::vec::Vec<TraceCall>,
+    /// Gas token mints and frees, self-destructs and large gas refunds of the
+    /// transaction, on events of transactions with a high or critical event.
+    #[prost(message, optional, tag="50")]
+    pub gas_token_usage: ::core::option::Option<GasTokenUsage>,
+    /// What contract_address behaved like in the token logs store_token_shapes
+    /// counted so far: "erc20", "nft" or "nonstandard"; set by
+    /// map_enriched_events, empty elsewhere and for contracts without any.
+    #[prost(string, tag="51")]
+    pub token_class: ::prost::alloc::string::String,
+    /// Routing keys of the routes and tenants params the event involves, and
+    /// the ids of the tenants whose watch list it involves at or above their
+    /// min_severity; empty when those params are.
+    #[prost(string, repeated, tag="52")]
+    pub routing_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag="53")]
+    pub tenants: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// uint256 values are decimal strings; *_decimal applies the token's decimals
+/// and is empty when they aren't known.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransferParams {
+    #[prost(string, tag="1")]
+    pub from: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub to: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub value_decimal: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApprovalParams {
+    #[prost(string, tag="1")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub spender: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub value_decimal: ::prost::alloc::string::String,
+}
+/// ERC-721 Transfer (token id indexed) or ERC-1155 TransferSingle /
+/// TransferBatch. token_ids and amounts pair up; ERC-721 amounts are "1" and
+/// operator is empty since its logs don't name one.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NftTransferParams {
+    #[prost(string, tag="1")]
+    pub operator: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub from: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub to: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag="4")]
+    pub token_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag="5")]
+    pub amounts: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OperatorApprovalParams {
+    #[prost(string, tag="1")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub operator: ::prost::alloc::string::String,
+    #[prost(bool, tag="3")]
+    pub approved: bool,
+}
+/// previous/current: the implementation, admin or owner replaced and the one
+/// that took over (previous empty when a log doesn't name it). Role events set
+/// role (bytes32 hex), current (the account) and sender.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GovernanceParams {
+    #[prost(string, tag="1")]
+    pub previous: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub current: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub role: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub sender: ::prost::alloc::string::String,
+}
+/// A front-run and back-run by one sender around another sender's swap on the
+/// same pool, in transaction order within one block. Amounts are raw; amount_in
+/// and amount_out (and so profit) are in the token the front-run sold.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SandwichCandidate {
+    #[prost(string, tag="1")]
+    pub pool: ::prost::alloc::string::String,
+    /// sender of the front- and back-run
+    #[prost(string, tag="2")]
+    pub attacker: ::prost::alloc::string::String,
+    /// sender of the sandwiched swap
+    #[prost(string, tag="3")]
+    pub victim: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub front_run_tx: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub victim_tx: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub back_run_tx: ::prost::alloc::string::String,
+    /// front-run and victim sold token0
+    #[prost(bool, tag="7")]
+    pub zero_for_one: bool,
+    /// token the front-run sold, when a transfer shows it
+    #[prost(string, tag="8")]
+    pub token_in: ::prost::alloc::string::String,
+    #[prost(string, tag="9")]
+    pub victim_amount_in: ::prost::alloc::string::String,
+    /// front-run input
+    #[prost(string, tag="10")]
+    pub amount_in: ::prost::alloc::string::String,
+    /// back-run output
+    #[prost(string, tag="11")]
+    pub amount_out: ::prost::alloc::string::String,
+    #[prost(string, tag="12")]
+    pub profit: ::prost::alloc::string::String,
+}
+/// A message sent or delivered over a bridge. Chain ids are the protocol's own:
+/// LayerZero endpoint ids, Hyperlane domains, EVM chain ids for Polygon PoS.
+/// Accounts are 32-byte hex unless they are EVM addresses.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BridgeMessage {
+    /// "sent","received"
+    #[prost(string, tag="1")]
+    pub direction: ::prost::alloc::string::String,
+    /// LayerZero guid, Hyperlane message id, state sync id
+    #[prost(string, tag="2")]
+    pub message_id: ::prost::alloc::string::String,
+    /// 0 when the side doesn't log it
+    #[prost(uint64, tag="3")]
+    pub nonce: u64,
+    #[prost(uint64, tag="4")]
+    pub src_chain_id: u64,
+    #[prost(uint64, tag="5")]
+    pub dst_chain_id: u64,
+    /// empty when the side doesn't log it
+    #[prost(string, tag="6")]
+    pub sender: ::prost::alloc::string::String,
+    /// empty when the side doesn't log it
+    #[prost(string, tag="7")]
+    pub receiver: ::prost::alloc::string::String,
+    /// "protocol:src_chain_id:dst_chain_id:message_id", equal on both sides.
+    #[prost(string, tag="8")]
+    pub link_key: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TokenBoundAccount {
+    #[prost(string, tag="1")]
+    pub account: ::prost::alloc::string::String,
+    /// chain of the controlling NFT
+    #[prost(uint64, tag="2")]
+    pub chain_id: u64,
+    #[prost(string, tag="3")]
+    pub collection: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub token_id: ::prost::alloc::string::String,
+}
+/// Swap is classified from UniswapV2
+/// Swap(address,uint256,uint256,uint256,uint256,address).
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SwapParams {
+    #[prost(string, tag="1")]
+    pub sender: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub to: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub amount0_in: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub amount1_in: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub amount0_out: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub amount1_out: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GasTokenUsage {
+    /// known gas tokens (GST1, GST2, CHI) minted or freed
+    #[prost(string, repeated, tag="1")]
+    pub tokens: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint32, tag="2")]
+    pub mints: u32,
+    #[prost(uint32, tag="3")]
+    pub frees: u32,
+    /// non-reverted calls that self-destructed
+    #[prost(uint32, tag="4")]
+    pub self_destructs: u32,
+    /// gas refunded at the end of the transaction
+    #[prost(uint64, tag="5")]
+    pub refund: u64,
+}
+/// One call of a transaction's trace. Values are decimal strings; addresses,
+/// storage keys and storage values are hex.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TraceCall {
+    #[prost(uint32, tag="1")]
+    pub index: u32,
+    #[prost(uint32, tag="2")]
+    pub parent_index: u32,
+    #[prost(uint32, tag="3")]
+    pub depth: u32,
+    /// "CALL","CALLCODE","DELEGATE","STATIC","CREATE"
+    #[prost(string, tag="4")]
+    pub call_type: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub caller: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(string, tag="7")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(uint64, tag="8")]
+    pub gas_limit: u64,
+    #[prost(uint64, tag="9")]
+    pub gas_consumed: u64,
+    #[prost(bytes="vec", tag="10")]
+    pub input: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", tag="11")]
+    pub output: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag="12")]
+    pub status_failed: bool,
+    #[prost(bool, tag="13")]
+    pub status_reverted: bool,
+    /// reverted itself or by a parent
+    #[prost(bool, tag="14")]
+    pub state_reverted: bool,
+    #[prost(string, tag="15")]
+    pub failure_reason: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="16")]
+    pub storage_changes: ::prost::alloc::vec::Vec<TraceStorageChange>,
+    #[prost(message, repeated, tag="17")]
+    pub balance_changes: ::prost::alloc::vec::Vec<TraceBalanceChange>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TraceStorageChange {
+    #[prost(string, tag="1")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub old_value: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub new_value: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TraceBalanceChange {
+    #[prost(string, tag="1")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub old_value: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub new_value: ::prost::alloc::string::String,
+    /// the node's balance change reason, e.g. "REASON_TRANSFER"
+    #[prost(string, tag="4")]
+    pub reason: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CandidateEvents {
     #[prost(message, repeated, tag="1")]
     pub events: ::prost::alloc::vec::Vec<CandidateEvent>,
+    /// hash of the params behind this output; changes when they do
+    #[prost(string, tag="2")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BlockSummary {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(string, tag="2")]
+    pub block_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="3")]
+    pub event_count: u64,
+    /// 2048-bit logsBloom-style filter over every address in the block's events
+    #[prost(bytes="vec", tag="4")]
+    pub address_bloom: ::prost::alloc::vec::Vec<u8>,
+    /// "extended", "base", or "hybrid" (base with call traces)
+    #[prost(string, tag="5")]
+    pub detail_level: ::prost::alloc::string::String,
+    /// bitfield: 1 receipts, 2 call traces, 4 balance changes, 8 state changes
+    #[prost(uint32, tag="6")]
+    pub capabilities: u32,
+    #[prost(string, tag="7")]
+    pub config_epoch: ::prost::alloc::string::String,
+    /// events of rules in shadow mode
+    #[prost(uint64, tag="8")]
+    pub shadow_count: u64,
+    /// block time, unix seconds
+    #[prost(uint64, tag="9")]
+    pub block_timestamp: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddressIndexEntry {
+    #[prost(string, tag="1")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag="2")]
+    pub fingerprints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddressIndex {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    /// sorted by address
+    #[prost(message, repeated, tag="2")]
+    pub entries: ::prost::alloc::vec::Vec<AddressIndexEntry>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StalePermit2Allowance {
+    #[prost(string, tag="1")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub spender: ::prost::alloc::string::String,
+    /// uint160, decimal
+    #[prost(string, tag="4")]
+    pub amount: ::prost::alloc::string::String,
+    /// unix seconds
+    #[prost(uint64, tag="5")]
+    pub expiration: u64,
+    /// amount with token decimals applied; empty when decimals are unknown
+    #[prost(string, tag="6")]
+    pub amount_decimal: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StalePermit2Allowances {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    /// expired by this block but never revoked
+    #[prost(message, repeated, tag="2")]
+    pub allowances: ::prost::alloc::vec::Vec<StalePermit2Allowance>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TopicCount {
+    /// empty for anonymous logs
+    #[prost(string, tag="1")]
+    pub topic0: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub count: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DecoderMetric {
+    #[prost(string, tag="1")]
+    pub decoder: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub calls: u64,
+    /// 0 inside the WASM runtime, which has no clock
+    #[prost(uint64, tag="3")]
+    pub nanos: u64,
+    #[prost(uint64, tag="4")]
+    pub allocations: u64,
+    #[prost(uint64, tag="5")]
+    pub allocated_bytes: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DecoderMetrics {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    /// empty unless built with the metrics feature
+    #[prost(message, repeated, tag="2")]
+    pub metrics: ::prost::alloc::vec::Vec<DecoderMetric>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActivitySpike {
+    #[prost(string, tag="1")]
+    pub contract: ::prost::alloc::string::String,
+    /// "transfer","approval","swap" (by emitting contract), "transfer_out" (by sender)
+    #[prost(string, tag="2")]
+    pub kind: ::prost::alloc::string::String,
+    /// this block
+    #[prost(uint64, tag="3")]
+    pub count: u64,
+    /// earlier in the baseline window
+    #[prost(uint64, tag="4")]
+    pub baseline_count: u64,
+    /// blocks of the baseline window before this one
+    #[prost(uint64, tag="5")]
+    pub baseline_blocks: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActivitySpikes {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub spikes: ::prost::alloc::vec::Vec<ActivitySpike>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+/// A successful contract call that used far more gas than the contract's
+/// earlier callers did on average.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GasAnomaly {
+    #[prost(string, tag="1")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    /// the transaction's recipient
+    #[prost(string, tag="2")]
+    pub contract: ::prost::alloc::string::String,
+    /// transaction sender
+    #[prost(string, tag="3")]
+    pub from: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub gas_used: u64,
+    /// of the contract's earlier transactions
+    #[prost(uint64, tag="5")]
+    pub average_gas: u64,
+    /// earlier transactions averaged
+    #[prost(uint64, tag="6")]
+    pub samples: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GasAnomalies {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub anomalies: ::prost::alloc::vec::Vec<GasAnomaly>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+/// A contract deployed in the block and a call to a watched contract, by the
+/// new contract or in a transaction of its deployer, from the deployment on.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FreshContractInteraction {
+    #[prost(string, tag="1")]
+    pub deployer: ::prost::alloc::string::String,
+    /// deployed in the block
+    #[prost(string, tag="2")]
+    pub contract: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub deploy_tx: ::prost::alloc::string::String,
+    /// of the interaction
+    #[prost(string, tag="4")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    /// watched contract called
+    #[prost(string, tag="5")]
+    pub protocol: ::prost::alloc::string::String,
+    #[prost(uint32, tag="6")]
+    pub call_index: u32,
+    /// deployed and called in one transaction
+    #[prost(bool, tag="7")]
+    pub same_transaction: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FreshContractInteractions {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub interactions: ::prost::alloc::vec::Vec<FreshContractInteraction>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RuleCardinality {
+    /// event type
+    #[prost(string, tag="1")]
+    pub rule: ::prost::alloc::string::String,
+    /// distinct contract_address values
+    #[prost(uint64, tag="2")]
+    pub contracts: u64,
+    /// distinct tx_from values
+    #[prost(uint64, tag="3")]
+    pub actors: u64,
+}
+/// Empty except on the first block of each rule_metrics_blocks window, which
+/// reports the window before it.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RuleCardinalities {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    /// first block of the reported window
+    #[prost(uint64, tag="2")]
+    pub window_start: u64,
+    #[prost(message, repeated, tag="3")]
+    pub rules: ::prost::alloc::vec::Vec<RuleCardinality>,
+    #[prost(string, tag="4")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContractTopicCounts {
+    #[prost(string, tag="1")]
+    pub contract: ::prost::alloc::string::String,
+    /// most frequent unmatched topic0s, descending
+    #[prost(message, repeated, tag="2")]
+    pub topics: ::prost::alloc::vec::Vec<TopicCount>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnknownTopicDigest {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    /// unix seconds; one digest per week of block time
+    #[prost(uint64, tag="2")]
+    pub period_start: u64,
+    /// watched contracts with unmatched logs
+    #[prost(message, repeated, tag="3")]
+    pub contracts: ::prost::alloc::vec::Vec<ContractTopicCounts>,
+    #[prost(string, tag="4")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PauseAction {
+    /// contract to call
+    #[prost(string, tag="1")]
+    pub guardian: ::prost::alloc::string::String,
+    /// 0x-prefixed 4-byte pause selector
+    #[prost(string, tag="2")]
+    pub selector: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActionRecommendation {
+    /// fingerprint of the critical event
+    #[prost(string, tag="1")]
+    pub incident_fingerprint: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub event_type: ::prost::alloc::string::String,
+    /// registry contract the incident touches
+    #[prost(string, tag="3")]
+    pub contract: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="4")]
+    pub actions: ::prost::alloc::vec::Vec<PauseAction>,
+    /// see EvidenceBundle.incident_id
+    #[prost(string, tag="5")]
+    pub incident_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActionRecommendations {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub recommendations: ::prost::alloc::vec::Vec<ActionRecommendation>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+/// A transaction whose events together score as suspicious; see
+/// map_suspicious_transactions for the heuristics behind reasons.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SuspiciousTransaction {
+    #[prost(string, tag="1")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub block_number: u64,
+    /// 0-100
+    #[prost(uint32, tag="3")]
+    pub score: u32,
+    /// heuristics that added to the score
+    #[prost(string, repeated, tag="4")]
+    pub reasons: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// every event of the transaction
+    #[prost(message, repeated, tag="5")]
+    pub contributing: ::prost::alloc::vec::Vec<CandidateEvent>,
+    /// Addresses whose token transfers in the transaction leave them net down
+    /// at least victim_min_units of some token.
+    #[prost(string, repeated, tag="6")]
+    pub victims: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SuspiciousTransactions {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub transactions: ::prost::alloc::vec::Vec<SuspiciousTransaction>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RawLog {
+    #[prost(uint32, tag="1")]
+    pub log_index: u32,
+    #[prost(string, tag="2")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag="3")]
+    pub topics: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag="4")]
+    pub data: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TxMetadata {
+    #[prost(string, tag="1")]
+    pub hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub from: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub to: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub nonce: u64,
+    /// wei, decimal string
+    #[prost(string, tag="5")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub input: ::prost::alloc::string::String,
+    #[prost(uint64, tag="7")]
+    pub gas_used: u64,
+    /// e.g. "SUCCEEDED", "REVERTED"
+    #[prost(string, tag="8")]
+    pub status: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RuleProvenance {
+    /// event_type of the detection
+    #[prost(string, tag="1")]
+    pub rule: ::prost::alloc::string::String,
+    /// module that produced it
+    #[prost(string, tag="2")]
+    pub module: ::prost::alloc::string::String,
+    /// package version of the rules
+    #[prost(string, tag="3")]
+    pub version: ::prost::alloc::string::String,
+}
+/// Everything needed to re-check an incident without the chain: the rules in
+/// map_candidate_events read no stores, so the transaction is the whole input.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EvidenceBundle {
+    #[prost(string, tag="1")]
+    pub incident_fingerprint: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="2")]
+    pub incident: ::core::option::Option<CandidateEvent>,
+    /// every event of the transaction
+    #[prost(message, repeated, tag="3")]
+    pub contributing: ::prost::alloc::vec::Vec<CandidateEvent>,
+    #[prost(message, repeated, tag="4")]
+    pub raw_logs: ::prost::alloc::vec::Vec<RawLog>,
+    #[prost(message, optional, tag="5")]
+    pub tx: ::core::option::Option<TxMetadata>,
+    #[prost(message, optional, tag="6")]
+    pub rule: ::core::option::Option<RuleProvenance>,
+    /// keccak256 over the sorted contributing fingerprints and rule ids; equal
+    /// across operators running the same package.
+    #[prost(string, tag="7")]
+    pub incident_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EvidenceBundles {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub bundles: ::prost::alloc::vec::Vec<EvidenceBundle>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+/// Header fields a light client needs to check the block is canonical and
+/// that a receipt belongs to it (via receipts_root).
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifiableHeader {
+    #[prost(uint64, tag="1")]
+    pub number: u64,
+    #[prost(string, tag="2")]
+    pub hash: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub parent_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub state_root: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub transactions_root: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub receipts_root: ::prost::alloc::string::String,
+    #[prost(uint64, tag="7")]
+    pub timestamp: u64,
+}
+/// The receipt an alert came from, with its receipts-trie position
+/// (key rlp(tx_index)) and every consensus field of the receipt, so the leaf
+/// can be re-encoded and checked against a Merkle-Patricia proof.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReceiptPosition {
+    #[prost(uint32, tag="1")]
+    pub tx_index: u32,
+    #[prost(string, tag="2")]
+    pub tx_hash: ::prost::alloc::string::String,
+    /// EIP-2718 type byte
+    #[prost(uint32, tag="3")]
+    pub tx_type: u32,
+    #[prost(bool, tag="4")]
+    pub succeeded: bool,
+    #[prost(uint64, tag="5")]
+    pub cumulative_gas_used: u64,
+    #[prost(string, tag="6")]
+    pub logs_bloom: ::prost::alloc::string::String,
+    /// pre-Byzantium receipts only
+    #[prost(string, tag="7")]
+    pub post_state: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="8")]
+    pub logs: ::prost::alloc::vec::Vec<RawLog>,
+    /// the alert's log_index names a log of its contract
+    #[prost(bool, tag="9")]
+    pub log_matched: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifiableAlert {
+    #[prost(message, optional, tag="1")]
+    pub alert: ::core::option::Option<CandidateEvent>,
+    #[prost(message, optional, tag="2")]
+    pub header: ::core::option::Option<VerifiableHeader>,
+    #[prost(message, optional, tag="3")]
+    pub receipt: ::core::option::Option<ReceiptPosition>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifiableAlerts {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub alerts: ::prost::alloc::vec::Vec<VerifiableAlert>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+/// One level of an inclusion proof: hash the node so far with `sibling`, on
+/// the left when `left` is set.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProofStep {
+    #[prost(string, tag="1")]
+    pub sibling: ::prost::alloc::string::String,
+    #[prost(bool, tag="2")]
+    pub left: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventProof {
+    /// position of the event in the block's output
+    #[prost(uint32, tag="1")]
+    pub index: u32,
+    #[prost(string, tag="2")]
+    pub fingerprint: ::prost::alloc::string::String,
+    /// keccak256 of the event's protobuf encoding
+    #[prost(string, tag="3")]
+    pub leaf: ::prost::alloc::string::String,
+    /// leaf to events_root
+    #[prost(message, repeated, tag="4")]
+    pub path: ::prost::alloc::vec::Vec<ProofStep>,
+}
+/// Commitment of a block's candidate events to anchor on-chain. events_root
+/// is the Merkle root over the event leaves in output order; root binds it to
+/// the block as keccak256(uint64 block_number ‖ block_hash ‖ events_root).
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BlockCommitment {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(string, tag="2")]
+    pub block_hash: ::prost::alloc::string::String,
+    /// zero when the block has no events
+    #[prost(string, tag="3")]
+    pub events_root: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub root: ::prost::alloc::string::String,
+    #[prost(uint32, tag="5")]
+    pub leaf_count: u32,
+    #[prost(message, repeated, tag="6")]
+    pub proofs: ::prost::alloc::vec::Vec<EventProof>,
+    #[prost(string, tag="7")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+/// Shape of a flagged transaction's call tree, for triage without the full
+/// trace.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CallTreeSummary {
+    #[prost(string, tag="1")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    /// event types that flagged it
+    #[prost(string, repeated, tag="2")]
+    pub flagged_by: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// 0 for the top-level call alone
+    #[prost(uint32, tag="3")]
+    pub max_depth: u32,
+    #[prost(uint32, tag="4")]
+    pub call_count: u32,
+    /// distinct callees
+    #[prost(uint32, tag="5")]
+    pub unique_contracts: u32,
+    /// Largest native value a non-reverted call moved, per depth (index).
+    #[prost(string, repeated, tag="6")]
+    pub max_value_by_depth: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Selectors from the top-level call to the deepest call that moved value
+    /// ("0x" for plain transfers); empty when no call moved value.
+    #[prost(string, repeated, tag="7")]
+    pub value_path: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CallTreeSummaries {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub summaries: ::prost::alloc::vec::Vec<CallTreeSummary>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Coverage {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(uint64, tag="2")]
+    pub logs_seen: u64,
+    /// classified into a CandidateEvent
+    #[prost(uint64, tag="3")]
+    pub logs_decoded: u64,
+    #[prost(uint64, tag="4")]
+    pub logs_dropped: u64,
+    /// most frequent dropped topic0s, descending
+    #[prost(message, repeated, tag="5")]
+    pub top_unknown_topics: ::prost::alloc::vec::Vec<TopicCount>,
+    #[prost(string, tag="6")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NftHolderSnapshot {
+    #[prost(string, tag="1")]
+    pub collection: ::prost::alloc::string::String,
+    /// addresses with a nonzero balance
+    #[prost(uint64, tag="2")]
+    pub holders: u64,
+    /// tokens held, ERC-1155 amounts included
+    #[prost(string, tag="3")]
+    pub supply: ::prost::alloc::string::String,
+    /// Herfindahl index of holder shares; 10000 = one holder
+    #[prost(uint32, tag="4")]
+    pub concentration_bps: u32,
+}
+/// Empty except every nft_snapshot_blocks blocks.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NftHolderSnapshots {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub collections: ::prost::alloc::vec::Vec<NftHolderSnapshot>,
+    #[prost(string, tag="3")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnumEntry {
+    /// value as it appears in the output
+    #[prost(string, tag="1")]
+    pub code: ::prost::alloc::string::String,
+    /// canonical English label
+    #[prost(string, tag="2")]
+    pub label: ::prost::alloc::string::String,
+    /// for event types, also the rule's description
+    #[prost(string, tag="3")]
+    pub description: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnumSet {
+    /// output field the codes appear in, e.g. "event_type"
+    #[prost(string, tag="1")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub entries: ::prost::alloc::vec::Vec<EnumEntry>,
+}
+/// Empty except every dictionary_blocks blocks.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnumDictionary {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    /// package version the labels belong to
+    #[prost(string, tag="2")]
+    pub version: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="3")]
+    pub sets: ::prost::alloc::vec::Vec<EnumSet>,
+    #[prost(string, tag="4")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ParamDescriptor {
+    /// key as passed in the module's params
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    /// "bool", "uint", "enum", "addresses", "topics", "entries" or "blob"
+    #[prost(string, tag="2")]
+    pub kind: ::prost::alloc::string::String,
+    /// as it would be passed; empty for an empty list
+    #[prost(string, tag="3")]
+    pub default_value: ::prost::alloc::string::String,
+    /// values an "enum" param accepts
+    #[prost(string, repeated, tag="4")]
+    pub choices: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint64, tag="5")]
+    pub minimum: u64,
+    /// 0 when unbounded
+    #[prost(uint64, tag="6")]
+    pub maximum: u64,
+    #[prost(string, tag="7")]
+    pub description: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RuleDescriptor {
+    /// event type, the key rule_modes takes
+    #[prost(string, tag="1")]
+    pub rule: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub label: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub description: ::prost::alloc::string::String,
+    /// severity when the detector sets none
+    #[prost(string, tag="4")]
+    pub default_severity: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub retention_class: ::prost::alloc::string::String,
+}
+/// Empty except every dictionary_blocks blocks.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConfigDescriptor {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    /// package version the descriptor belongs to
+    #[prost(string, tag="2")]
+    pub version: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="3")]
+    pub params: ::prost::alloc::vec::Vec<ParamDescriptor>,
+    #[prost(message, repeated, tag="4")]
+    pub rules: ::prost::alloc::vec::Vec<RuleDescriptor>,
+    #[prost(message, repeated, tag="5")]
+    pub enums: ::prost::alloc::vec::Vec<EnumSet>,
+    #[prost(string, tag="6")]
+    pub config_epoch: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ParamValue {
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    /// list entries; a single one for scalar params
+    #[prost(string, repeated, tag="2")]
+    pub values: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// The params of a module as one versioned protobuf, passed base64-encoded as
+/// the blob param.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ParamsBlob {
+    /// layout version, 1
+    #[prost(uint32, tag="1")]
+    pub version: u32,
+    /// applied in order, like key=value pairs
+    #[prost(message, repeated, tag="2")]
+    pub params: ::prost::alloc::vec::Vec<ParamValue>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutputMetric {
+    #[prost(string, tag="1")]
+    pub module: ::prost::alloc::string::String,
+    /// fully-qualified protobuf type of the output
+    #[prost(string, tag="2")]
+    pub output_type: ::prost::alloc::string::String,
+    /// encoded size of the module's output for the block
+    #[prost(uint64, tag="3")]
+    pub bytes: u64,
+    /// events, entity, table or KV changes; 1 for one-record outputs
+    #[prost(uint64, tag="4")]
+    pub items: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutputMetrics {
+    #[prost(uint64, tag="1")]
+    pub block_number: u64,
+    #[prost(message, repeated, tag="2")]
+    pub outputs: ::prost::alloc::vec::Vec<OutputMetric>,
+    /// sum of the outputs' bytes
+    #[prost(uint64, tag="3")]
+    pub total_bytes: u64,
+    #[prost(string, tag="4")]
+    pub config_epoch: ::prost::alloc::string::String,
 }
 // @@protoc_insertion_point(module)
@@ -0,0 +1,218 @@
+use substreams::store::{StoreGet, StoreGetString};
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::StalePermit2Allowance;
+use crate::retention::Lists;
+use crate::u256::U256;
+use crate::{abi, amount, calls};
+
+// Permit2 is deployed at the same address on every chain.
+const PERMIT2_ADDRESS: [u8; 20] = hex_literal::hex!("000000000022d473030f116ddee9f6b43ac78ba3");
+
+// Approval(address,address,address,uint160,uint48)
+const APPROVAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("da9fa7c1b00402c17d0161b249b1ab8bbec047c5a52207b9c112deffd817036b");
+// Permit(address,address,address,uint160,uint48,uint48)
+const PERMIT_TOPIC: [u8; 32] =
+    hex_literal::hex!("c6a377bfc4eb120024a8ac08eef205be16b817020812c73223e81d1bdb9708ec");
+// Lockdown(address,address,address)
+const LOCKDOWN_TOPIC: [u8; 32] =
+    hex_literal::hex!("89b1add15eff56b3dfe299ad94e01f2b52fbcb80ae1a3baea6ae8c04cb2b98a4");
+
+/// Expirations are indexed in hourly buckets so the stale-allowance module
+/// only has to look up the buckets a block's timestamp moved past.
+pub const EXPIRY_BUCKET_SECONDS: u64 = 3600;
+// Buckets below the current one dropped whenever the timestamp enters a new
+// bucket; a halt longer than this leaves the buckets it skipped behind.
+const RETAINED_BUCKETS: u64 = 24;
+
+pub enum Change {
+    Set {
+        key: String,
+        amount: U256,
+        expiration: u64,
+    },
+    Revoke {
+        key: String,
+    },
+}
+
+pub struct Allowance {
+    pub owner: String,
+    pub token: String,
+    pub spender: String,
+    pub amount: U256,
+    pub expiration: u64,
+}
+
+/// Store key for an allowance: `owner:token:spender`.
+pub fn allowance_key(owner: &str, token: &str, spender: &str) -> String {
+    format!("{}:{}:{}", owner, token, spender)
+}
+
+/// Key of the list of allowance keys expiring in one bucket. It ends in `:`
+/// so deleting it as a prefix drops that bucket alone.
+pub fn expiry_key(bucket: u64) -> String {
+    format!("expiry:{}:", bucket)
+}
+
+/// The allowance keys the block's Permit2 logs set, as `(ordinal,
+/// expiry_key, allowance key)`, to add to their expiration's bucket.
+pub fn expiry_entries(blk: &eth::Block, block_timestamp: u64) -> Vec<(u64, String, String)> {
+    calls::block_logs(blk)
+        .filter_map(|(_, log)| match change(&log, block_timestamp)? {
+            Change::Set {
+                key, expiration, ..
+            } => Some((
+                log.ordinal,
+                expiry_key(expiration / EXPIRY_BUCKET_SECONDS),
+                key,
+            )),
+            Change::Revoke { .. } => None,
+        })
+        .collect()
+}
+
+/// Buckets to drop when the timestamp moves from `previous` to `current`:
+/// on entering a new bucket, those below the one `previous` fell in, which
+/// `stale_allowances` visited at earlier blocks.
+pub fn expired_buckets(previous: u64, current: u64) -> std::ops::Range<u64> {
+    let (from, to) = (
+        previous / EXPIRY_BUCKET_SECONDS,
+        current / EXPIRY_BUCKET_SECONDS,
+    );
+    if previous == 0 || from == to {
+        return 0..0;
+    }
+    from.saturating_sub(RETAINED_BUCKETS)..from
+}
+
+/// Store value for an allowance: `amount:expiration`.
+pub fn encode_allowance(amount: &U256, expiration: u64) -> String {
+    format!("{}:{}", amount, expiration)
+}
+
+pub fn decode_allowance(key: &str, value: &str) -> Option<Allowance> {
+    let mut key_parts = key.split(':');
+    let (owner, token, spender) = (key_parts.next()?, key_parts.next()?, key_parts.next()?);
+    let (amount, expiration) = value.split_once(':')?;
+
+    Some(Allowance {
+        owner: owner.to_string(),
+        token: token.to_string(),
+        spender: spender.to_string(),
+        amount: U256::from_dec_str(amount).ok()?,
+        expiration: expiration.parse().ok()?,
+    })
+}
+
+/// Decodes the allowance changes in one Permit2 log. Permit2 stores an
+/// expiration of 0 as "expires this block", so those are resolved to the
+/// block timestamp here.
+pub fn change(log: &eth::Log, block_timestamp: u64) -> Option<Change> {
+    if log.address != PERMIT2_ADDRESS {
+        return None;
+    }
+    let topic0 = log.topics.first()?.as_slice();
+
+    if topic0 == APPROVAL_TOPIC || topic0 == PERMIT_TOPIC {
+        let key = allowance_key(
+            &abi::topic_address(log, 1)?,
+            &abi::topic_address(log, 2)?,
+            &abi::topic_address(log, 3)?,
+        );
+        let amount = abi::word_u256(&log.data, 0)?;
+        let expiration = u64::try_from(abi::word_u256(&log.data, 1)?).ok()?;
+        let expiration = if expiration == 0 {
+            block_timestamp
+        } else {
+            expiration
+        };
+
+        if amount.is_zero() {
+            return Some(Change::Revoke { key });
+        }
+        return Some(Change::Set {
+            key,
+            amount,
+            expiration,
+        });
+    }
+
+    if topic0 == LOCKDOWN_TOPIC {
+        let owner = abi::topic_address(log, 1)?;
+        let token = format!("0x{}", hex::encode(log.data.get(12..32)?));
+        let spender = format!("0x{}", hex::encode(log.data.get(44..64)?));
+        return Some(Change::Revoke {
+            key: allowance_key(&owner, &token, &spender),
+        });
+    }
+
+    None
+}
+
+/// A signed Permit2 permit as `Allowance`. An expiration of 0 (this block) is
+/// returned as is.
+pub fn permit(log: &eth::Log) -> Option<Allowance> {
+    if log.address != PERMIT2_ADDRESS || log.topics.first()?.as_slice() != PERMIT_TOPIC {
+        return None;
+    }
+    Some(Allowance {
+        owner: abi::topic_address(log, 1)?,
+        token: abi::topic_address(log, 2)?,
+        spender: abi::topic_address(log, 3)?,
+        amount: abi::word_u256(&log.data, 0)?,
+        expiration: u64::try_from(abi::word_u256(&log.data, 1)?).ok()?,
+    })
+}
+
+/// Collects allowances that expired between two consecutive block timestamps.
+/// Every bucket whose whole hour lies in `[previous, current)` has fully
+/// expired, so each bucket is visited by exactly one block.
+pub fn stale_allowances(
+    previous: u64,
+    current: u64,
+    expiries: &Lists,
+    allowances: &StoreGetString,
+    annotations: &Annotations,
+) -> Vec<StalePermit2Allowance> {
+    let mut stale = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+
+    for bucket in (previous / EXPIRY_BUCKET_SECONDS)..(current / EXPIRY_BUCKET_SECONDS) {
+        let Some(keys) = expiries.get(&expiry_key(bucket)) else {
+            continue;
+        };
+        for key in keys.split(';').filter(|k| !k.is_empty()) {
+            if seen.iter().any(|s| s == key) {
+                continue;
+            }
+            seen.push(key.to_string());
+
+            let Some(allowance) = allowances
+                .get_last(key)
+                .and_then(|value| decode_allowance(key, &value))
+            else {
+                continue;
+            };
+            // Renewed allowances carry a later expiration and are not stale yet.
+            if allowance.expiration > current {
+                continue;
+            }
+            let amount_decimal = amount::token_decimals(annotations, &allowance.token)
+                .map(|decimals| amount::to_decimal(allowance.amount, decimals))
+                .unwrap_or_default();
+            stale.push(StalePermit2Allowance {
+                owner: allowance.owner,
+                token: allowance.token,
+                spender: allowance.spender,
+                amount: allowance.amount.to_string(),
+                expiration: allowance.expiration,
+                amount_decimal,
+            });
+        }
+    }
+
+    stale
+}
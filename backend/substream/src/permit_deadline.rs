@@ -0,0 +1,128 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::u256::U256;
+use crate::{abi, calls, permit2};
+
+// EIP-2612 permit(address,address,uint256,uint256,uint8,bytes32,bytes32)
+const EIP2612_PERMIT: [u8; 4] = hex_literal::hex!("d505accf");
+// DAI permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)
+const DAI_PERMIT: [u8; 4] = hex_literal::hex!("8fcbaf0c");
+
+pub struct Permit {
+    pub index: u64,
+    /// `"eip2612"`, `"dai"` or `"permit2"`.
+    pub kind: &'static str,
+    pub token: String,
+    pub owner: String,
+    pub spender: String,
+    /// Allowance granted; DAI permits grant the maximum.
+    pub value: U256,
+    /// `None` when the permit never expires.
+    pub deadline: Option<U256>,
+}
+
+/// Permits a successful transaction used: EIP-2612 and DAI `permit` calls
+/// from the call frames, with the call index as `index`, and Permit2
+/// `Permit` logs, with the log index.
+pub fn permits(tx: &eth::TransactionTrace, logs: &[eth::Log]) -> Vec<Permit> {
+    if tx.status != eth::TransactionTraceStatus::Succeeded as i32 {
+        return Vec::new();
+    }
+
+    let mut permits: Vec<Permit> = calls::frames(tx)
+        .into_iter()
+        .filter_map(|(index, _, token, input)| decode(index, token, input))
+        .collect();
+    permits.extend(logs.iter().filter_map(|log| {
+        let allowance = permit2::permit(log)?;
+        Some(Permit {
+            index: log.index as u64,
+            kind: "permit2",
+            token: allowance.token,
+            owner: allowance.owner,
+            spender: allowance.spender,
+            value: allowance.amount,
+            deadline: Some(U256::from(allowance.expiration)),
+        })
+    }));
+    permits
+}
+
+/// Flags permits whose deadline lies more than `max_lifetime_seconds` after
+/// the block (or never comes) and whose spender is neither labelled nor a
+/// registered protocol: a signature or allowance that stays usable long after
+/// the owner has forgotten it. `log_index` carries the permit's `index`.
+pub fn long_lived_permits(
+    tx: &eth::TransactionTrace,
+    logs: &[eth::Log],
+    block_number: u64,
+    block_timestamp: u64,
+    max_lifetime_seconds: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let horizon = U256::from(block_timestamp.saturating_add(max_lifetime_seconds));
+    permits(tx, logs)
+        .into_iter()
+        .filter(|p| p.deadline.is_none_or(|deadline| deadline > horizon))
+        .filter(|p| {
+            annotations.labels(&p.spender).is_empty() && annotations.protocol(&p.spender).is_none()
+        })
+        .map(|p| {
+            // Deadlines past u64 seconds are as good as never.
+            let lifetime = p
+                .deadline
+                .and_then(|deadline| u64::try_from(deadline).ok())
+                .map(|deadline| deadline.saturating_sub(block_timestamp).to_string())
+                .unwrap_or_else(|| "null".to_string());
+            CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                block_number,
+                log_index: p.index,
+                contract_address: p.token,
+                event_type: "LongLivedPermit".to_string(),
+                owner: p.owner,
+                spender: p.spender,
+                metadata: format!(
+                    "{{\"kind\":\"{}\",\"deadline\":{},\"lifetime_seconds\":{}}}",
+                    p.kind,
+                    p.deadline
+                        .map(|d| format!("\"{}\"", d))
+                        .unwrap_or_else(|| "null".to_string()),
+                    lifetime
+                ),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn decode(index: u32, token: &[u8], input: &[u8]) -> Option<Permit> {
+    let (selector, args) = (input.get(..4)?, input.get(4..)?);
+    let (kind, value, deadline) = if selector == EIP2612_PERMIT {
+        (
+            "eip2612",
+            abi::word_u256(args, 2)?,
+            Some(abi::word_u256(args, 3)?),
+        )
+    } else if selector == DAI_PERMIT {
+        // `allowed == false` revokes; an expiry of 0 never expires.
+        if abi::word_u256(args, 4)?.is_zero() {
+            return None;
+        }
+        let expiry = abi::word_u256(args, 3)?;
+        ("dai", U256::MAX, (!expiry.is_zero()).then_some(expiry))
+    } else {
+        return None;
+    };
+    Some(Permit {
+        index: index as u64,
+        kind,
+        token: format!("0x{}", hex::encode(token)),
+        owner: abi::word_address(args, 0)?,
+        spender: abi::word_address(args, 1)?,
+        value,
+        deadline,
+    })
+}
@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy;
+use crate::u256::U256;
+use crate::{abi, amount, calls};
+
+// Wallets typically abbreviate addresses to 0x1234…abcd, which is exactly the
+// part poisoners grind a vanity address to match.
+const VISIBLE_CHARS: usize = 4;
+
+pub struct Transfer<'a> {
+    pub tx_hash: &'a [u8],
+    pub log_index: u32,
+    pub ordinal: u64,
+    pub token: Cow<'a, [u8]>,
+    pub from: String,
+    pub to: String,
+    pub amount: U256,
+}
+
+/// ERC20 transfers in the block, with addresses hex-encoded.
+pub fn transfers(blk: &eth::Block) -> Vec<Transfer<'_>> {
+    let mut out = Vec::new();
+    for (tx, log) in calls::block_logs(blk) {
+        let Some(transfer) = abi::transfer(&log) else {
+            continue;
+        };
+        let from = format!("0x{}", hex::encode(transfer.from));
+        let to = format!("0x{}", hex::encode(transfer.to));
+        let amount = transfer.amount;
+        out.push(Transfer {
+            tx_hash: &tx.hash,
+            log_index: log.index,
+            ordinal: log.ordinal,
+            token: match log {
+                Cow::Borrowed(log) => Cow::Borrowed(&log.address[..]),
+                Cow::Owned(log) => Cow::Owned(log.address),
+            },
+            from,
+            to,
+            amount,
+        });
+    }
+    out
+}
+
+/// Prefix of the counterparties filed in one retention window.
+pub fn window_prefix(window: u64) -> String {
+    format!("lookalike:{}:", window)
+}
+
+/// Store key under which `owner`'s real counterparties of one window are
+/// filed by their visible prefix and suffix:
+/// `lookalike:{window}:{owner}:{prefix}{suffix}`.
+pub fn lookalike_key(window: u64, owner: &str, counterparty: &str) -> String {
+    let hex = counterparty.trim_start_matches("0x");
+    let prefix = &hex[..VISIBLE_CHARS.min(hex.len())];
+    let suffix = &hex[hex.len().saturating_sub(VISIBLE_CHARS)..];
+    format!("{}{}:{}{}", window_prefix(window), owner, prefix, suffix)
+}
+
+pub fn candidate(
+    transfer: &Transfer,
+    block_number: u64,
+    victim: &str,
+    lookalike: &str,
+    impersonated: &str,
+    annotations: &Annotations,
+) -> CandidateEvent {
+    let token = format!("0x{}", hex::encode(&transfer.token));
+    CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(transfer.tx_hash)),
+        block_number,
+        log_index: transfer.log_index as u64,
+        event_type: "AddressPoisoningCandidate".to_string(),
+        from: transfer.from.clone(),
+        to: transfer.to.clone(),
+        metadata: format!(
+            "{{\"victim\":\"{}\",\"lookalike\":\"{}\",\"impersonated\":\"{}\",{}}}",
+            victim,
+            lookalike,
+            impersonated,
+            amount::json_fields(annotations, "amount", transfer.amount, &token)
+        ),
+        contract_address: token,
+        standard: taxonomy::ERC20.to_string(),
+        ..Default::default()
+    }
+}
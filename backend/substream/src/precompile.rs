@@ -0,0 +1,84 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CandidateEvent;
+
+// Precompiles at 0x01..0x0a, by the last byte of their address.
+const PRECOMPILES: [&str; 10] = [
+    "ecrecover",
+    "sha256",
+    "ripemd160",
+    "identity",
+    "modexp",
+    "bn256_add",
+    "bn256_mul",
+    "bn256_pairing",
+    "blake2f",
+    "point_evaluation",
+];
+// Compilers route memory copies through identity, so its counts say nothing.
+const IDENTITY: usize = 3;
+
+/// Calls a transaction made to one precompile.
+#[derive(Clone, Copy, Default)]
+struct Usage {
+    calls: u64,
+    gas: u64,
+    first_call: Option<u32>,
+}
+
+fn precompile(address: &[u8]) -> Option<usize> {
+    let (last, rest) = address.split_last()?;
+    (address.len() == 20 && rest.iter().all(|b| *b == 0) && (1..=10).contains(last))
+        .then(|| *last as usize - 1)
+}
+
+/// Flags a transaction calling one precompile at least `min_calls` times,
+/// e.g. thousands of ecrecovers when forging or brute-forcing signatures, or
+/// modexp and pairing loops burning gas to grief. One `PrecompileAnomaly` per
+/// transaction, for its most-called precompile; `log_index` is the index of
+/// the first call to it. `metadata` has the `precompile`, its `calls` and
+/// `gas` consumed, and the calls per precompile. Identity is never flagged.
+/// Needs call traces.
+pub fn anomalies(
+    tx: &eth::TransactionTrace,
+    block_number: u64,
+    min_calls: u64,
+) -> Vec<CandidateEvent> {
+    let mut usage = [Usage::default(); PRECOMPILES.len()];
+    for call in tx.calls.iter() {
+        let Some(usage) = precompile(&call.address).and_then(|i| usage.get_mut(i)) else {
+            continue;
+        };
+        usage.calls += 1;
+        usage.gas += call.gas_consumed;
+        usage.first_call.get_or_insert(call.index);
+    }
+
+    let used = || PRECOMPILES.iter().zip(usage.iter()).enumerate();
+    let Some((top, (name, top_usage))) = used()
+        .filter(|&(i, (_, usage))| i != IDENTITY && usage.calls >= min_calls.max(1))
+        .max_by_key(|&(i, (_, usage))| (usage.calls, std::cmp::Reverse(i)))
+    else {
+        return Vec::new();
+    };
+    let counts: Vec<String> = used()
+        .filter(|(_, (_, usage))| usage.calls > 0)
+        .map(|(_, (name, usage))| format!("\"{}\":{}", name, usage.calls))
+        .collect();
+    vec![CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+        block_number,
+        log_index: top_usage.first_call.unwrap_or_default() as u64,
+        contract_address: format!("0x{:040x}", top + 1),
+        event_type: "PrecompileAnomaly".to_string(),
+        to: format!("0x{}", hex::encode(&tx.to)),
+        metadata: format!(
+            "{{\"precompile\":\"{}\",\"calls\":{},\"gas\":{},\"counts\":{{{}}}}}",
+            name,
+            top_usage.calls,
+            top_usage.gas,
+            counts.join(",")
+        ),
+        ..Default::default()
+    }]
+}
@@ -0,0 +1,33 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::u256;
+
+/// Whether a transaction plausibly arrived through private orderflow (a
+/// bundle or builder RPC) rather than the public mempool: it pays the block
+/// producer directly while bidding no priority fee, which only makes sense
+/// when inclusion was arranged off-chain. Chains whose headers leave the
+/// coinbase zeroed (Bor) never match.
+pub fn likely_private(tx: &eth::TransactionTrace, header: Option<&eth::BlockHeader>) -> bool {
+    let Some(header) = header else {
+        return false;
+    };
+    let coinbase = header.coinbase.as_slice();
+    if coinbase.iter().all(|b| *b == 0) {
+        return false;
+    }
+
+    let gas_price = u256::from_pb(tx.gas_price.as_ref());
+    let base_fee = u256::from_pb(header.base_fee_per_gas.as_ref());
+    if !gas_price.saturating_sub(base_fee).is_zero() {
+        return false;
+    }
+
+    if tx.calls.is_empty() {
+        return tx.to == coinbase && !u256::from_pb(tx.value.as_ref()).is_zero();
+    }
+    tx.calls.iter().any(|call| {
+        !call.state_reverted
+            && call.address == coinbase
+            && !u256::from_pb(call.value.as_ref()).is_zero()
+    })
+}
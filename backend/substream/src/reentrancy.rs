@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::CandidateEvent;
+use crate::u256;
+
+/// Views that protocols and lenders read to price a pool's LP token or
+/// shares, which are exactly what a mid-call read sees in a stale state.
+const PRICING_VIEWS: [([u8; 4], &str); 8] = [
+    (hex_literal::hex!("bb7b8b80"), "get_virtual_price()"),
+    (hex_literal::hex!("54f0f7d5"), "lp_price()"),
+    (hex_literal::hex!("86fc88d3"), "price_oracle()"),
+    (hex_literal::hex!("679aefce"), "getRate()"),
+    (hex_literal::hex!("f94d4668"), "getPoolTokens(bytes32)"),
+    (hex_literal::hex!("0902f1ac"), "getReserves()"),
+    (hex_literal::hex!("07a2d13a"), "convertToAssets(uint256)"),
+    (hex_literal::hex!("99530b06"), "pricePerShare()"),
+];
+
+/// Flags a third party statically calling one of a pool's pricing views while
+/// a state-mutating call into that same pool is still on the stack, i.e. from
+/// inside a callback the pool made mid-update. `log_index` carries the index
+/// of the view call.
+pub fn reentrancy_events(tx: &eth::TransactionTrace, block_number: u64) -> Vec<CandidateEvent> {
+    let by_index: BTreeMap<u32, &eth::Call> = tx.calls.iter().map(|c| (c.index, c)).collect();
+    let mut events = Vec::new();
+
+    for view in tx.calls.iter() {
+        if view.call_type != eth::CallType::Static as i32 || view.caller == view.address {
+            continue;
+        }
+        let Some((_, function)) = PRICING_VIEWS
+            .iter()
+            .find(|(selector, _)| view.input.get(..4) == Some(&selector[..]))
+        else {
+            continue;
+        };
+
+        let Some(outer) = open_mutating_call(&by_index, view) else {
+            continue;
+        };
+        events.push(CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+            block_number,
+            log_index: view.index as u64,
+            contract_address: format!("0x{}", hex::encode(&view.address)),
+            event_type: "ReadOnlyReentrancyCandidate".to_string(),
+            from: format!("0x{}", hex::encode(&view.caller)),
+            metadata: format!(
+                "{{\"pool\":\"0x{}\",\"reader\":\"0x{}\",\"view\":\"{}\",\"outer_call_index\":{},\"outer_caller\":\"0x{}\"}}",
+                hex::encode(&view.address),
+                hex::encode(&view.caller),
+                function,
+                outer.index,
+                hex::encode(&outer.caller)
+            ),
+            ..Default::default()
+        });
+    }
+
+    events
+}
+
+/// Flags a contract re-entered while a state-mutating call into it is still
+/// open: a call into the contract whose nearest ancestor targeting it is
+/// separated from it by calls into other contracts. `metadata.path` lists the
+/// loop from that ancestor down, as `{index, address}`; loops that move
+/// native value on the way are `high` severity, others `medium`. Delegate
+/// calls run another contract's code in the caller and are never counted as
+/// entries. `log_index` carries the index of the reentrant call.
+pub fn reentrant_calls(tx: &eth::TransactionTrace, block_number: u64) -> Vec<CandidateEvent> {
+    let by_index: BTreeMap<u32, &eth::Call> = tx.calls.iter().map(|c| (c.index, c)).collect();
+    let mut events = Vec::new();
+
+    for call in tx.calls.iter() {
+        if call.state_reverted
+            || call.call_type == eth::CallType::Static as i32
+            || call.call_type == eth::CallType::Delegate as i32
+        {
+            continue;
+        }
+        let Some(path) = reentry_path(&by_index, call) else {
+            continue;
+        };
+        let value_transfer = path
+            .iter()
+            .any(|c| !u256::from_pb(c.value.as_ref()).is_zero());
+        let hops: Vec<String> = path
+            .iter()
+            .map(|c| {
+                format!(
+                    "{{\"index\":{},\"address\":\"0x{}\"}}",
+                    c.index,
+                    hex::encode(&c.address)
+                )
+            })
+            .collect();
+
+        events.push(CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+            block_number,
+            log_index: call.index as u64,
+            contract_address: format!("0x{}", hex::encode(&call.address)),
+            event_type: "ReentrancySuspect".to_string(),
+            from: format!("0x{}", hex::encode(&call.caller)),
+            severity: if value_transfer { "high" } else { "medium" }.to_string(),
+            metadata: format!(
+                "{{\"path\":[{}],\"value_transfer\":{}}}",
+                hops.join(","),
+                value_transfer
+            ),
+            ..Default::default()
+        });
+    }
+
+    events
+}
+
+/// Calls from the nearest non-static ancestor of `call` targeting the same
+/// contract down to `call`, when another contract sits in between.
+fn reentry_path<'a>(
+    by_index: &BTreeMap<u32, &'a eth::Call>,
+    call: &'a eth::Call,
+) -> Option<Vec<&'a eth::Call>> {
+    let mut path = vec![call];
+    let mut current = call;
+    for _ in 0..call.depth {
+        let parent = by_index.get(&current.parent_index).copied()?;
+        if parent.index == current.index {
+            return None;
+        }
+        if parent.address == call.address && parent.call_type != eth::CallType::Static as i32 {
+            // A contract calling itself directly isn't being re-entered.
+            if path.len() == 1 {
+                return None;
+            }
+            path.push(parent);
+            path.reverse();
+            return Some(path);
+        }
+        path.push(parent);
+        current = parent;
+    }
+    None
+}
+
+/// The outermost non-static ancestor of `view` that targets the same
+/// contract, if any.
+fn open_mutating_call<'a>(
+    by_index: &BTreeMap<u32, &'a eth::Call>,
+    view: &eth::Call,
+) -> Option<&'a eth::Call> {
+    let mut found = None;
+    let mut current = view;
+    // Depth bounds the walk even if parent links are malformed.
+    for _ in 0..view.depth {
+        let Some(parent) = by_index.get(&current.parent_index).copied() else {
+            break;
+        };
+        if parent.index == current.index {
+            break;
+        }
+        if parent.address == view.address && parent.call_type != eth::CallType::Static as i32 {
+            found = Some(parent);
+        }
+        current = parent;
+    }
+    found
+}
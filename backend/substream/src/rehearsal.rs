@@ -0,0 +1,192 @@
+use std::ops::RangeInclusive;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::retention::{self, Lists};
+use crate::u256::{self, U256};
+use crate::{abi, amount, calls, sender};
+
+// Plain token plumbing says nothing about which exploit path is exercised.
+// transfer(address,uint256), approve(address,uint256),
+// transferFrom(address,address,uint256)
+const TOKEN_SELECTORS: [[u8; 4]; 3] = [
+    hex_literal::hex!("a9059cbb"),
+    hex_literal::hex!("095ea7b3"),
+    hex_literal::hex!("23b872dd"),
+];
+
+/// Prefix of every key of one probe window, so a whole window can be
+/// dropped once no lookback reaches it.
+pub fn window_prefix(window: u64) -> String {
+    format!("probe:{}:", window)
+}
+
+/// Key listing an actor's low-value probe calls in one window as
+/// `block:tx_hash:contract:selector` entries joined by `;`.
+pub fn probe_key(window: u64, actor: &str) -> String {
+    format!("{}{}", window_prefix(window), actor)
+}
+
+/// The block's low-value transactions' calls as `(ordinal, probe_key,
+/// encoded probe)`, to add to the actor's list of the block's window.
+pub fn entries(
+    blk: &eth::Block,
+    window: u64,
+    annotations: &Annotations,
+) -> Vec<(u64, String, String)> {
+    let mut out = Vec::new();
+    for tx in blk
+        .transaction_traces
+        .iter()
+        .filter(|tx| is_low_value(tx, annotations))
+    {
+        let Some(sender) = sender::sender(tx) else {
+            continue;
+        };
+        let key = probe_key(window, &format!("0x{}", hex::encode(sender)));
+        for target in call_targets(tx) {
+            out.push((
+                tx.begin_ordinal,
+                key.clone(),
+                encode_probe(blk.number, &tx.hash, &target),
+            ));
+        }
+    }
+    out
+}
+
+pub fn encode_probe(block_number: u64, tx_hash: &[u8], call: &(String, String)) -> String {
+    format!(
+        "{}:0x{}:{}:{};",
+        block_number,
+        hex::encode(tx_hash),
+        call.0,
+        call.1
+    )
+}
+
+/// Distinct `(contract, selector)` pairs a transaction calls, leaving out
+/// plain ERC20 transfers and approvals.
+pub fn call_targets(tx: &eth::TransactionTrace) -> Vec<(String, String)> {
+    let mut targets: Vec<(String, String)> = calls::frames(tx)
+        .into_iter()
+        .filter_map(|(_, _, callee, input)| {
+            let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+            if TOKEN_SELECTORS.contains(&selector) {
+                return None;
+            }
+            Some((
+                format!("0x{}", hex::encode(callee)),
+                format!("0x{}", hex::encode(selector)),
+            ))
+        })
+        .collect();
+    targets.sort_unstable();
+    targets.dedup();
+    targets
+}
+
+/// Whether a transaction moves at most one whole unit of the native asset and
+/// of every listed token, i.e. looks like a rehearsal rather than the real
+/// thing.
+pub fn is_low_value(tx: &eth::TransactionTrace, annotations: &Annotations) -> bool {
+    let one = |decimals: u8| U256::exp10(decimals as usize);
+    if u256::from_pb(tx.value.as_ref()) > one(18) {
+        return false;
+    }
+    calls::tx_logs(tx).0.iter().all(|log| {
+        let Some(transfer) = abi::transfer(log) else {
+            return true;
+        };
+        match amount::token_decimals(annotations, &format!("0x{}", hex::encode(&log.address))) {
+            Some(decimals) => transfer.amount <= one(decimals),
+            None => true,
+        }
+    })
+}
+
+/// Earlier low-value transactions of `actor` at an age in `ages` that hit
+/// any of `targets`, as `(tx_hash, block, matched targets)`. Like
+/// `setup::recent_setups`, reads the windows of `window_blocks` that `ages`
+/// can reach.
+pub fn rehearsals(
+    probes: &Lists,
+    actor: &str,
+    tx_hash: &str,
+    targets: &[(String, String)],
+    block_number: u64,
+    window_blocks: u64,
+    ages: &RangeInclusive<u64>,
+) -> Vec<(String, u64, Vec<String>)> {
+    let entries: String = retention::live(block_number, window_blocks)
+        .filter_map(|window| probes.get(&probe_key(window, actor)))
+        .collect();
+
+    let mut out: Vec<(String, u64, Vec<String>)> = Vec::new();
+    for entry in entries.split(';') {
+        let mut parts = entry.split(':');
+        let (Some(block), Some(probe_tx), Some(contract), Some(selector)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let Ok(block) = block.parse::<u64>() else {
+            continue;
+        };
+        if probe_tx == tx_hash
+            || !ages.contains(&block_number.saturating_sub(block))
+            || !targets.iter().any(|(c, s)| c == contract && s == selector)
+        {
+            continue;
+        }
+
+        let target = format!("{}:{}", contract, selector);
+        match out.iter_mut().find(|(tx, _, _)| tx == probe_tx) {
+            Some((_, _, matched)) => {
+                if !matched.contains(&target) {
+                    matched.push(target);
+                }
+            }
+            None => out.push((probe_tx.to_string(), block, vec![target])),
+        }
+    }
+    out
+}
+
+/// Builds an `ExploitRehearsalCandidate` on a flagged event's transaction,
+/// listing the rehearsals that exercised the same calls beforehand.
+pub fn candidate(
+    flagged: &CandidateEvent,
+    actor: &str,
+    rehearsals: &[(String, u64, Vec<String>)],
+) -> CandidateEvent {
+    let entries: Vec<String> = rehearsals
+        .iter()
+        .map(|(tx, block, matched)| {
+            let matched: Vec<String> = matched.iter().map(|m| format!("\"{}\"", m)).collect();
+            format!(
+                "{{\"tx\":\"{}\",\"block\":{},\"calls\":[{}]}}",
+                tx,
+                block,
+                matched.join(",")
+            )
+        })
+        .collect();
+
+    CandidateEvent {
+        transaction_hash: flagged.transaction_hash.clone(),
+        block_number: flagged.block_number,
+        log_index: flagged.log_index,
+        event_type: "ExploitRehearsalCandidate".to_string(),
+        from: actor.to_string(),
+        metadata: format!(
+            "{{\"flagged\":\"{}\",\"flagged_fingerprint\":\"{}\",\"rehearsals\":[{}]}}",
+            flagged.event_type,
+            flagged.fingerprint,
+            entries.join(",")
+        ),
+        ..Default::default()
+    }
+}
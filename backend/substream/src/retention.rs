@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use substreams::store::{StoreGet, StoreGetInt64, StoreGetString};
+
+/// Window of `window_blocks` blocks the block falls in. Stores keyed by
+/// window keep what a lookback of at most `window_blocks` can still read:
+/// the current window and the previous one.
+pub fn window(block_number: u64, window_blocks: u64) -> u64 {
+    block_number / window_blocks
+}
+
+/// Windows a lookback of at most `window_blocks` from this block reaches,
+/// oldest first.
+pub fn live(block_number: u64, window_blocks: u64) -> RangeInclusive<u64> {
+    let window = window(block_number, window_blocks);
+    window.saturating_sub(1)..=window
+}
+
+/// Window whose keys to drop at this block: at the first block of a window,
+/// the one before the previous, which nothing reads any more.
+pub fn expired(block_number: u64, window_blocks: u64) -> Option<u64> {
+    let window = window(block_number, window_blocks);
+    (block_number.is_multiple_of(window_blocks) && window >= 2).then(|| window - 2)
+}
+
+/// Key of the entry at `index` of a pruned list. Lists that a store would
+/// otherwise append to are split over two stores, since only set and add
+/// stores can drop a prefix: an add store counting each list's entries under
+/// the list's key, and a set store holding the entries under this key. Both
+/// keep the list's key as a prefix, so deleting a list's prefix from both
+/// drops it whole.
+pub fn entry_key(list: &str, index: u64) -> String {
+    format!("{}#{}", list, index)
+}
+
+/// Entry keys for a block's `appended` `(ordinal, list, entry)`s, in order.
+/// `length` reads a list's length from the add store, which has already
+/// counted this block, so the block's entries take the indices just below
+/// it.
+pub fn entry_keys(
+    appended: Vec<(u64, String, String)>,
+    length: impl Fn(&str) -> u64,
+) -> Vec<(u64, String, String)> {
+    let mut added: BTreeMap<String, u64> = BTreeMap::new();
+    for (_, list, _) in appended.iter() {
+        *added.entry(list.clone()).or_default() += 1;
+    }
+    let mut next: BTreeMap<String, u64> = added
+        .into_iter()
+        .map(|(list, count)| {
+            let start = length(&list).saturating_sub(count);
+            (list, start)
+        })
+        .collect();
+    appended
+        .into_iter()
+        .map(|(ordinal, list, entry)| {
+            let index = next.get_mut(&list).map_or(0, |next| {
+                *next += 1;
+                *next - 1
+            });
+            (ordinal, entry_key(&list, index), entry)
+        })
+        .collect()
+}
+
+/// Length of a list in the add store of a pruned list store.
+pub fn length(lengths: &StoreGetInt64, list: &str) -> u64 {
+    lengths.get_last(list).unwrap_or_default().max(0) as u64
+}
+
+/// Both stores of a pruned list store, as a module reading it gets them.
+pub struct Lists {
+    pub lengths: StoreGetInt64,
+    pub entries: StoreGetString,
+}
+
+impl Lists {
+    /// A list's entries in the order they were added, each followed by `;`
+    /// as an append store would hold them. `None` for empty lists.
+    pub fn get(&self, list: &str) -> Option<String> {
+        let length = length(&self.lengths, list);
+        (length > 0).then(|| {
+            (0..length)
+                .filter_map(|index| self.entries.get_last(entry_key(list, index)))
+                .map(|entry| format!("{};", entry))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{metrics, permit2, poisoning, rehearsal, rug_pull, setup, velocity};
+
+    /// A store as the runtime keeps it: keys to values, pruned by prefix.
+    #[derive(Default)]
+    struct Store(BTreeMap<String, String>);
+
+    impl Store {
+        fn delete_prefix(&mut self, prefix: &str) {
+            self.0.retain(|key, _| !key.starts_with(prefix));
+        }
+
+        fn add(&mut self, key: &str) {
+            let length = self.length(key);
+            self.0.insert(key.to_string(), (length + 1).to_string());
+        }
+
+        fn length(&self, key: &str) -> u64 {
+            self.0
+                .get(key)
+                .and_then(|length| length.parse().ok())
+                .unwrap_or_default()
+        }
+    }
+
+    /// A pruned list store: the add store of lengths and the set store of
+    /// entries, written the way their handlers write them.
+    #[derive(Default)]
+    struct ListStore {
+        lengths: Store,
+        entries: Store,
+    }
+
+    impl ListStore {
+        fn delete_prefix(&mut self, prefix: &str) {
+            self.lengths.delete_prefix(prefix);
+            self.entries.delete_prefix(prefix);
+        }
+
+        fn append(&mut self, appended: Vec<(u64, String, String)>) {
+            for (_, list, _) in appended.iter() {
+                self.lengths.add(list);
+            }
+            for (_, key, entry) in entry_keys(appended, |list| self.lengths.length(list)) {
+                self.entries.0.insert(key, entry);
+            }
+        }
+
+        fn get(&self, list: &str) -> Vec<&str> {
+            (0..self.lengths.length(list))
+                .filter_map(|index| self.entries.0.get(&entry_key(list, index)))
+                .map(String::as_str)
+                .collect()
+        }
+    }
+
+    fn appended(list: &str, entries: &[&str]) -> Vec<(u64, String, String)> {
+        entries
+            .iter()
+            .map(|entry| (0, list.to_string(), entry.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn lists_keep_append_order_across_blocks() {
+        let mut store = ListStore::default();
+        store.append(appended("setup:0:0xa", &["1", "2"]));
+        store.append(
+            appended("setup:0:0xb", &["x"])
+                .into_iter()
+                .chain(appended("setup:0:0xa", &["3"]))
+                .collect(),
+        );
+        store.append(appended("setup:0:0xa", &["4", "5"]));
+
+        assert_eq!(store.get("setup:0:0xa"), ["1", "2", "3", "4", "5"]);
+        assert_eq!(store.get("setup:0:0xb"), ["x"]);
+    }
+
+    #[test]
+    fn deleting_a_window_leaves_the_others() {
+        let mut store = ListStore::default();
+        store.append(appended(&setup::setup_key(1, "0xa"), &["old"]));
+        store.append(appended(&setup::setup_key(12, "0xa"), &["new"]));
+        store.delete_prefix(&setup::window_prefix(1));
+
+        assert!(store.get(&setup::setup_key(1, "0xa")).is_empty());
+        assert_eq!(store.get(&setup::setup_key(12, "0xa")), ["new"]);
+    }
+
+    #[test]
+    fn expired_windows_are_never_live() {
+        for window_blocks in [1, 7, 150, 1_800] {
+            for block in 0..window_blocks * 10 {
+                if let Some(expired) = expired(block, window_blocks) {
+                    assert!(!live(block, window_blocks).contains(&expired));
+                    assert!(!live(block + window_blocks - 1, window_blocks).contains(&expired));
+                }
+            }
+        }
+    }
+
+    /// Replays `blocks` blocks writing `actors` keys per block into a store
+    /// keyed by window, pruned like the window stores, and returns the most
+    /// keys it ever held.
+    fn replay_window_store(
+        blocks: u64,
+        window_blocks: u64,
+        actors: u64,
+        prefix: fn(u64) -> String,
+    ) -> usize {
+        let mut store = ListStore::default();
+        let mut most = 0;
+        for block in 0..blocks {
+            if let Some(window) = expired(block, window_blocks) {
+                store.delete_prefix(&prefix(window));
+            }
+            let window = window(block, window_blocks);
+            let entries = (0..actors)
+                .map(|actor| {
+                    let list = format!("{}0x{:040x}", prefix(window), actor);
+                    (0, list, block.to_string())
+                })
+                .collect();
+            store.append(entries);
+            most = most.max(store.lengths.0.len() + store.entries.0.len());
+        }
+        most
+    }
+
+    #[test]
+    fn window_stores_stay_bounded_over_long_replays() {
+        let prefixes: [fn(u64) -> String; 6] = [
+            poisoning::window_prefix,
+            setup::window_prefix,
+            rehearsal::window_prefix,
+            rug_pull::window_prefix,
+            velocity::window_prefix,
+            metrics::window_prefix,
+        ];
+        for prefix in prefixes {
+            let (window_blocks, actors) = (150, 4);
+            // Two windows of lists, and of one entry per block and actor.
+            let bound = (2 * actors + 2 * window_blocks * actors) as usize;
+            let short = replay_window_store(3 * window_blocks, window_blocks, actors, prefix);
+            let long = replay_window_store(100 * window_blocks, window_blocks, actors, prefix);
+            assert!(long <= bound, "{} keys, bound {}", long, bound);
+            assert_eq!(short, long);
+        }
+    }
+
+    #[test]
+    fn permit2_expiry_buckets_stay_bounded_over_long_replays() {
+        let mut store = ListStore::default();
+        let (mut most, mut previous) = (0, 0);
+        // Ten days of 12 second blocks, each setting an allowance that
+        // expires within a day.
+        for block in 1..10 * 7_200u64 {
+            let timestamp = 1_700_000_000 + 12 * block;
+            for bucket in permit2::expired_buckets(previous, timestamp) {
+                store.delete_prefix(&permit2::expiry_key(bucket));
+            }
+            let expiration = timestamp + block * 7_919 % 86_400;
+            let list = permit2::expiry_key(expiration / permit2::EXPIRY_BUCKET_SECONDS);
+            store.append(vec![(0, list, format!("0x{:x}:t:s", block))]);
+            most = most.max(store.lengths.0.len());
+            previous = timestamp;
+        }
+        // The retained day, the day ahead and the bucket in progress.
+        assert!(most <= 24 + 24 + 2, "{} buckets", most);
+    }
+}
@@ -0,0 +1,137 @@
+use substreams::store::{StoreGet, StoreGetInt64};
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::params::Params;
+use crate::pb::pulseproof::{ApprovalParams, CandidateEvent};
+use crate::u256::U256;
+use crate::{abi, amount, decoded, deployer, permit_deadline};
+
+// Approval(address,address,uint256)
+const APPROVAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+
+/// Key holding the block a contract was created in.
+pub fn creation_key(contract: &str) -> String {
+    format!("created:{}", contract)
+}
+
+fn unknown(annotations: &Annotations, spender: &str) -> bool {
+    annotations.labels(spender).is_empty() && annotations.protocol(spender).is_none()
+}
+
+/// Emits `RiskyApproval` for grants to spenders that are neither labelled nor
+/// a registered protocol, the first step of most wallet drainers:
+///
+/// - ERC20 approvals of `MAX_UINT256`, or of more than
+///   `risky_approval_units` whole tokens when it is set (`kind`
+///   `infinite_approval` / `large_approval`);
+/// - permits whose spender has sent a transaction since the start block, so
+///   is an EOA (`eoa_spender`), or was created within `fresh_spender_blocks`
+///   (`fresh_spender`).
+///
+/// `activity` is `store_eoa_activity`; `creations` is
+/// `store_contract_creations`. Both only know what they saw since their
+/// initial block. `log_index` carries the log index, or the permit's call
+/// index.
+pub fn risky_approvals(
+    tx: &eth::TransactionTrace,
+    logs: &[eth::Log],
+    block_number: u64,
+    params: &Params,
+    activity: &StoreGetInt64,
+    creations: &StoreGetInt64,
+) -> Vec<CandidateEvent> {
+    let (large_units, fresh_blocks) = (params.risky_approval_units, params.fresh_spender_blocks);
+    let annotations = &params.annotations();
+    let transaction_hash = format!("0x{}", hex::encode(&tx.hash));
+    let mut events = Vec::new();
+    if tx.status != eth::TransactionTraceStatus::Succeeded as i32 {
+        return events;
+    }
+
+    for log in logs
+        .iter()
+        .filter(|log| log.topics.first().map(Vec::as_slice) == Some(&APPROVAL_TOPIC[..]))
+    {
+        let Some(approval) = decoded::approval(annotations, log) else {
+            continue;
+        };
+        let Some(value) = abi::word_u256(&log.data, 0) else {
+            continue;
+        };
+        let token = format!("0x{}", hex::encode(&log.address));
+        let kind = if value == U256::MAX {
+            "infinite_approval"
+        } else if large_units > 0 && amount::above_units(annotations, value, &token, large_units) {
+            "large_approval"
+        } else {
+            continue;
+        };
+        if !unknown(annotations, &approval.spender) {
+            continue;
+        }
+        events.push(CandidateEvent {
+            transaction_hash: transaction_hash.clone(),
+            block_number,
+            log_index: log.index as u64,
+            contract_address: token,
+            event_signature: format!("0x{}", hex::encode(APPROVAL_TOPIC)),
+            event_type: "RiskyApproval".to_string(),
+            owner: approval.owner.clone(),
+            spender: approval.spender.clone(),
+            metadata: format!("{{\"kind\":\"{}\"}}", kind),
+            approval: Some(approval),
+            ..Default::default()
+        });
+    }
+
+    for permit in permit_deadline::permits(tx, logs) {
+        if !unknown(annotations, &permit.spender) {
+            continue;
+        }
+        let created = creations
+            .get_last(creation_key(&permit.spender))
+            .map(|block| block.max(0) as u64);
+        let detail = if activity
+            .get_last(deployer::activity_key(&permit.spender))
+            .is_some()
+        {
+            "\"kind\":\"eoa_spender\"".to_string()
+        } else if let Some(created) =
+            created.filter(|created| block_number.saturating_sub(*created) < fresh_blocks)
+        {
+            format!(
+                "\"kind\":\"fresh_spender\",\"spender_created_block\":{}",
+                created
+            )
+        } else {
+            continue;
+        };
+        let deadline = permit
+            .deadline
+            .map(|d| format!("\"{}\"", d))
+            .unwrap_or_else(|| "null".to_string());
+        events.push(CandidateEvent {
+            transaction_hash: transaction_hash.clone(),
+            block_number,
+            log_index: permit.index,
+            event_type: "RiskyApproval".to_string(),
+            owner: permit.owner.clone(),
+            spender: permit.spender.clone(),
+            metadata: format!(
+                "{{{},\"permit\":\"{}\",\"deadline\":{}}}",
+                detail, permit.kind, deadline
+            ),
+            approval: Some(ApprovalParams {
+                owner: permit.owner,
+                spender: permit.spender,
+                value: permit.value.to_string(),
+                value_decimal: decoded::decimal(annotations, permit.value, &permit.token),
+            }),
+            contract_address: permit.token,
+            ..Default::default()
+        });
+    }
+    events
+}
@@ -0,0 +1,60 @@
+//! Routing keys and tenant tags, so a multi-tenant alerting backend can
+//! dispatch events without keeping its own contract-to-team mapping.
+
+use crate::params::Params;
+use crate::pb::pulseproof::CandidateEvent;
+
+/// Severities from least to most severe.
+const SEVERITIES: [&str; 4] = ["info", "medium", "high", "critical"];
+
+fn rank(severity: &str) -> Option<usize> {
+    SEVERITIES.iter().position(|s| *s == severity)
+}
+
+pub fn is_severity(severity: &str) -> bool {
+    rank(severity).is_some()
+}
+
+/// Tags each event with the `routes` keys of the contracts it involves and
+/// the `tenants` whose watch list it involves at or above the tenant's
+/// `min_severity`, adding those tenants' routes. An event involves the
+/// addresses it names and the recipient of its transaction. Keys and tenants
+/// are listed once each, in param order.
+pub fn route(events: &mut [CandidateEvent], params: &Params) {
+    if params.routes.is_empty() && params.tenants.is_empty() {
+        return;
+    }
+    for event in events.iter_mut() {
+        let addresses = [
+            &event.contract_address,
+            &event.from,
+            &event.to,
+            &event.owner,
+            &event.spender,
+            &event.tx_to,
+        ];
+        let involves = |address: &String| addresses.contains(&address);
+        let severity = rank(&event.severity);
+
+        let mut routing_keys: Vec<String> = Vec::new();
+        let mut tenants: Vec<String> = Vec::new();
+        let mut add_key = |key: &str| {
+            if !key.is_empty() && !routing_keys.iter().any(|k| k == key) {
+                routing_keys.push(key.to_string());
+            }
+        };
+        for (contract, key) in params.routes.iter() {
+            if involves(contract) {
+                add_key(key);
+            }
+        }
+        for tenant in params.tenants.iter() {
+            if severity >= rank(&tenant.min_severity) && tenant.addresses.iter().any(involves) {
+                tenants.push(tenant.id.clone());
+                add_key(&tenant.route);
+            }
+        }
+        event.routing_keys = routing_keys;
+        event.tenants = tenants;
+    }
+}
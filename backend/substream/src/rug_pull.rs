@@ -0,0 +1,241 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::Tag;
+use crate::u256::U256;
+use crate::{abi, amount, calls};
+
+// Uniswap V2 pair Burn(address,uint256,uint256,address), also logged by its
+// forks.
+const V2_BURN_TOPIC: [u8; 32] =
+    hex_literal::hex!("dccd412f0b1252819cb1fd330b93224ca42612892bb3f4f789976e6d81936496");
+// Uniswap V3 pool Burn(address,int24,int24,uint128,uint256,uint256)
+const V3_BURN_TOPIC: [u8; 32] =
+    hex_literal::hex!("0c396cd989a39f4459b5fa1aed6a9a8dcdbc45908acfd67e028cd568da98982c");
+// Curve RemoveLiquidity, RemoveLiquidityOne and RemoveLiquidityImbalance, for
+// two- and three-coin pools and both RemoveLiquidityOne layouts.
+const CURVE_REMOVE_TOPICS: [[u8; 32]; 6] = [
+    hex_literal::hex!("7c363854ccf79623411f8995b362bce5eddff18c927edc6f5dbbb5e05819a82c"),
+    hex_literal::hex!("a49d4cf02656aebf8c771f5a8585638a2a15ee6c97cf7205d4208ed7c1df252d"),
+    hex_literal::hex!("9e96dd3b997a2a257eec4df9bb6eaf626e206df5f543bd963682d143300be310"),
+    hex_literal::hex!("5ad056f2e28a8cec232015406b843668c1e36cda598127ec3b8c59b8c72773a0"),
+    hex_literal::hex!("2b5508378d7e19e0d5fa338419034731416c4f5b219a10379956f764317fd47e"),
+    hex_literal::hex!("173599dbf9c6ca6f7c3b590df07ae98a45d74ff54065505141e7de6c46a624c2"),
+];
+
+/// A liquidity removal from a pool, with what the pool paid out in its
+/// transaction.
+pub struct Removal {
+    pub block_number: u64,
+    pub transaction_hash: String,
+    pub pool: String,
+    pub protocol: &'static str,
+    pub protocol_version: &'static str,
+    /// `(token, amount)` the pool transferred out in the transaction.
+    pub payouts: Vec<(String, U256)>,
+    /// Ordinal of the removal log, to store it at; not encoded.
+    pub ordinal: u64,
+}
+
+/// Prefix of every key of one removal window, so a whole window can be
+/// dropped once no removal in it is recent enough to flag.
+pub fn window_prefix(window: u64) -> String {
+    format!("removal:{}:", window)
+}
+
+/// Key holding the latest liquidity removal in one window from a pool a
+/// deployer created.
+pub fn removal_key(window: u64, deployer: &str) -> String {
+    format!("{}{}", window_prefix(window), deployer)
+}
+
+fn tag(log: &eth::Log) -> Option<Tag> {
+    let topic0 = log.topics.first()?.as_slice();
+    if topic0 == V2_BURN_TOPIC && log.topics.len() == 3 {
+        return Some(Tag::protocol("uniswap", "v2"));
+    }
+    // A V3 Burn of no liquidity only updates the position's fees.
+    if topic0 == V3_BURN_TOPIC
+        && log.topics.len() == 4
+        && abi::word_u256(&log.data, 0).is_some_and(|liquidity| !liquidity.is_zero())
+    {
+        return Some(Tag::protocol("uniswap", "v3"));
+    }
+    CURVE_REMOVE_TOPICS
+        .iter()
+        .any(|topic| topic0 == topic)
+        .then(|| Tag::protocol("curve", ""))
+}
+
+/// Uniswap V2 and V3 Burns and Curve liquidity removals of the block's
+/// successful transactions. A pool removed from several times in one
+/// transaction counts once.
+pub fn removals(blk: &eth::Block) -> Vec<Removal> {
+    let mut out: Vec<Removal> = Vec::new();
+    for tx in blk.transaction_traces.iter() {
+        if tx.status != eth::TransactionTraceStatus::Succeeded as i32 {
+            continue;
+        }
+        let (logs, _) = calls::tx_logs(tx);
+        let first = out.len();
+        for log in logs.iter() {
+            let Some(tag) = tag(log) else {
+                continue;
+            };
+            let pool = format!("0x{}", hex::encode(&log.address));
+            if out.iter().skip(first).any(|r| r.pool == pool) {
+                continue;
+            }
+            // The pool's own LP token burns are not payouts.
+            let mut payouts: Vec<(String, U256)> = Vec::new();
+            for l in logs.iter().filter(|l| l.address != log.address) {
+                let Some(transfer) = abi::transfer(l) else {
+                    continue;
+                };
+                if transfer.from != log.address.as_slice() {
+                    continue;
+                }
+                let token = format!("0x{}", hex::encode(&l.address));
+                match payouts.iter_mut().find(|(t, _)| *t == token) {
+                    Some((_, total)) => *total = total.saturating_add(transfer.amount),
+                    None => payouts.push((token, transfer.amount)),
+                }
+            }
+            out.push(Removal {
+                block_number: blk.number,
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                pool,
+                protocol: tag.protocol,
+                protocol_version: tag.protocol_version,
+                payouts,
+                ordinal: log.ordinal,
+            });
+        }
+    }
+    out
+}
+
+/// `block|tx|pool|protocol|version|token=amount,...`
+pub fn encode(removal: &Removal) -> String {
+    let payouts: Vec<String> = removal
+        .payouts
+        .iter()
+        .map(|(token, amount)| format!("{}={}", token, amount))
+        .collect();
+    format!(
+        "{}|{}|{}|{}|{}|{}",
+        removal.block_number,
+        removal.transaction_hash,
+        removal.pool,
+        removal.protocol,
+        removal.protocol_version,
+        payouts.join(",")
+    )
+}
+
+/// Inverse of `encode`. Protocols outside this module's decoders come back
+/// untagged.
+pub fn decode(value: &str) -> Option<Removal> {
+    let mut parts = value.split('|');
+    let block_number = parts.next()?.parse().ok()?;
+    let transaction_hash = parts.next()?.to_string();
+    let pool = parts.next()?.to_string();
+    let tag = match (parts.next()?, parts.next()?) {
+        ("uniswap", "v2") => Tag::protocol("uniswap", "v2"),
+        ("uniswap", "v3") => Tag::protocol("uniswap", "v3"),
+        ("curve", "") => Tag::protocol("curve", ""),
+        _ => Tag::default(),
+    };
+    let payouts = parts
+        .next()?
+        .split(',')
+        .filter(|payout| !payout.is_empty())
+        .map(|payout| {
+            let (token, amount) = payout.split_once('=')?;
+            Some((token.to_string(), U256::from_dec_str(amount).ok()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Removal {
+        block_number,
+        transaction_hash,
+        pool,
+        protocol: tag.protocol,
+        protocol_version: tag.protocol_version,
+        payouts,
+        ordinal: 0,
+    })
+}
+
+/// `RugPullCandidate` events for pool deployers moving out the liquidity
+/// just pulled from their pool: a transfer by the deployer, in the block of
+/// the removal or within `window_blocks` after it, of at least `min_share`
+/// percent of what the pool paid out of that token. `removal` looks up a
+/// sender's latest removal (see `removal_key`). One event per deployer and
+/// transaction, on its first qualifying transfer; `contract_address` is the
+/// pool and `to` the transfer's recipient.
+pub fn candidates(
+    blk: &eth::Block,
+    removal: impl Fn(&str) -> Option<Removal>,
+    window_blocks: u64,
+    min_share: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+    for tx in blk.transaction_traces.iter() {
+        if tx.status != eth::TransactionTraceStatus::Succeeded as i32 {
+            continue;
+        }
+        let mut flagged: Vec<String> = Vec::new();
+        for log in calls::tx_logs(tx).0.iter() {
+            let Some(transfer) = abi::transfer(log) else {
+                continue;
+            };
+            let deployer = format!("0x{}", hex::encode(transfer.from));
+            if flagged.contains(&deployer) {
+                continue;
+            }
+            let Some(removal) = removal(&deployer) else {
+                continue;
+            };
+            let blocks_after = blk.number.saturating_sub(removal.block_number);
+            if blocks_after > window_blocks {
+                continue;
+            }
+            let token = format!("0x{}", hex::encode(&log.address));
+            let Some((_, removed)) = removal.payouts.iter().find(|(t, _)| *t == token) else {
+                continue;
+            };
+            if removed.is_zero()
+                || transfer.amount.saturating_mul(U256::from(100))
+                    < removed.saturating_mul(U256::from(min_share))
+            {
+                continue;
+            }
+
+            let mut event = CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                block_number: blk.number,
+                log_index: log.index as u64,
+                contract_address: removal.pool.clone(),
+                event_type: "RugPullCandidate".to_string(),
+                from: deployer.clone(),
+                to: format!("0x{}", hex::encode(transfer.to)),
+                metadata: format!(
+                    "{{\"removal_tx\":\"{}\",\"removal_block\":{},\"blocks_after\":{},\"token\":\"{}\",{},{}}}",
+                    removal.transaction_hash,
+                    removal.block_number,
+                    blocks_after,
+                    token,
+                    amount::json_fields(annotations, "amount", transfer.amount, &token),
+                    amount::json_fields(annotations, "removed", *removed, &token),
+                ),
+                ..Default::default()
+            };
+            Tag::protocol(removal.protocol, removal.protocol_version).apply(&mut event);
+            events.push(event);
+            flagged.push(deployer);
+        }
+    }
+    events
+}
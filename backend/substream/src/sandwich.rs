@@ -0,0 +1,290 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::{CandidateEvent, SandwichCandidate};
+use crate::u256::U256;
+use crate::{abi, amount, calls, sender};
+
+// Uniswap V2 style Swap(address,uint256,uint256,uint256,uint256,address)
+const SWAP_TOPIC: [u8; 32] =
+    hex_literal::hex!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+
+/// One V2 swap of the block, with the sender of its transaction.
+struct Swap<'a> {
+    pool: Vec<u8>,
+    tx: &'a eth::TransactionTrace,
+    initiator: Vec<u8>,
+    log_index: u32,
+    /// Sells token0 for token1.
+    zero_for_one: bool,
+    amount_in: U256,
+    amount_out: U256,
+    /// Token of the last transfer into the pool before the swap, if any.
+    token_in: Option<Vec<u8>>,
+}
+
+fn swaps(blk: &eth::Block) -> Vec<Swap<'_>> {
+    let mut out = Vec::new();
+    for tx in blk.transaction_traces.iter() {
+        if tx.status != eth::TransactionTraceStatus::Succeeded as i32 {
+            continue;
+        }
+        let Some(initiator) = sender::sender(tx) else {
+            continue;
+        };
+        let (logs, _) = calls::tx_logs(tx);
+        for (pos, log) in logs.iter().enumerate() {
+            if log.topics.first().map(Vec::as_slice) != Some(&SWAP_TOPIC[..]) {
+                continue;
+            }
+            let word = |index| abi::word_u256(&log.data, index);
+            let (Some(in0), Some(in1), Some(out0), Some(out1)) =
+                (word(0), word(1), word(2), word(3))
+            else {
+                continue;
+            };
+            let (zero_for_one, amount_in, amount_out) = if !in0.is_zero() && !out1.is_zero() {
+                (true, in0, out1)
+            } else if !in1.is_zero() && !out0.is_zero() {
+                (false, in1, out0)
+            } else {
+                continue;
+            };
+            let token_in = logs.iter().take(pos).rev().find_map(|l| {
+                let transfer = abi::transfer(l)?;
+                (transfer.to == log.address.as_slice()).then(|| l.address.clone())
+            });
+            out.push(Swap {
+                pool: log.address.clone(),
+                tx,
+                initiator: initiator.to_vec(),
+                log_index: log.index,
+                zero_for_one,
+                amount_in,
+                amount_out,
+                token_in,
+            });
+        }
+    }
+    out
+}
+
+/// Flags the buy, victim, sell pattern on Uniswap V2 style pools: one sender
+/// swaps into a pool, another sender swaps the same way in a later
+/// transaction, then the first sender swaps back out at a profit, all in the
+/// block. The victim swap must move at least `min_victim_units` whole tokens
+/// when set (tokens with known decimals only); the largest qualifying victim
+/// between the two legs is reported. One `SandwichCandidate` is emitted per
+/// back-run, on its Swap log.
+pub fn sandwiches(
+    blk: &eth::Block,
+    min_victim_units: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let swaps = swaps(blk);
+    let mut used = vec![false; swaps.len()];
+    let mut events = Vec::new();
+
+    for (back_pos, back) in swaps.iter().enumerate() {
+        let front = swaps
+            .iter()
+            .zip(used.iter())
+            .enumerate()
+            .take(back_pos)
+            .rev()
+            .find(|&(_, (front, &used))| {
+                !used
+                    && front.pool == back.pool
+                    && front.initiator == back.initiator
+                    && front.tx.index < back.tx.index
+                    && front.zero_for_one != back.zero_for_one
+                    && back.amount_out > front.amount_in
+            })
+            .map(|(pos, (front, _))| (pos, front));
+        let Some((front_pos, front)) = front else {
+            continue;
+        };
+        let victim = swaps
+            .get(front_pos + 1..back_pos)
+            .unwrap_or_default()
+            .iter()
+            .filter(|v| {
+                v.pool == front.pool
+                    && v.initiator != front.initiator
+                    && v.tx.index > front.tx.index
+                    && v.tx.index < back.tx.index
+                    && v.zero_for_one == front.zero_for_one
+            })
+            .filter(|v| {
+                min_victim_units == 0
+                    || v.token_in.as_ref().is_some_and(|token| {
+                        let token = format!("0x{}", hex::encode(token));
+                        amount::at_least_units(annotations, v.amount_in, &token, min_victim_units)
+                    })
+            })
+            .max_by_key(|v| v.amount_in);
+        let Some(victim) = victim else {
+            continue;
+        };
+        for pos in [front_pos, back_pos] {
+            if let Some(used) = used.get_mut(pos) {
+                *used = true;
+            }
+        }
+
+        let hex = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        let pool = hex(&front.pool);
+        let attacker = hex(&front.initiator);
+        events.push(CandidateEvent {
+            transaction_hash: hex(&back.tx.hash),
+            block_number: blk.number,
+            log_index: back.log_index as u64,
+            contract_address: pool.clone(),
+            event_signature: hex(&SWAP_TOPIC),
+            event_type: "SandwichCandidate".to_string(),
+            from: attacker.clone(),
+            to: hex(&victim.initiator),
+            sandwich: Some(SandwichCandidate {
+                pool,
+                attacker,
+                victim: hex(&victim.initiator),
+                front_run_tx: hex(&front.tx.hash),
+                victim_tx: hex(&victim.tx.hash),
+                back_run_tx: hex(&back.tx.hash),
+                zero_for_one: front.zero_for_one,
+                token_in: front.token_in.as_deref().map(hex).unwrap_or_default(),
+                victim_amount_in: victim.amount_in.to_string(),
+                amount_in: front.amount_in.to_string(),
+                amount_out: back.amount_out.to_string(),
+                profit: (back.amount_out - front.amount_in).to_string(),
+            }),
+            ..Default::default()
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{CanonicalToken, Params};
+
+    const TRANSFER_TOPIC: [u8; 32] =
+        hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+    const POOL: [u8; 20] = [0xaa; 20];
+    const TOKEN: [u8; 20] = [0xcc; 20];
+
+    /// One swap: its sender, direction, amount in and amount out.
+    type Leg = (u8, bool, u64, u64);
+
+    fn word(value: u64) -> Vec<u8> {
+        let mut word = vec![0; 24];
+        word.extend_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    fn address_topic(address: &[u8]) -> Vec<u8> {
+        let mut topic = vec![0; 12];
+        topic.extend_from_slice(address);
+        topic
+    }
+
+    /// A transaction of `sender` paying `TOKEN` into the pool, then swapping.
+    fn tx(index: u32, (sender, zero_for_one, amount_in, amount_out): Leg) -> eth::TransactionTrace {
+        let from = vec![sender; 20];
+        let (in0, in1, out0, out1) = if zero_for_one {
+            (amount_in, 0, 0, amount_out)
+        } else {
+            (0, amount_in, amount_out, 0)
+        };
+        let logs = vec![
+            eth::Log {
+                address: TOKEN.to_vec(),
+                topics: vec![
+                    TRANSFER_TOPIC.to_vec(),
+                    address_topic(&from),
+                    address_topic(&POOL),
+                ],
+                data: word(amount_in),
+                index: 2 * index,
+                ..Default::default()
+            },
+            eth::Log {
+                address: POOL.to_vec(),
+                topics: vec![
+                    SWAP_TOPIC.to_vec(),
+                    address_topic(&from),
+                    address_topic(&from),
+                ],
+                data: [in0, in1, out0, out1].into_iter().flat_map(word).collect(),
+                index: 2 * index + 1,
+                ..Default::default()
+            },
+        ];
+        eth::TransactionTrace {
+            hash: vec![index as u8 + 1; 32],
+            index,
+            from,
+            status: eth::TransactionTraceStatus::Succeeded as i32,
+            receipt: Some(eth::TransactionReceipt {
+                logs,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Victims of the sandwiches found in a block of `legs`, one transaction
+    /// each, with `TOKEN` listed at 0 decimals.
+    fn victims(legs: &[Leg], min_victim_units: u64) -> Vec<String> {
+        let blk = eth::Block {
+            number: 1,
+            transaction_traces: legs
+                .iter()
+                .enumerate()
+                .map(|(index, &leg)| tx(index as u32, leg))
+                .collect(),
+            ..Default::default()
+        };
+        let tokens = [CanonicalToken {
+            address: format!("0x{}", hex::encode(TOKEN)),
+            symbol: "TKN".to_string(),
+            decimals: 0,
+            peg: String::new(),
+        }];
+        let annotations = Annotations::new(Params::default().chain, &tokens);
+        sandwiches(&blk, min_victim_units, &annotations)
+            .into_iter()
+            .filter_map(|event| event.sandwich)
+            .map(|sandwich| sandwich.victim)
+            .collect()
+    }
+
+    const FRONT: Leg = (0x01, true, 1_000, 900);
+    const VICTIM: Leg = (0x02, true, 500, 400);
+    const BACK: Leg = (0x01, false, 900, 1_100);
+
+    #[test]
+    fn front_victim_back_fires() {
+        let victim = format!("0x{}", "02".repeat(20));
+        assert_eq!(victims(&[FRONT, VICTIM, BACK], 0), [victim.as_str()]);
+        assert_eq!(victims(&[FRONT, VICTIM, BACK], 500), [victim.as_str()]);
+    }
+
+    #[test]
+    fn same_direction_back_leg_does_not_fire() {
+        let back = (0x01, true, 900, 1_100);
+        assert!(victims(&[FRONT, VICTIM, back], 0).is_empty());
+    }
+
+    #[test]
+    fn unprofitable_back_leg_does_not_fire() {
+        let back = (0x01, false, 900, 1_000);
+        assert!(victims(&[FRONT, VICTIM, back], 0).is_empty());
+    }
+
+    #[test]
+    fn victim_under_min_victim_units_does_not_fire() {
+        assert!(victims(&[FRONT, VICTIM, BACK], 501).is_empty());
+    }
+}
@@ -0,0 +1,365 @@
+use std::borrow::Cow;
+
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use substreams_ethereum::pb::eth::v2 as eth;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::chain;
+
+/// Derives an address from an uncompressed secp256k1 public key: the last 20
+/// bytes of keccak256 over the 64-byte `x || y` encoding. A leading SEC1
+/// `0x04` tag is accepted and skipped.
+pub fn address_from_public_key(key: &[u8]) -> Option<[u8; 20]> {
+    let key = match key {
+        [0x04, xy @ ..] if xy.len() == 64 => xy,
+        xy if xy.len() == 64 => xy,
+        _ => return None,
+    };
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(keccak(key).get(12..)?);
+    Some(address)
+}
+
+/// The transaction's sender. Some provider variants leave `from` empty or
+/// zeroed, in which case the address is derived from the signer's public key
+/// (only present at EXTENDED detail level) or recovered from the signature.
+/// Returns `None`, an unknown sender, rather than the zero address when none
+/// of them is usable.
+pub fn sender(tx: &eth::TransactionTrace) -> Option<Cow<'_, [u8]>> {
+    if is_address(&tx.from) {
+        return Some(Cow::Borrowed(&tx.from));
+    }
+    address_from_public_key(&tx.public_key)
+        .or_else(|| recover(tx))
+        .filter(|address| is_address(address))
+        .map(|address| Cow::Owned(address.to_vec()))
+}
+
+/// Recovers the signer of `tx` from its `v`, `r` and `s`. The signing payload
+/// is rebuilt from the trace's fields and only trusted when the signed
+/// encoding hashes to `tx.hash`, so a trace missing a signed field (the
+/// EIP-1559 fee caps are EXTENDED only) gives `None` rather than a wrong
+/// address. Legacy transactions carry their chain id in `v`; typed ones sign
+/// over a chain id the trace doesn't, so the known chain profiles' ids are
+/// tried and their senders stay unknown on other chains.
+pub fn recover(tx: &eth::TransactionTrace) -> Option<[u8; 20]> {
+    if tx.r#type == eth::transaction_trace::Type::TrxTypeLegacy as i32 {
+        let v = uint(&tx.v)?;
+        return recover_on(tx, (v >= 35).then(|| (v - 35) / 2));
+    }
+    chain::PROFILES
+        .iter()
+        .find_map(|profile| recover_on(tx, Some(profile.chain_id)))
+}
+
+/// Recovers the signer of `tx` signed for `chain_id`, `None` meaning a
+/// legacy transaction from before EIP-155.
+fn recover_on(tx: &eth::TransactionTrace, chain_id: Option<u64>) -> Option<[u8; 20]> {
+    let (prefix, fields) = fields(tx, chain_id)?;
+    let v = uint(&tx.v)?;
+    let mut unsigned = fields.clone();
+    let parity = match (prefix, chain_id) {
+        (None, Some(chain_id)) => {
+            unsigned.extend([rlp_uint(chain_id), rlp_uint(0), rlp_uint(0)]);
+            v.checked_sub(35 + 2 * chain_id)?
+        }
+        (None, None) => v.checked_sub(27)?,
+        (Some(_), _) => v,
+    };
+    let mut signed = fields;
+    signed.extend([rlp_int(&tx.v), rlp_int(&tx.r), rlp_int(&tx.s)]);
+    if keccak(&envelope(prefix, &signed)) != tx.hash.as_slice() {
+        return None;
+    }
+
+    let recovery = RecoveryId::from_byte(u8::try_from(parity).ok().filter(|p| *p <= 1)?)?;
+    let signature = Signature::from_scalars(word(&tx.r)?, word(&tx.s)?).ok()?;
+    let prehash = keccak(&envelope(prefix, &unsigned));
+    let key = VerifyingKey::recover_from_prehash(&prehash, &signature, recovery).ok()?;
+    address_from_public_key(key.to_encoded_point(false).as_bytes())
+}
+
+/// The RLP-encoded fields every signature of `tx` covers, in order, and the
+/// EIP-2718 type byte of typed transactions. Types the trace can't rebuild
+/// (chain-specific ones) give `None`.
+fn fields(tx: &eth::TransactionTrace, chain_id: Option<u64>) -> Option<(Option<u8>, Vec<Vec<u8>>)> {
+    use eth::transaction_trace::Type;
+
+    let int = |value: &Option<eth::BigInt>| {
+        rlp_int(
+            value
+                .as_ref()
+                .map(|v| v.bytes.as_slice())
+                .unwrap_or_default(),
+        )
+    };
+    let access_list = || {
+        rlp_list(
+            &tx.access_list
+                .iter()
+                .map(|tuple| {
+                    let keys: Vec<Vec<u8>> =
+                        tuple.storage_keys.iter().map(|k| rlp_bytes(k)).collect();
+                    rlp_list(&[rlp_bytes(&tuple.address), rlp_list(&keys)])
+                })
+                .collect::<Vec<_>>(),
+        )
+    };
+    let call = [
+        rlp_uint(tx.gas_limit),
+        rlp_bytes(&tx.to),
+        int(&tx.value),
+        rlp_bytes(&tx.input),
+    ];
+    let ty = Type::try_from(tx.r#type).ok()?;
+    if ty == Type::TrxTypeLegacy {
+        let mut fields = vec![rlp_uint(tx.nonce), int(&tx.gas_price)];
+        fields.extend(call);
+        return Some((None, fields));
+    }
+
+    let mut fields = vec![rlp_uint(chain_id?), rlp_uint(tx.nonce)];
+    match ty {
+        Type::TrxTypeAccessList => fields.push(int(&tx.gas_price)),
+        Type::TrxTypeDynamicFee | Type::TrxTypeBlob | Type::TrxTypeSetCode => {
+            fields.extend([int(&tx.max_priority_fee_per_gas), int(&tx.max_fee_per_gas)])
+        }
+        _ => return None,
+    }
+    fields.extend(call);
+    fields.push(access_list());
+    match ty {
+        Type::TrxTypeBlob => {
+            let hashes: Vec<Vec<u8>> = tx.blob_hashes.iter().map(|h| rlp_bytes(h)).collect();
+            fields.extend([int(&tx.blob_gas_fee_cap), rlp_list(&hashes)]);
+        }
+        Type::TrxTypeSetCode => {
+            let authorizations: Vec<Vec<u8>> = tx
+                .set_code_authorizations
+                .iter()
+                .map(|auth| {
+                    rlp_list(&[
+                        rlp_int(&auth.chain_id),
+                        rlp_bytes(&auth.address),
+                        rlp_uint(auth.nonce),
+                        rlp_uint(u64::from(auth.v)),
+                        rlp_int(&auth.r),
+                        rlp_int(&auth.s),
+                    ])
+                })
+                .collect();
+            fields.push(rlp_list(&authorizations));
+        }
+        _ => {}
+    }
+    Some((Some(ty as u8), fields))
+}
+
+fn envelope(prefix: Option<u8>, fields: &[Vec<u8>]) -> Vec<u8> {
+    prefix.into_iter().chain(rlp_list(fields)).collect()
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    match bytes {
+        [byte] if *byte < 0x80 => vec![*byte],
+        _ => rlp_prefixed(0x80, bytes),
+    }
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    rlp_prefixed(0xc0, &items.concat())
+}
+
+fn rlp_prefixed(offset: u8, payload: &[u8]) -> Vec<u8> {
+    let mut encoded = if payload.len() < 56 {
+        vec![offset + payload.len() as u8]
+    } else {
+        let length = trim(&(payload.len() as u64).to_be_bytes()).to_vec();
+        let mut encoded = vec![offset + 55 + length.len() as u8];
+        encoded.extend(length);
+        encoded
+    };
+    encoded.extend_from_slice(payload);
+    encoded
+}
+
+/// A big-endian integer, encoded without leading zeros as RLP requires.
+fn rlp_int(bytes: &[u8]) -> Vec<u8> {
+    rlp_bytes(trim(bytes))
+}
+
+fn rlp_uint(value: u64) -> Vec<u8> {
+    rlp_int(&value.to_be_bytes())
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    bytes.get(zeros..).unwrap_or_default()
+}
+
+fn uint(bytes: &[u8]) -> Option<u64> {
+    let bytes = trim(bytes);
+    (bytes.len() <= 8).then(|| bytes.iter().fold(0, |v, b| v << 8 | u64::from(*b)))
+}
+
+fn word(bytes: &[u8]) -> Option<[u8; 32]> {
+    let bytes = trim(bytes);
+    let mut word = [0u8; 32];
+    word.get_mut(32usize.checked_sub(bytes.len())?..)?
+        .copy_from_slice(bytes);
+    Some(word)
+}
+
+fn keccak(bytes: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(bytes);
+    keccak.finalize(&mut hash);
+    hash
+}
+
+fn is_address(bytes: &[u8]) -> bool {
+    bytes.len() == 20 && bytes.iter().any(|b| *b != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use k256::ecdsa::SigningKey;
+
+    use super::*;
+
+    fn int(value: u64) -> Option<eth::BigInt> {
+        Some(eth::BigInt {
+            bytes: trim(&value.to_be_bytes()).to_vec(),
+        })
+    }
+
+    /// The example transaction of EIP-155, signed by the key `0x4646…46`.
+    fn eip155() -> eth::TransactionTrace {
+        let raw = hex!(
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000"
+            "8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f"
+            "761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+        eth::TransactionTrace {
+            nonce: 9,
+            gas_price: int(20_000_000_000),
+            gas_limit: 21_000,
+            to: vec![0x35; 20],
+            value: int(1_000_000_000_000_000_000),
+            v: vec![0x25],
+            r: hex!("28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276").to_vec(),
+            s: hex!("67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").to_vec(),
+            hash: keccak(&raw).to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Signs `tx` for `chain_id` with the key `0x00…01`, filling `v`, `r`,
+    /// `s` and `hash`.
+    fn sign(mut tx: eth::TransactionTrace, chain_id: u64) -> Option<eth::TransactionTrace> {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        let key = SigningKey::from_slice(&secret).ok()?;
+        let (prefix, unsigned) = fields(&tx, Some(chain_id))?;
+        let (signature, recovery) = key
+            .sign_prehash_recoverable(&keccak(&envelope(prefix, &unsigned)))
+            .ok()?;
+        let (r, s) = signature.split_bytes();
+        tx.v = vec![recovery.to_byte()];
+        tx.r = r.to_vec();
+        tx.s = s.to_vec();
+        let (prefix, mut signed) = fields(&tx, Some(chain_id))?;
+        signed.extend([rlp_int(&tx.v), rlp_int(&tx.r), rlp_int(&tx.s)]);
+        tx.hash = keccak(&envelope(prefix, &signed)).to_vec();
+        Some(tx)
+    }
+
+    fn dynamic_fee() -> eth::TransactionTrace {
+        eth::TransactionTrace {
+            r#type: eth::transaction_trace::Type::TrxTypeDynamicFee as i32,
+            nonce: 3,
+            max_priority_fee_per_gas: int(30_000_000_000),
+            max_fee_per_gas: int(90_000_000_000),
+            gas_limit: 60_000,
+            to: vec![0xaa; 20],
+            input: hex!("a9059cbb").to_vec(),
+            access_list: vec![eth::AccessTuple {
+                address: vec![0xbb; 20],
+                storage_keys: vec![vec![0; 32], vec![0x11; 32]],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recovers_the_eip155_example() {
+        assert_eq!(
+            recover(&eip155()),
+            Some(hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"))
+        );
+    }
+
+    #[test]
+    fn recovers_typed_transactions_on_known_chains() {
+        let signer = hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf");
+        for profile in chain::PROFILES {
+            let tx = sign(dynamic_fee(), profile.chain_id);
+            assert_eq!(
+                tx.as_ref().and_then(recover),
+                Some(signer),
+                "{}",
+                profile.chain_id
+            );
+        }
+        let access_list = eth::TransactionTrace {
+            r#type: eth::transaction_trace::Type::TrxTypeAccessList as i32,
+            gas_price: int(40_000_000_000),
+            ..dynamic_fee()
+        };
+        assert_eq!(
+            sign(access_list, 137).as_ref().and_then(recover),
+            Some(signer)
+        );
+    }
+
+    #[test]
+    fn unknown_when_the_trace_misses_signed_fields() {
+        // Other chains' typed transactions, and traces without the fee caps
+        // or the hash, can't be rebuilt.
+        let tx = sign(dynamic_fee(), 1);
+        assert!(tx.is_some());
+        assert_eq!(tx.as_ref().and_then(recover), None);
+        let tx = sign(dynamic_fee(), 137).map(|tx| eth::TransactionTrace {
+            max_fee_per_gas: None,
+            ..tx
+        });
+        assert!(tx.is_some());
+        assert_eq!(tx.as_ref().and_then(recover), None);
+        let tx = eth::TransactionTrace {
+            hash: Vec::new(),
+            ..eip155()
+        };
+        assert_eq!(recover(&tx), None);
+        assert_eq!(sender(&eth::TransactionTrace::default()), None);
+    }
+
+    #[test]
+    fn sender_prefers_from_then_recovers() {
+        let tx = eth::TransactionTrace {
+            from: vec![0; 20],
+            ..eip155()
+        };
+        assert_eq!(
+            sender(&tx).as_deref(),
+            Some(hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").as_slice())
+        );
+        let tx = eth::TransactionTrace {
+            from: vec![0xcc; 20],
+            ..eip155()
+        };
+        assert_eq!(sender(&tx).as_deref(), Some([0xcc; 20].as_slice()));
+    }
+}
@@ -0,0 +1,133 @@
+use std::ops::RangeInclusive;
+
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::retention::{self, Lists};
+use crate::u256::U256;
+use crate::{abi, amount, calls, deployer, fingerprint, sender};
+
+// Approval(address,address,uint256)
+const APPROVAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+
+/// Prefix of every key of one setup window, so a whole window can be
+/// dropped once no lookback reaches it.
+pub fn window_prefix(window: u64) -> String {
+    format!("setup:{}:", window)
+}
+
+/// Key listing the setup actions an actor took in one window as
+/// `block:tx_hash:fingerprint` entries joined by `;`.
+pub fn setup_key(window: u64, actor: &str) -> String {
+    format!("{}{}", window_prefix(window), actor)
+}
+
+/// Fingerprints of the actions in `tx` that typically prepare an exploit:
+/// contract deployments, approvals granted by the sender, and test transfers
+/// of at most one whole token. Deployments have no log and are fingerprinted
+/// by call index as `ContractDeployment`.
+pub fn setups(
+    tx: &eth::TransactionTrace,
+    sender: &[u8],
+    annotations: &Annotations,
+) -> Vec<(u64, String)> {
+    let mut out: Vec<(u64, String)> = Vec::new();
+    for (ordinal, _) in deployer::created(tx) {
+        let index = tx
+            .calls
+            .iter()
+            .find(|c| c.begin_ordinal == ordinal)
+            .map(|c| c.index)
+            .unwrap_or_default();
+        out.push((
+            ordinal,
+            fingerprint::of(&tx.hash, index as u64, "ContractDeployment"),
+        ));
+    }
+
+    for log in calls::tx_logs(tx).0.iter() {
+        let event_type = if let Some(transfer) = abi::transfer(log) {
+            let token = format!("0x{}", hex::encode(&log.address));
+            let Some(decimals) = amount::token_decimals(annotations, &token) else {
+                continue;
+            };
+            if transfer.from != sender || transfer.amount > U256::exp10(decimals as usize) {
+                continue;
+            }
+            "Transfer"
+        } else if log.topics.first().map(Vec::as_slice) == Some(&APPROVAL_TOPIC[..])
+            && log.topics.get(1).and_then(|t| abi::address_bytes(t)) == Some(sender)
+        {
+            "Approval"
+        } else {
+            continue;
+        };
+        out.push((
+            log.ordinal,
+            fingerprint::of(&tx.hash, log.index as u64, event_type),
+        ));
+    }
+    out
+}
+
+/// The block's setup actions as `(ordinal, setup_key, encoded setup)`, to
+/// add to the actor's list of the block's window.
+pub fn entries(
+    blk: &eth::Block,
+    window: u64,
+    annotations: &Annotations,
+) -> Vec<(u64, String, String)> {
+    let mut out = Vec::new();
+    for tx in blk.transaction_traces.iter() {
+        let Some(sender) = sender::sender(tx) else {
+            continue;
+        };
+        let key = setup_key(window, &format!("0x{}", hex::encode(&sender)));
+        for (ordinal, fingerprint) in setups(tx, &sender, annotations) {
+            out.push((
+                ordinal,
+                key.clone(),
+                encode_setup(blk.number, &tx.hash, &fingerprint),
+            ));
+        }
+    }
+    out
+}
+
+pub fn encode_setup(block_number: u64, tx_hash: &[u8], fingerprint: &str) -> String {
+    format!(
+        "{}:0x{}:{};",
+        block_number,
+        hex::encode(tx_hash),
+        fingerprint
+    )
+}
+
+/// Setup fingerprints `actor` recorded a number of blocks before
+/// `block_number` that falls in `ages`, excluding those of the transaction
+/// `tx_hash` itself. Reads the windows of `window_blocks` that `ages` can
+/// reach, so its end must not exceed `window_blocks`.
+pub fn recent_setups(
+    setups: &Lists,
+    actor: &str,
+    tx_hash: &str,
+    block_number: u64,
+    window_blocks: u64,
+    ages: &RangeInclusive<u64>,
+) -> Vec<String> {
+    let entries: String = retention::live(block_number, window_blocks)
+        .filter_map(|window| setups.get(&setup_key(window, actor)))
+        .collect();
+    entries
+        .split(';')
+        .filter_map(|entry| {
+            let mut parts = entry.split(':');
+            let block: u64 = parts.next()?.parse().ok()?;
+            let setup_tx = parts.next()?;
+            let fingerprint = parts.next()?;
+            (setup_tx != tx_hash && ages.contains(&block_number.saturating_sub(block)))
+                .then(|| fingerprint.to_string())
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+//! Entry points for the sink check in `sinkcheck/`. It replays fixture blocks
+//! through `map_candidate_events` and `db_out` and applies the changes to a
+//! database created from `schema.sql`, the way the SQL sink would.
+
+use anyhow::{bail, Error};
+use prost::Message;
+use substreams_ethereum::pb::eth::v2 as eth;
+
+pub use substreams_ethereum::pb::eth::v2::Block;
+
+use crate::database;
+use crate::params::{self, Params};
+use crate::pb::pulseproof::CandidateEvents;
+use crate::pb::sf::substreams::sink::database::v1::{table_change, TableChange};
+
+/// Decodes a fixture block, a protobuf-encoded `sf.ethereum.type.v2.Block`.
+pub fn decode_block(bytes: &[u8]) -> Result<eth::Block, Error> {
+    Ok(eth::Block::decode(bytes)?)
+}
+
+fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn statement(change: &TableChange) -> Result<String, Error> {
+    let Some(table_change::PrimaryKey::CompositePk(key)) = &change.primary_key else {
+        bail!("table {} change has no composite primary key", change.table);
+    };
+    if change.operation != table_change::Operation::Create as i32 {
+        bail!(
+            "table {} change has operation {}, only creates are replayed",
+            change.table,
+            change.operation
+        );
+    }
+    let columns: Vec<(&str, &str)> = key
+        .keys
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .chain(
+            change
+                .fields
+                .iter()
+                .map(|field| (field.name.as_str(), field.new_value.as_str())),
+        )
+        .collect();
+    Ok(format!(
+        "insert into {} ({}) values ({});",
+        change.table,
+        columns
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        columns
+            .iter()
+            .map(|(_, value)| literal(value))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ))
+}
+
+/// The rows `db_out` creates for a block under `params`, as `(table, insert
+/// statement)` pairs in output order. Values are quoted literals, which
+/// Postgres casts to each column's type, so a column the schema lacks, a
+/// type it can't parse or a missing `not null` column fails the insert.
+pub fn inserts(blk: &eth::Block, raw_params: &str) -> Result<Vec<(String, String)>, Error> {
+    let params = Params::parse(raw_params)?;
+    let events = CandidateEvents {
+        events: crate::candidate_events(&params, blk),
+        config_epoch: params::config_epoch(raw_params, ""),
+    };
+    database::database_changes(&events)
+        .table_changes
+        .iter()
+        .map(|change| Ok((change.table.clone(), statement(change)?)))
+        .collect()
+}
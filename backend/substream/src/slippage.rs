@@ -0,0 +1,211 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::Tag;
+use crate::u256::U256;
+use crate::{abi, amount, calls};
+
+// Uniswap V2 router (and forks)
+// swapExactTokensForTokens(uint256,uint256,address[],address,uint256)
+const V2_TOKENS_FOR_TOKENS: [u8; 4] = hex_literal::hex!("38ed1739");
+// swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)
+const V2_TOKENS_FOR_TOKENS_FOT: [u8; 4] = hex_literal::hex!("5c11d795");
+// swapExactTokensForETH(uint256,uint256,address[],address,uint256)
+const V2_TOKENS_FOR_ETH: [u8; 4] = hex_literal::hex!("18cbafe5");
+// swapExactTokensForETHSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)
+const V2_TOKENS_FOR_ETH_FOT: [u8; 4] = hex_literal::hex!("791ac947");
+// swapExactETHForTokens(uint256,address[],address,uint256)
+const V2_ETH_FOR_TOKENS: [u8; 4] = hex_literal::hex!("7ff36ab5");
+// swapExactETHForTokensSupportingFeeOnTransferTokens(uint256,address[],address,uint256)
+const V2_ETH_FOR_TOKENS_FOT: [u8; 4] = hex_literal::hex!("b6f9de95");
+// Uniswap V3 SwapRouter
+// exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))
+const V3_EXACT_INPUT_SINGLE: [u8; 4] = hex_literal::hex!("414bf389");
+// exactInput((bytes,address,uint256,uint256,uint256))
+const V3_EXACT_INPUT: [u8; 4] = hex_literal::hex!("c04b8d59");
+// Uniswap SwapRouter02, same calls without the deadline
+// exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))
+const V3_02_EXACT_INPUT_SINGLE: [u8; 4] = hex_literal::hex!("04e45aaf");
+// exactInput((bytes,address,uint256,uint256))
+const V3_02_EXACT_INPUT: [u8; 4] = hex_literal::hex!("b858183f");
+// 1inch AggregationRouterV5
+// swap(address,(address,address,address,address,uint256,uint256,uint256),bytes,bytes)
+const ONEINCH_V5_SWAP: [u8; 4] = hex_literal::hex!("12aa3caf");
+
+/// A minimum output at or below this many base units accepts any price,
+/// i.e. a 100% slippage tolerance.
+const ABSURD_MIN_OUT: u64 = 1;
+
+/// The slippage-relevant parameters of one exact-input router call.
+struct Intent {
+    protocol: Tag,
+    function: &'static str,
+    token_in: Option<String>,
+    token_out: Option<String>,
+    /// `None` when the input is the call value rather than an argument.
+    amount_in: Option<U256>,
+    min_out: U256,
+    /// SwapRouter02 calls carry no deadline.
+    deadline: Option<U256>,
+}
+
+/// Flags router swaps that executed with a minimum output of (almost) zero,
+/// leaving the swapper fully exposed to sandwiching or a manipulated pool.
+/// `log_index` carries the index of the router call.
+pub fn slippage_events(
+    tx: &eth::TransactionTrace,
+    block_number: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+    for (call_index, caller, router, input) in calls::frames(tx) {
+        let Some(intent) = decode(input) else {
+            continue;
+        };
+        if intent.min_out > U256::from(ABSURD_MIN_OUT) {
+            continue;
+        }
+
+        let mut event = CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+            block_number,
+            log_index: call_index as u64,
+            contract_address: format!("0x{}", hex::encode(router)),
+            event_type: "SlippageAbuseCandidate".to_string(),
+            from: format!("0x{}", hex::encode(&caller)),
+            metadata: metadata(&intent, annotations),
+            ..Default::default()
+        };
+        intent.protocol.apply(&mut event);
+        events.push(event);
+    }
+    events
+}
+
+fn metadata(intent: &Intent, annotations: &Annotations) -> String {
+    let token_in = intent.token_in.as_deref().unwrap_or("native");
+    let token_out = intent.token_out.as_deref().unwrap_or("native");
+
+    let mut out = format!(
+        "{{\"function\":\"{}\",\"token_in\":\"{}\",\"token_out\":\"{}\"",
+        intent.function, token_in, token_out
+    );
+    if let Some(amount_in) = intent.amount_in {
+        out.push_str(&format!(
+            ",{}",
+            amount::json_fields(annotations, "amount_in", amount_in, token_in)
+        ));
+    }
+    out.push_str(&format!(
+        ",{}",
+        amount::json_fields(annotations, "min_out", intent.min_out, token_out)
+    ));
+    if let Some(deadline) = intent.deadline {
+        out.push_str(&format!(",\"deadline\":\"{}\"", deadline));
+    }
+    out.push('}');
+    out
+}
+
+fn decode(input: &[u8]) -> Option<Intent> {
+    let selector = input.get(..4)?;
+    let args = input.get(4..)?;
+
+    if selector == V2_TOKENS_FOR_TOKENS
+        || selector == V2_TOKENS_FOR_TOKENS_FOT
+        || selector == V2_TOKENS_FOR_ETH
+        || selector == V2_TOKENS_FOR_ETH_FOT
+    {
+        let path = abi::word_array(args, 2)?;
+        let to_native = selector == V2_TOKENS_FOR_ETH || selector == V2_TOKENS_FOR_ETH_FOT;
+        return Some(Intent {
+            protocol: Tag::protocol("uniswap", "v2"),
+            function: if to_native {
+                "swapExactTokensForETH"
+            } else {
+                "swapExactTokensForTokens"
+            },
+            token_in: path.first().and_then(|t| abi::word_address(t, 0)),
+            token_out: match to_native {
+                true => None,
+                false => path.last().and_then(|t| abi::word_address(t, 0)),
+            },
+            amount_in: Some(abi::word_u256(args, 0)?),
+            min_out: abi::word_u256(args, 1)?,
+            deadline: Some(abi::word_u256(args, 4)?),
+        });
+    }
+
+    if selector == V2_ETH_FOR_TOKENS || selector == V2_ETH_FOR_TOKENS_FOT {
+        let path = abi::word_array(args, 1)?;
+        return Some(Intent {
+            protocol: Tag::protocol("uniswap", "v2"),
+            function: "swapExactETHForTokens",
+            token_in: None,
+            token_out: path.last().and_then(|t| abi::word_address(t, 0)),
+            amount_in: None,
+            min_out: abi::word_u256(args, 0)?,
+            deadline: Some(abi::word_u256(args, 3)?),
+        });
+    }
+
+    if selector == V3_EXACT_INPUT_SINGLE || selector == V3_02_EXACT_INPUT_SINGLE {
+        // The params struct is static, so it is encoded inline.
+        let (deadline, amount_at) = if selector == V3_EXACT_INPUT_SINGLE {
+            (Some(abi::word_u256(args, 4)?), 5)
+        } else {
+            (None, 4)
+        };
+        return Some(Intent {
+            protocol: Tag::protocol("uniswap", "v3"),
+            function: "exactInputSingle",
+            token_in: Some(abi::word_address(args, 0)?),
+            token_out: Some(abi::word_address(args, 1)?),
+            amount_in: Some(abi::word_u256(args, amount_at)?),
+            min_out: abi::word_u256(args, amount_at + 1)?,
+            deadline,
+        });
+    }
+
+    if selector == V3_EXACT_INPUT || selector == V3_02_EXACT_INPUT {
+        // The params struct holds `bytes path`, so it sits behind an offset.
+        let params = args.get(abi::word_usize(args, 0)?..)?;
+        let path = abi::word_bytes(params, 0)?;
+        let (Some(first), Some(last)) = (path.first_chunk::<20>(), path.last_chunk::<20>()) else {
+            return None;
+        };
+        if path.len() < 40 {
+            return None;
+        }
+        let (deadline, amount_at) = if selector == V3_EXACT_INPUT {
+            (Some(abi::word_u256(params, 2)?), 3)
+        } else {
+            (None, 2)
+        };
+        return Some(Intent {
+            protocol: Tag::protocol("uniswap", "v3"),
+            function: "exactInput",
+            token_in: Some(format!("0x{}", hex::encode(first))),
+            token_out: Some(format!("0x{}", hex::encode(last))),
+            amount_in: Some(abi::word_u256(params, amount_at)?),
+            min_out: abi::word_u256(params, amount_at + 1)?,
+            deadline,
+        });
+    }
+
+    if selector == ONEINCH_V5_SWAP {
+        // `desc` is static and follows the executor inline.
+        return Some(Intent {
+            protocol: Tag::protocol("1inch", "v5"),
+            function: "swap",
+            token_in: Some(abi::word_address(args, 1)?),
+            token_out: Some(abi::word_address(args, 2)?),
+            amount_in: Some(abi::word_u256(args, 5)?),
+            min_out: abi::word_u256(args, 6)?,
+            deadline: None,
+        });
+    }
+
+    None
+}
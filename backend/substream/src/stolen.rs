@@ -0,0 +1,53 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::params::TokenRange;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::Tag;
+use crate::{abi, decoded, nft};
+
+/// Emits `StolenAssetMovement` for every NFT transfer log moving a token in
+/// `flagged`, so recovery and marketplace-blocking workflows can follow it.
+/// One event per log; `metadata` lists the flagged ids it moved and the
+/// typed `nft_transfer` has all of them.
+pub fn movements(
+    tx_hash: &[u8],
+    logs: &[eth::Log],
+    block_number: u64,
+    flagged: &[TokenRange],
+) -> Vec<CandidateEvent> {
+    if flagged.is_empty() {
+        return Vec::new();
+    }
+    let mut events = Vec::new();
+    for log in logs.iter() {
+        let items = nft::items(log);
+        let hits: Vec<String> = items
+            .iter()
+            .filter(|item| {
+                flagged
+                    .iter()
+                    .any(|range| range.contains(&item.collection, item.id))
+            })
+            .map(|item| format!("\"{}\"", item.id))
+            .collect();
+        let Some(first) = items.first().filter(|_| !hits.is_empty()) else {
+            continue;
+        };
+        let mut event = CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(tx_hash)),
+            block_number,
+            log_index: log.index as u64,
+            contract_address: first.collection.clone(),
+            event_signature: abi::signature(log),
+            event_type: "StolenAssetMovement".to_string(),
+            from: first.from.clone(),
+            to: first.to.clone(),
+            metadata: format!("{{\"flagged_token_ids\":[{}]}}", hits.join(",")),
+            nft_transfer: decoded::nft_transfer(log),
+            ..Default::default()
+        };
+        Tag::standard(nft::standard(log)).apply(&mut event);
+        events.push(event);
+    }
+    events
+}
@@ -0,0 +1,188 @@
+use crate::amount;
+use crate::params::Params;
+use crate::pb::pulseproof::{CandidateEvent, SuspiciousTransaction};
+use crate::u256::U256;
+
+// Points each heuristic adds to a transaction's score, which is capped at 100.
+const FLASH_SWAP_APPROVAL: u32 = 50;
+const FLASH_FUNDED: u32 = 20;
+const VICTIM_DRAIN: u32 = 30;
+const CRITICAL_DETECTION: u32 = 50;
+const HIGH_DETECTION: u32 = 25;
+const MAX_SCORE: u32 = 100;
+
+/// Groups `events` by transaction and scores each group, returning those
+/// that reach `params.suspicion_min_score`. Block-wide events have no
+/// transaction and are never scored.
+pub fn suspicious_transactions(
+    events: &[CandidateEvent],
+    params: &Params,
+) -> Vec<SuspiciousTransaction> {
+    let mut groups: Vec<(&str, Vec<&CandidateEvent>)> = Vec::new();
+    for event in events.iter().filter(|e| !e.transaction_hash.is_empty()) {
+        match groups
+            .iter_mut()
+            .find(|(hash, _)| *hash == event.transaction_hash)
+        {
+            Some((_, group)) => group.push(event),
+            None => groups.push((&event.transaction_hash, vec![event])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(hash, group)| {
+            let (score, reasons) = score(&group, params);
+            let block_number = group.first()?.block_number;
+            (score >= params.suspicion_min_score).then(|| SuspiciousTransaction {
+                transaction_hash: hash.to_string(),
+                block_number,
+                score,
+                reasons,
+                victims: victims(&group, params),
+                contributing: group.into_iter().cloned().collect(),
+            })
+        })
+        .collect()
+}
+
+/// Addresses the group's ERC20 transfers leave net down at least
+/// `params.victim_min_units` of some token, in the order they first move it.
+/// The threshold needs the token's decimals, so with a nonzero threshold only
+/// losses of listed tokens qualify.
+fn victims(group: &[&CandidateEvent], params: &Params) -> Vec<String> {
+    // (token, address, received, sent), in order of first appearance.
+    let mut flows: Vec<(&str, &str, U256, U256)> = Vec::new();
+    for event in group {
+        let Some(transfer) = event.transfer.as_ref() else {
+            continue;
+        };
+        let Ok(value) = U256::from_dec_str(&transfer.value) else {
+            continue;
+        };
+        let token = event.contract_address.as_str();
+        for (address, incoming) in [
+            (transfer.from.as_str(), false),
+            (transfer.to.as_str(), true),
+        ] {
+            let index = match flows
+                .iter()
+                .position(|(t, a, _, _)| *t == token && *a == address)
+            {
+                Some(index) => index,
+                None => {
+                    flows.push((token, address, U256::zero(), U256::zero()));
+                    flows.len() - 1
+                }
+            };
+            let Some((_, _, received, sent)) = flows.get_mut(index) else {
+                continue;
+            };
+            if incoming {
+                *received = received.saturating_add(value);
+            } else {
+                *sent = sent.saturating_add(value);
+            }
+        }
+    }
+
+    let annotations = params.annotations();
+    let mut victims: Vec<String> = Vec::new();
+    for (token, address, received, sent) in flows {
+        let Some(loss) = sent.checked_sub(received).filter(|loss| !loss.is_zero()) else {
+            continue;
+        };
+        let large = params.victim_min_units == 0
+            || amount::at_least_units(&annotations, loss, token, params.victim_min_units);
+        if large && !victims.iter().any(|v| v == address) {
+            victims.push(address.to_string());
+        }
+    }
+    victims
+}
+
+fn score(group: &[&CandidateEvent], params: &Params) -> (u32, Vec<String>) {
+    let has = |event_type: &str| group.iter().any(|e| e.event_type == event_type);
+    let mut score = 0;
+    let mut reasons = Vec::new();
+    let mut add = |points: u32, reason: &str| {
+        score += points;
+        reasons.push(reason.to_string());
+    };
+
+    let flash_funded = group.iter().any(|e| e.flash_funded);
+    if flash_funded && has("Swap") && has("Approval") {
+        add(FLASH_SWAP_APPROVAL, "flash_swap_approval");
+    } else if flash_funded {
+        add(FLASH_FUNDED, "flash_funded");
+    }
+
+    // One address losing tokens to many transfers in one transaction looks
+    // like a drainer sweeping its approvals.
+    let mut senders: Vec<(&str, usize)> = Vec::new();
+    for event in group.iter().filter(|e| {
+        matches!(
+            e.event_type.as_str(),
+            "Transfer" | "LargeTransfer" | "NftTransfer"
+        )
+    }) {
+        match senders.iter_mut().find(|(from, _)| *from == event.from) {
+            Some((_, count)) => *count += 1,
+            None => senders.push((&event.from, 1)),
+        }
+    }
+    if senders
+        .iter()
+        .any(|(_, count)| *count >= params.victim_min_transfers)
+    {
+        add(VICTIM_DRAIN, "victim_drain");
+    }
+
+    if group.iter().any(|e| e.severity == "critical") {
+        add(CRITICAL_DETECTION, "critical_detection");
+    } else if group.iter().any(|e| e.severity == "high") {
+        add(HIGH_DETECTION, "high_detection");
+    }
+
+    (score.min(MAX_SCORE), reasons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::pulseproof::TransferParams;
+
+    fn transfer(token: &str, from: &str, to: &str, value: u64) -> CandidateEvent {
+        CandidateEvent {
+            transaction_hash: "0x11".to_string(),
+            event_type: "Transfer".to_string(),
+            contract_address: token.to_string(),
+            severity: "critical".to_string(),
+            transfer: Some(TransferParams {
+                from: from.to_string(),
+                to: to.to_string(),
+                value: value.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn victims_are_the_net_losers_of_the_transaction() {
+        let events = [
+            transfer("0xt1", "0xvictim", "0xdrainer", 100),
+            // A round trip nets out.
+            transfer("0xt1", "0xrouter", "0xpool", 40),
+            transfer("0xt1", "0xpool", "0xrouter", 40),
+            // Getting some back still leaves a loss.
+            transfer("0xt2", "0xother", "0xdrainer", 70),
+            transfer("0xt2", "0xdrainer", "0xother", 20),
+        ];
+        let victims: Vec<Vec<String>> = suspicious_transactions(&events, &Params::default())
+            .into_iter()
+            .map(|t| t.victims)
+            .collect();
+        assert_eq!(victims, [["0xvictim", "0xother"]]);
+    }
+}
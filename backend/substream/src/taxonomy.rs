@@ -0,0 +1,57 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::nft;
+use crate::pb::pulseproof::CandidateEvent;
+
+pub const ERC20: &str = "ERC-20";
+pub const ERC721: &str = "ERC-721";
+pub const ERC1155: &str = "ERC-1155";
+pub const ERC4626: &str = "ERC-4626";
+pub const EIP7702: &str = "EIP-7702";
+
+/// What produced an event: the protocol (lowercase slug) and its version when
+/// the event comes from protocol-specific contracts, and the token or account
+/// standard it follows. Unknown parts stay empty.
+#[derive(Clone, Copy, Default)]
+pub struct Tag {
+    pub protocol: &'static str,
+    pub protocol_version: &'static str,
+    pub standard: &'static str,
+}
+
+impl Tag {
+    pub fn standard(standard: &'static str) -> Self {
+        Tag {
+            standard,
+            ..Default::default()
+        }
+    }
+
+    pub fn protocol(protocol: &'static str, protocol_version: &'static str) -> Self {
+        Tag {
+            protocol,
+            protocol_version,
+            ..Default::default()
+        }
+    }
+
+    pub fn apply(self, event: &mut CandidateEvent) {
+        event.protocol = self.protocol.to_string();
+        event.protocol_version = self.protocol_version.to_string();
+        event.standard = self.standard.to_string();
+    }
+}
+
+/// Tag for a log classified as `event_type`. ERC-721 shares the Transfer and
+/// Approval signatures with ERC-20 and differs only in indexing the token id
+/// as a fourth topic. `ApprovalForAll` is shared by ERC-721 and ERC-1155 and
+/// gets no standard.
+pub fn for_log(event_type: &str, log: &eth::Log) -> Tag {
+    match event_type {
+        "Approval" if log.topics.len() == 4 => Tag::standard(ERC721),
+        "Transfer" | "Approval" => Tag::standard(ERC20),
+        "NftTransfer" => Tag::standard(nft::standard(log)),
+        "Swap" => Tag::protocol("uniswap", "v2"),
+        _ => Tag::default(),
+    }
+}
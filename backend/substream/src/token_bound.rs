@@ -0,0 +1,99 @@
+use substreams::store::{StoreGet, StoreGetString};
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::pb::pulseproof::{CandidateEvent, TokenBoundAccount};
+use crate::u256::U256;
+use crate::{abi, calls};
+
+// Canonical ERC-6551 registries, v0.3 and v0.2. Other contracts can emit the
+// same events to make any address look token-bound, so only these count.
+const REGISTRY_V3: [u8; 20] = hex_literal::hex!("000000006551c19487814612e58fe06813775758");
+const REGISTRY_V2: [u8; 20] = hex_literal::hex!("02101dfb77fde026414827fdc604ddaf224f0921");
+// ERC6551AccountCreated(address,address,bytes32,uint256,address,uint256)
+const ACCOUNT_CREATED_V3_TOPIC: [u8; 32] =
+    hex_literal::hex!("79f19b3655ee38b1ce526556b7731a20c8f218fbda4a3990b6cc4172fdf88722");
+// AccountCreated(address,address,uint256,address,uint256,uint256)
+const ACCOUNT_CREATED_V2_TOPIC: [u8; 32] =
+    hex_literal::hex!("07fba7bba1191da7ee1155dcfa0030701c9c9a9cc34a93b991fc6fd0c9268d8f");
+
+/// Key holding the NFT controlling a token-bound account, as
+/// `chain_id:collection:token_id`.
+pub fn account_key(account: &str) -> String {
+    format!("tba:{}", account)
+}
+
+pub fn encode(account: &TokenBoundAccount) -> String {
+    format!(
+        "{}:{}:{}",
+        account.chain_id, account.collection, account.token_id
+    )
+}
+
+fn decode(account: &str, value: &str) -> Option<TokenBoundAccount> {
+    let mut parts = value.splitn(3, ':');
+    Some(TokenBoundAccount {
+        account: account.to_string(),
+        chain_id: parts.next()?.parse().ok()?,
+        collection: parts.next()?.to_string(),
+        token_id: parts.next()?.to_string(),
+    })
+}
+
+/// Decodes an account creation logged by a canonical registry.
+pub fn created(log: &eth::Log) -> Option<TokenBoundAccount> {
+    let topic0 = log.topics.first()?.as_slice();
+    let chain_id = |word: U256| (word <= U256::from(u64::MAX)).then(|| word.as_u64());
+    if log.address == REGISTRY_V3 && topic0 == ACCOUNT_CREATED_V3_TOPIC && log.topics.len() == 4 {
+        return Some(TokenBoundAccount {
+            account: abi::word_address(&log.data, 0)?,
+            chain_id: chain_id(abi::word_u256(&log.data, 2)?)?,
+            collection: abi::topic_address(log, 2)?,
+            token_id: abi::topic_u256(log, 3)?.to_string(),
+        });
+    }
+    if log.address == REGISTRY_V2 && topic0 == ACCOUNT_CREATED_V2_TOPIC {
+        return Some(TokenBoundAccount {
+            account: abi::word_address(&log.data, 0)?,
+            chain_id: chain_id(abi::word_u256(&log.data, 2)?)?,
+            collection: abi::word_address(&log.data, 3)?,
+            token_id: abi::word_u256(&log.data, 4)?.to_string(),
+        });
+    }
+    None
+}
+
+/// `TokenBoundAccountCreated` events for the block's registry logs, with the
+/// account as `contract_address` and the controlling NFT in `token_bound`.
+pub fn creations(blk: &eth::Block) -> Vec<CandidateEvent> {
+    calls::block_logs(blk)
+        .filter_map(|(tx, log)| {
+            let account = created(&log)?;
+            Some(CandidateEvent {
+                transaction_hash: format!("0x{}", hex::encode(&tx.hash)),
+                block_number: blk.number,
+                log_index: log.index as u64,
+                contract_address: account.account.clone(),
+                event_signature: abi::signature(&log),
+                event_type: "TokenBoundAccountCreated".to_string(),
+                owner: account.collection.clone(),
+                token_bound: Some(account),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// The token-bound account an event involves, preferring the side that
+/// acted (`from`, `owner`) over the one acted on.
+pub fn involved(event: &CandidateEvent, accounts: &StoreGetString) -> Option<TokenBoundAccount> {
+    [
+        &event.from,
+        &event.owner,
+        &event.spender,
+        &event.to,
+        &event.contract_address,
+    ]
+    .into_iter()
+    .filter(|address| !address.is_empty())
+    .find_map(|address| decode(address, &accounts.get_last(account_key(address))?))
+}
@@ -0,0 +1,136 @@
+use substreams::store::{StoreGet, StoreGetString};
+use substreams_ethereum::pb::eth::rpc::{RpcCall, RpcCalls, RpcResponse};
+use substreams_ethereum::pb::eth::v2 as eth;
+use substreams_ethereum::rpc;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::u256::U256;
+use crate::{abi, amount, calls};
+
+// Approval(address,address,uint256)
+const APPROVAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+// symbol()
+const SYMBOL: [u8; 4] = hex_literal::hex!("95d89b41");
+// decimals()
+const DECIMALS: [u8; 4] = hex_literal::hex!("313ce567");
+
+/// Longest symbol kept; anything longer is a token showing off, not a ticker.
+const MAX_SYMBOL_LEN: usize = 32;
+
+/// Key of a token in `store_tokens_seen` and `store_token_metadata`.
+pub fn token_key(token: &str) -> String {
+    format!("token:{}", token)
+}
+
+/// ERC20 tokens the block's Transfer and Approval logs come from whose
+/// decimals aren't in `data/annotations.json`, first log first.
+pub fn unlisted_tokens(blk: &eth::Block, annotations: &Annotations) -> Vec<(u64, String)> {
+    let mut out: Vec<(u64, String)> = Vec::new();
+    for (_, log) in calls::block_logs(blk) {
+        let approval =
+            log.topics.len() == 3 && log.topics.first().is_some_and(|t| *t == APPROVAL_TOPIC);
+        if !approval && abi::transfer(&log).is_none() {
+            continue;
+        }
+        let token = format!("0x{}", hex::encode(&log.address));
+        if amount::token_decimals(annotations, &token).is_none()
+            && !out.iter().any(|(_, t)| *t == token)
+        {
+            out.push((log.ordinal, token));
+        }
+    }
+    out
+}
+
+/// Calls `decimals()` and `symbol()` on each token, returning `decimals:symbol`
+/// for those that answered `decimals()`. The symbol may be empty.
+pub fn resolve(tokens: &[String]) -> Vec<(String, String)> {
+    let calls = tokens
+        .iter()
+        .flat_map(|token| {
+            let to_addr = hex::decode(&token[2..]).unwrap_or_default();
+            [DECIMALS, SYMBOL].map(|selector| RpcCall {
+                to_addr: to_addr.clone(),
+                data: selector.to_vec(),
+            })
+        })
+        .collect();
+    let responses = rpc::eth_call(&RpcCalls { calls }).responses;
+
+    tokens
+        .iter()
+        .zip(responses.chunks(2))
+        .filter_map(|(token, answers)| {
+            let [decimals, symbol] = answers else {
+                return None;
+            };
+            let decimals = decode_decimals(decimals)?;
+            let symbol = decode_symbol(symbol).unwrap_or_default();
+            Some((token.clone(), format!("{}:{}", decimals, symbol)))
+        })
+        .collect()
+}
+
+fn decode_decimals(response: &RpcResponse) -> Option<u8> {
+    if response.failed {
+        return None;
+    }
+    let decimals = abi::word_u256(&response.raw, 0)?;
+    // 10^77 is the largest power of ten a uint256 holds.
+    (decimals <= U256::from(77)).then(|| decimals.as_u32() as u8)
+}
+
+/// Reads a `string` symbol, or the `bytes32` older tokens (MKR, SAI) return.
+fn decode_symbol(response: &RpcResponse) -> Option<String> {
+    if response.failed {
+        return None;
+    }
+    let bytes = match abi::word_bytes(&response.raw, 0) {
+        Some(bytes) => bytes,
+        None if response.raw.len() == 32 => response.raw.split(|b| *b == 0).next()?,
+        None => return None,
+    };
+    let symbol: String = String::from_utf8_lossy(bytes)
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_SYMBOL_LEN)
+        .collect();
+    (!symbol.is_empty()).then_some(symbol)
+}
+
+/// Fills `token_symbol` and the decoded `value_decimal` of events on tokens
+/// `data/annotations.json` doesn't list, from what `store_token_metadata`
+/// resolved on chain.
+pub fn enrich(event: &mut CandidateEvent, tokens: &StoreGetString, annotations: &Annotations) {
+    if amount::token_decimals(annotations, &event.contract_address).is_some() {
+        return;
+    }
+    let Some(entry) = tokens.get_last(token_key(&event.contract_address)) else {
+        return;
+    };
+    let Some((decimals, symbol)) = entry.split_once(':') else {
+        return;
+    };
+    let Ok(decimals) = decimals.parse::<u8>() else {
+        return;
+    };
+    if event.token_symbol.is_empty() {
+        event.token_symbol = symbol.to_string();
+    }
+    let scale = |value: &str, value_decimal: &mut String| {
+        if !value_decimal.is_empty() {
+            return;
+        }
+        if let Ok(raw) = U256::from_dec_str(value) {
+            *value_decimal = amount::to_decimal(raw, decimals);
+        }
+    };
+    if let Some(transfer) = event.transfer.as_mut() {
+        scale(&transfer.value, &mut transfer.value_decimal);
+    }
+    if let Some(approval) = event.approval.as_mut() {
+        scale(&approval.value, &mut approval.value_decimal);
+    }
+}
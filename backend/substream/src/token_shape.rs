@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use substreams::store::{StoreGet, StoreGetInt64};
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::calls;
+use crate::pb::pulseproof::CandidateEvent;
+
+// Transfer(address,address,uint256), shared by ERC20 and ERC-721.
+const TRANSFER_TOPIC: [u8; 32] =
+    hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+// Approval(address,address,uint256), shared by ERC20 and ERC-721.
+const APPROVAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+// ApprovalForAll(address,address,bool)
+const APPROVAL_FOR_ALL_TOPIC: [u8; 32] =
+    hex_literal::hex!("17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31");
+// TransferSingle(address,address,address,uint256,uint256)
+const TRANSFER_SINGLE_TOPIC: [u8; 32] =
+    hex_literal::hex!("c3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62");
+// TransferBatch(address,address,address,uint256[],uint256[])
+const TRANSFER_BATCH_TOPIC: [u8; 32] =
+    hex_literal::hex!("4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb");
+
+/// Shapes a token log can have, as counted per contract.
+const FUNGIBLE: &str = "fungible";
+const NON_FUNGIBLE: &str = "non_fungible";
+const NONSTANDARD: &str = "nonstandard";
+
+/// Key counting the logs of one shape `contract` emitted.
+pub fn shape_key(contract: &str, shape: &str) -> String {
+    format!("shape:{}:{}", contract, shape)
+}
+
+/// Shape of a token log: Transfer and Approval with two indexed addresses and
+/// a 32-byte amount are fungible, with the token id indexed too and no data
+/// non-fungible, and any other layout nonstandard. ApprovalForAll and the
+/// ERC-1155 transfers are non-fungible. A fungible shape is also how ERC-721
+/// drafts that left the token id unindexed log, which is why contracts are
+/// classified on everything they emit.
+fn shape(log: &eth::Log) -> Option<&'static str> {
+    let topic0 = log.topics.first()?.as_slice();
+    if topic0 == TRANSFER_TOPIC || topic0 == APPROVAL_TOPIC {
+        return Some(match (log.topics.len(), log.data.len()) {
+            (3, 32) => FUNGIBLE,
+            (4, 0) => NON_FUNGIBLE,
+            _ => NONSTANDARD,
+        });
+    }
+    (topic0 == APPROVAL_FOR_ALL_TOPIC
+        || topic0 == TRANSFER_SINGLE_TOPIC
+        || topic0 == TRANSFER_BATCH_TOPIC)
+        .then_some(NON_FUNGIBLE)
+}
+
+/// Token logs of the block per emitting contract and shape.
+pub fn counts(blk: &eth::Block) -> BTreeMap<(String, &'static str), i64> {
+    let mut counts = BTreeMap::new();
+    for (_, log) in calls::block_logs(blk) {
+        if let Some(shape) = shape(&log) {
+            let contract = format!("0x{}", hex::encode(&log.address));
+            *counts.entry((contract, shape)).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// What `contract` has behaved like so far: `erc20` when it only logged
+/// fungible shapes (stray nonstandard logs aside), `nft` when it only logged
+/// non-fungible ones, and `nonstandard` when it mixed the two, as hybrid
+/// tokens and unindexed ERC-721 drafts do, or never logged a standard shape.
+/// Empty for contracts that never logged a token event.
+pub fn class(shapes: &StoreGetInt64, contract: &str) -> &'static str {
+    let seen = |shape| {
+        shapes
+            .get_last(shape_key(contract, shape))
+            .is_some_and(|count| count > 0)
+    };
+    match (seen(FUNGIBLE), seen(NON_FUNGIBLE), seen(NONSTANDARD)) {
+        (false, false, false) => "",
+        (true, false, _) => "erc20",
+        (false, true, _) => "nft",
+        _ => "nonstandard",
+    }
+}
+
+/// Sets `token_class` and routes transfers decoded as ERC20 away from the
+/// ERC20 reading when their contract isn't ERC20-like: a `LargeTransfer` is
+/// demoted to `Transfer`, since its amount may be a token id, and
+/// `nonstandard_abi` is set. Returns whether the event type changed.
+pub fn route(event: &mut CandidateEvent, class: &str) -> bool {
+    event.token_class = class.to_string();
+    if event.transfer.is_none() || class.is_empty() || class == "erc20" {
+        return false;
+    }
+    event.nonstandard_abi = true;
+    if event.event_type != "LargeTransfer" {
+        return false;
+    }
+    event.event_type = "Transfer".to_string();
+    true
+}
@@ -0,0 +1,70 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::params::Params;
+use crate::pb::pulseproof::{CandidateEvent, TraceBalanceChange, TraceCall, TraceStorageChange};
+use crate::u256;
+
+fn call(call: &eth::Call) -> TraceCall {
+    let hex = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+    TraceCall {
+        index: call.index,
+        parent_index: call.parent_index,
+        depth: call.depth,
+        call_type: eth::CallType::try_from(call.call_type)
+            .map(|call_type| call_type.as_str_name().to_string())
+            .unwrap_or_default(),
+        caller: hex(&call.caller),
+        address: hex(&call.address),
+        value: u256::from_pb(call.value.as_ref()).to_string(),
+        gas_limit: call.gas_limit,
+        gas_consumed: call.gas_consumed,
+        input: call.input.clone(),
+        output: call.return_data.clone(),
+        status_failed: call.status_failed,
+        status_reverted: call.status_reverted,
+        state_reverted: call.state_reverted,
+        failure_reason: call.failure_reason.clone(),
+        storage_changes: call
+            .storage_changes
+            .iter()
+            .map(|change| TraceStorageChange {
+                address: hex(&change.address),
+                key: hex(&change.key),
+                old_value: hex(&change.old_value),
+                new_value: hex(&change.new_value),
+            })
+            .collect(),
+        balance_changes: call
+            .balance_changes
+            .iter()
+            .map(|change| TraceBalanceChange {
+                address: hex(&change.address),
+                old_value: u256::from_pb(change.old_value.as_ref()).to_string(),
+                new_value: u256::from_pb(change.new_value.as_ref()).to_string(),
+                reason: eth::balance_change::Reason::try_from(change.reason)
+                    .map(|reason| reason.as_str_name().to_string())
+                    .unwrap_or_default(),
+            })
+            .collect(),
+    }
+}
+
+/// With the `critical_traces` param set, attaches the full call trace of
+/// their transaction to `critical` events. Run after `shadow`, so rules in
+/// shadow mode don't export traces. Blocks without call traces leave
+/// `trace` empty.
+pub fn attach(events: &mut [CandidateEvent], blk: &eth::Block, params: &Params) {
+    if !params.critical_traces {
+        return;
+    }
+    for event in events.iter_mut().filter(|e| e.severity == "critical") {
+        let Some(tx) = blk
+            .transaction_traces
+            .iter()
+            .find(|tx| format!("0x{}", hex::encode(&tx.hash)) == event.transaction_hash)
+        else {
+            continue;
+        };
+        event.trace = tx.calls.iter().map(call).collect();
+    }
+}
@@ -0,0 +1,21 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+pub use primitive_types::U256;
+
+/// Totals token amounts, pinning at `U256::MAX` rather than wrapping so a
+/// hostile token can't make a huge flow look small.
+pub fn saturating_sum<'a>(amounts: impl IntoIterator<Item = &'a U256>) -> U256 {
+    amounts
+        .into_iter()
+        .fold(U256::zero(), |acc, amount| acc.saturating_add(*amount))
+}
+
+/// Converts a Firehose big integer, saturating values wider than 256 bits and
+/// treating a missing value as zero.
+pub fn from_pb(value: Option<&eth::BigInt>) -> U256 {
+    match value {
+        Some(v) if v.bytes.len() <= 32 => U256::from_big_endian(&v.bytes),
+        Some(_) => U256::MAX,
+        None => U256::zero(),
+    }
+}
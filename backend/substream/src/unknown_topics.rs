@@ -0,0 +1,57 @@
+use substreams::store::{StoreGet, StoreGetInt64, StoreGetString};
+
+use crate::pb::pulseproof::{ContractTopicCounts, TopicCount};
+
+/// Digests are emitted once per week of block time.
+pub const DIGEST_PERIOD_SECONDS: u64 = 7 * 24 * 3600;
+
+// Unknown topics listed per contract in a digest.
+const DIGEST_TOP: usize = 10;
+
+/// Count key for an unmatched topic0 on a contract: `unknown:{contract}:{topic0}`.
+pub fn count_key(contract: &str, topic0: &str) -> String {
+    format!("unknown:{}:{}", contract, topic0)
+}
+
+/// Splits a count key back into `(contract, topic0)`.
+pub fn parse_count_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix("unknown:")?.split_once(':')
+}
+
+/// Index key listing every unmatched topic0 seen on a contract.
+pub fn index_key(contract: &str) -> String {
+    format!("topics:{}", contract)
+}
+
+/// The most frequent unmatched topic0s on `contract` since the stores' start
+/// block, descending.
+pub fn digest(
+    contract: &str,
+    counts: &StoreGetInt64,
+    index: &StoreGetString,
+) -> Option<ContractTopicCounts> {
+    let topics = index.get_last(index_key(contract))?;
+
+    let mut seen: Vec<&str> = Vec::new();
+    let mut top: Vec<TopicCount> = Vec::new();
+    for topic0 in topics.split(';').filter(|t| !t.is_empty()) {
+        if seen.contains(&topic0) {
+            continue;
+        }
+        seen.push(topic0);
+        let count = counts
+            .get_last(count_key(contract, topic0))
+            .unwrap_or_default();
+        top.push(TopicCount {
+            topic0: topic0.to_string(),
+            count: count.max(0) as u64,
+        });
+    }
+    top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.topic0.cmp(&b.topic0)));
+    top.truncate(DIGEST_TOP);
+
+    Some(ContractTopicCounts {
+        contract: contract.to_string(),
+        topics: top,
+    })
+}
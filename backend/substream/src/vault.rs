@@ -0,0 +1,147 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::abi;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::{self, Tag};
+use crate::u256::U256;
+
+// ERC-4626 Deposit(address,address,uint256,uint256)
+const DEPOSIT_TOPIC: [u8; 32] =
+    hex_literal::hex!("dcbc1c05240f31ff3ad067ef1ee35ce4997762752e3a095284754544f4c709d7");
+
+pub struct Deposit {
+    pub vault: String,
+    pub sender: String,
+    pub owner: String,
+    pub assets: U256,
+    pub shares: U256,
+    pub log_index: u32,
+    pub ordinal: u64,
+}
+
+/// A vault's first deposit or latest donation as kept in the vault stores.
+pub struct Record {
+    pub block_number: u64,
+    pub account: String,
+    pub assets: U256,
+    pub shares: U256,
+}
+
+pub fn deposit(log: &eth::Log) -> Option<Deposit> {
+    if log.topics.len() != 3 || log.topics.first()? != &DEPOSIT_TOPIC {
+        return None;
+    }
+    Some(Deposit {
+        vault: format!("0x{}", hex::encode(&log.address)),
+        sender: abi::topic_address(log, 1)?,
+        owner: abi::topic_address(log, 2)?,
+        assets: abi::word_u256(&log.data, 0)?,
+        shares: abi::word_u256(&log.data, 1)?,
+        log_index: log.index,
+        ordinal: log.ordinal,
+    })
+}
+
+pub fn first_deposit_key(vault: &str) -> String {
+    format!("first:{}", vault)
+}
+
+pub fn donation_key(vault: &str) -> String {
+    format!("donation:{}", vault)
+}
+
+/// Store value for a record: `block:account:assets:shares`.
+pub fn encode_record(record: &Record) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        record.block_number, record.account, record.assets, record.shares
+    )
+}
+
+pub fn decode_record(value: &str) -> Option<Record> {
+    let mut parts = value.split(':');
+    Some(Record {
+        block_number: parts.next()?.parse().ok()?,
+        account: parts.next()?.to_string(),
+        assets: U256::from_dec_str(parts.next()?).ok()?,
+        shares: U256::from_dec_str(parts.next()?).ok()?,
+    })
+}
+
+/// Inbound transfers to `vault` in a transaction whose sender made no deposit
+/// into it in that transaction, as `(ordinal, donor, amount)`.
+pub fn donations<'a>(
+    logs: &'a [eth::Log],
+    vault: &[u8],
+) -> impl Iterator<Item = (u64, String, U256)> + 'a {
+    let depositors: Vec<String> = logs
+        .iter()
+        .filter(|log| log.address == vault)
+        .filter_map(deposit)
+        .map(|d| d.sender)
+        .collect();
+    let vault = vault.to_vec();
+
+    logs.iter().filter_map(move |log| {
+        let transfer = abi::transfer(log)?;
+        if transfer.to != vault.as_slice() || log.address == vault {
+            return None;
+        }
+        let donor = format!("0x{}", hex::encode(transfer.from));
+        if depositors.contains(&donor) {
+            return None;
+        }
+        Some((log.ordinal, donor, transfer.amount))
+    })
+}
+
+/// The exploited deposit: a later depositor paying in more assets than the
+/// first depositor yet receiving no more shares, after a donation inflated the
+/// share price, all within `window` blocks of the first deposit.
+pub fn exploit(
+    victim: &Deposit,
+    block_number: u64,
+    first: &Record,
+    donation: &Record,
+    window: u64,
+) -> bool {
+    victim.owner != first.account
+        && block_number.saturating_sub(first.block_number) <= window
+        && donation.block_number >= first.block_number
+        && donation.block_number <= block_number
+        && victim.assets > first.assets
+        && victim.shares <= first.shares
+}
+
+pub fn candidate(
+    tx_hash: &[u8],
+    block_number: u64,
+    victim: &Deposit,
+    first: &Record,
+    donation: &Record,
+) -> CandidateEvent {
+    let mut event = CandidateEvent {
+        transaction_hash: format!("0x{}", hex::encode(tx_hash)),
+        block_number,
+        log_index: victim.log_index as u64,
+        contract_address: victim.vault.clone(),
+        event_type: "FirstDepositorExploitCandidate".to_string(),
+        from: first.account.clone(),
+        to: victim.owner.clone(),
+        metadata: format!(
+            "{{\"first_depositor\":\"{}\",\"first_deposit_block\":{},\"first_shares\":\"{}\",\"donor\":\"{}\",\"donation_block\":{},\"donation_amount\":\"{}\",\"victim\":\"{}\",\"victim_assets\":\"{}\",\"victim_shares\":\"{}\"}}",
+            first.account,
+            first.block_number,
+            first.shares,
+            donation.account,
+            donation.block_number,
+            donation.assets,
+            victim.owner,
+            victim.assets,
+            victim.shares
+        ),
+        ..Default::default()
+    };
+    Tag::standard(taxonomy::ERC4626).apply(&mut event);
+    event
+}
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use substreams::store::{StoreGet, StoreGetInt64};
+
+use crate::approvals::Grant;
+use crate::pb::pulseproof::CandidateEvent;
+
+/// Prefix of every key of one baseline window, so a whole window can be
+/// dropped once it is no longer the previous one.
+pub fn window_prefix(window: u64) -> String {
+    format!("approvals:{}:", window)
+}
+
+/// Key summing a token's approvals per block over one window.
+pub fn count_key(window: u64, token: &str) -> String {
+    format!("{}n:{}", window_prefix(window), token)
+}
+
+/// Key summing the squares of a token's approvals per block over one window.
+pub fn square_key(window: u64, token: &str) -> String {
+    format!("{}sq:{}", window_prefix(window), token)
+}
+
+/// Approvals granted on each token in the block.
+pub fn counts(grants: &[Grant]) -> BTreeMap<&str, i64> {
+    let mut counts: BTreeMap<&str, i64> = BTreeMap::new();
+    for grant in grants {
+        *counts.entry(grant.token.as_str()).or_default() += 1;
+    }
+    counts
+}
+
+/// `ApprovalSpike` events for tokens whose approvals this block sit at least
+/// `min_z` standard deviations above their per-block rate over the previous
+/// full window of `window_blocks`, blocks without approvals counting as zero.
+/// Tokens with no variance in the baseline have no z-score and are flagged on
+/// `min_count` alone, which every spike must also reach.
+pub fn spikes(
+    counts: &BTreeMap<&str, i64>,
+    stats: &StoreGetInt64,
+    block_number: u64,
+    window_blocks: u64,
+    min_z: u64,
+    min_count: u64,
+) -> Vec<CandidateEvent> {
+    let window = block_number / window_blocks;
+    // The first window has no baseline yet.
+    let Some(previous) = window.checked_sub(1) else {
+        return Vec::new();
+    };
+
+    counts
+        .iter()
+        .filter(|(_, count)| **count as u64 >= min_count)
+        .filter_map(|(token, count)| {
+            let sum = stats.get_last(count_key(previous, token)).unwrap_or(0) as f64;
+            let squares = stats.get_last(square_key(previous, token)).unwrap_or(0) as f64;
+            let mean = sum / window_blocks as f64;
+            let stddev = (squares / window_blocks as f64 - mean * mean).max(0.0).sqrt();
+            let count = *count as f64;
+
+            let z = (stddev > 0.0).then(|| (count - mean) / stddev);
+            if z.is_some_and(|z| z < min_z as f64) || count <= mean {
+                return None;
+            }
+            Some(CandidateEvent {
+                block_number,
+                contract_address: token.to_string(),
+                event_type: "ApprovalSpike".to_string(),
+                metadata: format!(
+                    "{{\"approvals\":{},\"baseline_mean\":{:.4},\"baseline_stddev\":{:.4},\"z\":{}}}",
+                    count,
+                    mean,
+                    stddev,
+                    z.map(|z| format!("{:.2}", z))
+                        .unwrap_or_else(|| "null".to_string())
+                ),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,62 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::evidence;
+use crate::pb::pulseproof::{CandidateEvent, ReceiptPosition, VerifiableAlert, VerifiableHeader};
+
+fn hex0x(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+pub fn header(blk: &eth::Block) -> VerifiableHeader {
+    let header = blk.header.clone().unwrap_or_default();
+    VerifiableHeader {
+        number: blk.number,
+        hash: hex0x(&blk.hash),
+        parent_hash: hex0x(&header.parent_hash),
+        state_root: hex0x(&header.state_root),
+        transactions_root: hex0x(&header.transactions_root),
+        receipts_root: hex0x(&header.receipt_root),
+        timestamp: header
+            .timestamp
+            .map(|t| t.seconds as u64)
+            .unwrap_or_default(),
+    }
+}
+
+/// Packages `alert` with its block header and receipt position. Alerts
+/// without a transaction, or whose transaction has no receipt (call-derived
+/// logs can't be proven against receipts_root), carry no receipt.
+pub fn package(
+    alert: &CandidateEvent,
+    header: &VerifiableHeader,
+    blk: &eth::Block,
+) -> VerifiableAlert {
+    let receipt = blk
+        .transaction_traces
+        .iter()
+        .find(|tx| hex0x(&tx.hash) == alert.transaction_hash)
+        .and_then(|tx| Some((tx, tx.receipt.as_ref()?)))
+        .map(|(tx, receipt)| ReceiptPosition {
+            tx_index: tx.index,
+            tx_hash: hex0x(&tx.hash),
+            tx_type: tx.r#type as u32,
+            succeeded: tx.status == eth::TransactionTraceStatus::Succeeded as i32,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs_bloom: hex0x(&receipt.logs_bloom),
+            post_state: if receipt.state_root.is_empty() {
+                String::new()
+            } else {
+                hex0x(&receipt.state_root)
+            },
+            logs: receipt.logs.iter().map(evidence::raw_log).collect(),
+            log_matched: receipt.logs.iter().any(|log| {
+                log.index as u64 == alert.log_index && hex0x(&log.address) == alert.contract_address
+            }),
+        });
+
+    VerifiableAlert {
+        alert: Some(alert.clone()),
+        header: Some(header.clone()),
+        receipt,
+    }
+}
@@ -0,0 +1,139 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::taxonomy::{self, Tag};
+use crate::u256::U256;
+use crate::{abi, amount};
+
+// Sablier V2.0 WithdrawFromLockupStream(uint256,address,uint128)
+const SABLIER_V2_0_WITHDRAW_TOPIC: [u8; 32] =
+    hex_literal::hex!("fa54f9f9bdcdd28778cbb9f78490df6691cc4e2729588e10f4cc0a26c465686a");
+// Sablier V2.1+ WithdrawFromLockupStream(uint256,address,address,uint128)
+const SABLIER_V2_WITHDRAW_TOPIC: [u8; 32] =
+    hex_literal::hex!("40b88e5c41c5a97ffb7b6ef88a0a2d505aa0c634cf8a0275cb236ea7dd87ed4d");
+// Sablier V1 WithdrawFromStream(uint256,address,uint256)
+const SABLIER_V1_WITHDRAW_TOPIC: [u8; 32] =
+    hex_literal::hex!("36c3ab437e6a424ed25dc4bfdeb62706aa06558660fab2dab229d2555adaf89c");
+// Team Finance LockToken LogWithdrawal(address,uint256)
+const TEAM_FINANCE_WITHDRAWAL_TOPIC: [u8; 32] =
+    hex_literal::hex!("b4214c8c54fc7442f36d3682f59aebaf09358a4431835b30efb29d52cf9e1e91");
+
+struct Unlock {
+    event_type: &'static str,
+    protocol: Tag,
+    stream_id: Option<U256>,
+    beneficiary: Vec<u8>,
+    asset: Option<Vec<u8>>,
+    amount: U256,
+}
+
+/// Emits `VestingUnlock` for withdrawals from Sablier streams and
+/// `LockExpired` for Team Finance token lock withdrawals. When the event
+/// doesn't name the asset it is taken from the matching Transfer out of the
+/// lock contract; Team Finance withdrawals without one are dropped, since
+/// `LogWithdrawal` is a generic enough name to collide.
+pub fn vesting_events(
+    tx_hash: &[u8],
+    logs: &[eth::Log],
+    block_number: u64,
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    let mut events = Vec::new();
+    for log in logs {
+        let Some(unlock) = decode(log) else {
+            continue;
+        };
+        let asset = unlock
+            .asset
+            .clone()
+            .or_else(|| payout_token(logs, &log.address, &unlock));
+        if asset.is_none() && unlock.event_type == "LockExpired" {
+            continue;
+        }
+        let token = asset
+            .map(|a| format!("0x{}", hex::encode(a)))
+            .unwrap_or_default();
+
+        let stream_id = unlock
+            .stream_id
+            .map(|id| format!("\"stream_id\":\"{}\",", id))
+            .unwrap_or_default();
+        let mut event = CandidateEvent {
+            transaction_hash: format!("0x{}", hex::encode(tx_hash)),
+            block_number,
+            log_index: log.index as u64,
+            contract_address: format!("0x{}", hex::encode(&log.address)),
+            event_type: unlock.event_type.to_string(),
+            to: format!("0x{}", hex::encode(&unlock.beneficiary)),
+            metadata: format!(
+                "{{{}\"token\":\"{}\",{}}}",
+                stream_id,
+                token,
+                amount::json_fields(annotations, "amount", unlock.amount, &token)
+            ),
+            ..Default::default()
+        };
+        unlock.protocol.apply(&mut event);
+        if !token.is_empty() {
+            event.standard = taxonomy::ERC20.to_string();
+        }
+        events.push(event);
+    }
+    events
+}
+
+fn decode(log: &eth::Log) -> Option<Unlock> {
+    let topic0 = log.topics.first()?.as_slice();
+    let topic_bytes = |index: usize| -> Option<Vec<u8>> {
+        abi::address_bytes(log.topics.get(index)?).map(<[u8]>::to_vec)
+    };
+    let stream_id = || abi::topic_u256(log, 1);
+
+    if topic0 == SABLIER_V2_WITHDRAW_TOPIC {
+        Some(Unlock {
+            event_type: "VestingUnlock",
+            protocol: Tag::protocol("sablier", "v2"),
+            stream_id: stream_id(),
+            beneficiary: topic_bytes(2)?,
+            asset: Some(topic_bytes(3)?),
+            amount: abi::word_u256(&log.data, 0)?,
+        })
+    } else if topic0 == SABLIER_V2_0_WITHDRAW_TOPIC || topic0 == SABLIER_V1_WITHDRAW_TOPIC {
+        let version = if topic0 == SABLIER_V1_WITHDRAW_TOPIC {
+            "v1"
+        } else {
+            "v2"
+        };
+        Some(Unlock {
+            event_type: "VestingUnlock",
+            protocol: Tag::protocol("sablier", version),
+            stream_id: stream_id(),
+            beneficiary: topic_bytes(2)?,
+            asset: None,
+            amount: abi::word_u256(&log.data, 0)?,
+        })
+    } else if topic0 == TEAM_FINANCE_WITHDRAWAL_TOPIC && log.topics.len() == 1 {
+        Some(Unlock {
+            event_type: "LockExpired",
+            protocol: Tag::protocol("team-finance", ""),
+            stream_id: None,
+            beneficiary: abi::address_bytes(abi::word(&log.data, 0)?)?.to_vec(),
+            asset: None,
+            amount: abi::word_u256(&log.data, 1)?,
+        })
+    } else {
+        None
+    }
+}
+
+/// The token of the Transfer that paid `unlock` out of `contract`.
+fn payout_token(logs: &[eth::Log], contract: &[u8], unlock: &Unlock) -> Option<Vec<u8>> {
+    logs.iter().find_map(|log| {
+        let transfer = abi::transfer(log)?;
+        (transfer.from == contract
+            && transfer.to == unlock.beneficiary.as_slice()
+            && transfer.amount == unlock.amount)
+            .then(|| log.address.clone())
+    })
+}
@@ -0,0 +1,43 @@
+use substreams_ethereum::pb::eth::v2 as eth;
+
+use crate::amount;
+use crate::annotations::Annotations;
+use crate::pb::pulseproof::CandidateEvent;
+use crate::u256;
+
+/// Emits one `BeaconWithdrawal` per EIP-4895 withdrawal credited to a watched
+/// address. Withdrawals are block-level balance changes with no transaction or
+/// log, so `log_index` carries the change's position in the block's balance
+/// changes, and the fingerprint takes the block number in place of a
+/// transaction hash. Only extended blocks record them.
+pub fn withdrawal_events(
+    blk: &eth::Block,
+    watch: &[String],
+    annotations: &Annotations,
+) -> Vec<CandidateEvent> {
+    blk.balance_changes
+        .iter()
+        .enumerate()
+        .filter(|(_, change)| change.reason == eth::balance_change::Reason::Withdrawal as i32)
+        .filter_map(|(position, change)| {
+            let recipient = format!("0x{}", hex::encode(&change.address));
+            if !watch.contains(&recipient) {
+                return None;
+            }
+            let amount = u256::from_pb(change.new_value.as_ref())
+                .saturating_sub(u256::from_pb(change.old_value.as_ref()));
+
+            Some(CandidateEvent {
+                block_number: blk.number,
+                log_index: position as u64,
+                event_type: "BeaconWithdrawal".to_string(),
+                to: recipient,
+                metadata: format!(
+                    "{{{}}}",
+                    amount::json_fields(annotations, "amount", amount, "native")
+                ),
+                ..Default::default()
+            })
+        })
+        .collect()
+}